@@ -1,7 +1,7 @@
-use clap::{Command, Arg};
+use clap::{Command, Arg, ArgAction};
 use gstreamer as gst;
-use std::net::SocketAddr;
-use switcher::http::Server;
+use std::{net::SocketAddr, path::PathBuf};
+use switcher::http::{config::Config, Server};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -11,6 +11,9 @@ enum RTMPSwitcherError {
 
     #[error("invalid listen address `{0}`")]
     InvalidSocketAddr(String),
+
+    #[error("--check requires --config")]
+    MissingConfig,
 }
 
 #[tokio::main]
@@ -27,7 +30,41 @@ async fn main() -> eyre::Result<()> {
                 .value_name("ADDRESS")
                 .help("sets the server listen address"),
         )
+        .arg(
+            Arg::new("state")
+                .short('s')
+                .long("state")
+                .value_name("FILE")
+                .help("restores mixers and schedules from FILE on startup, saves them on change"),
+        )
+        .arg(
+            Arg::new("config")
+                .short('c')
+                .long("config")
+                .value_name("FILE")
+                .help(
+                    "builds the mixers declared in FILE (toml, yaml or json) on startup, \
+                     ignored when the file given to --state exists",
+                ),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .help("validates the file given to --config and exits"),
+        )
         .get_matches();
+
+    let config = match matches.get_one::<String>("config") {
+        Some(path) => Some(Config::load(&PathBuf::from(path))?),
+        None => None,
+    };
+    if matches.get_flag("check") {
+        config.ok_or(RTMPSwitcherError::MissingConfig)?.validate()?;
+        println!("config is valid");
+        return Ok(());
+    }
+
     let addr: SocketAddr = parse_addr(matches.get_one::<String>("addr").unwrap_or({
         eprintln!("using 0.0.0.0:3030 as addr");
         &String::from("0.0.0.0:3030")
@@ -35,7 +72,22 @@ async fn main() -> eyre::Result<()> {
 
     gst::init().map_err(RTMPSwitcherError::FailedInitGstreamer)?;
 
-    let server = Server::new_with_config(addr);
+    let mut server = Server::new_with_config(addr);
+    let state = matches.get_one::<String>("state").map(PathBuf::from);
+    if let Some(config) = config {
+        // The state file carries on from where the last run left off, the config only
+        // describes how the show starts.
+        match state.as_ref().filter(|state| state.exists()) {
+            Some(state) => tracing::warn!(
+                "state file {} exists, ignoring --config and restoring from it",
+                state.display()
+            ),
+            None => server.config_apply(config).await?,
+        }
+    }
+    if let Some(state) = state {
+        server.state_restore(&state).await?;
+    }
     server.run().await;
 
     Ok(())
@@ -1,18 +1,27 @@
+pub mod config;
 mod filters;
 pub mod input;
 pub mod mixer;
 pub mod output;
+pub mod scene;
+pub mod schedule;
+pub mod snapshot;
+pub mod state;
 
 use crate::{
+    http::config::Config,
     input::Input,
     mixer::{Config as MixerConfig, Error as MixerError, Mixer},
     output::Output,
 };
 use regex::Regex;
+use schedule::{Action, Scheduled};
 use serde::Serialize;
+use state::State;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use thiserror::Error;
@@ -39,6 +48,15 @@ pub enum Error {
 
     #[error("MixerError: '{0}'")]
     Mixer(#[from] MixerError),
+
+    #[error("failed to persist state: '{0}'")]
+    State(String),
+
+    #[error("invalid config: {0}")]
+    Config(String),
+
+    #[error("invalid schedule: {0}")]
+    Schedule(String),
 }
 impl Reject for Error {}
 
@@ -75,11 +93,24 @@ pub async fn recover(err: Rejection) -> Result<impl Reply, Rejection> {
                 Error::InvalidName => StatusCode::BAD_REQUEST,
                 Error::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
                 Error::NotFound => StatusCode::NOT_FOUND,
+                Error::State(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                Error::Config(_) => StatusCode::BAD_REQUEST,
+                Error::Schedule(_) => StatusCode::BAD_REQUEST,
                 Error::Mixer(e) => match e {
                     MixerError::Exists(_, _) => StatusCode::BAD_REQUEST,
                     MixerError::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
                     MixerError::NotFound(_, _) => StatusCode::NOT_FOUND,
-                    MixerError::System(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                    MixerError::BusEmpty(_) => StatusCode::BAD_REQUEST,
+                    MixerError::NotPlaylist(_) => StatusCode::BAD_REQUEST,
+                    MixerError::NoFrame(_) => StatusCode::SERVICE_UNAVAILABLE,
+                    MixerError::InvalidLocation(_, _) => StatusCode::BAD_REQUEST,
+                    MixerError::InvalidOffset(_, _) => StatusCode::BAD_REQUEST,
+                    MixerError::LayoutCapacity(_, _) => StatusCode::BAD_REQUEST,
+                    MixerError::InvalidLayout(_) => StatusCode::BAD_REQUEST,
+                    MixerError::InvalidVideo(_) => StatusCode::BAD_REQUEST,
+                    MixerError::System(_) | MixerError::Teardown(_) => {
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    }
                     MixerError::GstBool(_)
                     | MixerError::GstStateChange(_)
                     | MixerError::Gstreamer(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -105,22 +136,19 @@ impl Server {
     pub fn new_with_config(socket_addr: SocketAddr) -> Self {
         Server {
             socket_addr,
-            mixers: Arc::new(Mutex::new(Mixers {
-                mixers: HashMap::new(),
-            })),
+            mixers: Arc::new(Mutex::new(Mixers::default())),
         }
     }
 
     pub fn new() -> Self {
         Server {
             socket_addr: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 3030)),
-            mixers: Arc::new(Mutex::new(Mixers {
-                mixers: HashMap::new(),
-            })),
+            mixers: Arc::new(Mutex::new(Mixers::default())),
         }
     }
 
     pub async fn run(&self) {
+        tokio::spawn(supervise(Arc::clone(&self.mixers)));
         warp::serve(filters::routes(Arc::clone(&self.mixers)))
             .run(self.socket_addr)
             .await;
@@ -137,6 +165,103 @@ impl Server {
     pub async fn output_add(&mut self, mixer: &str, output: Output) -> Result<(), Error> {
         self.mixers.lock().await.output_add(mixer, output)
     }
+
+    /// Builds the mixers declared in `config`, with their inputs and outputs.
+    pub async fn config_apply(&mut self, config: Config) -> Result<(), Error> {
+        config.validate()?;
+
+        let mut mixers = self.mixers.lock().await;
+        for declaration in config.mixers {
+            let name = declaration.mixer.name.clone();
+            mixers.mixer_create(declaration.mixer.config())?;
+
+            let inputs = fallbacks_first(declaration.inputs, |input| {
+                (
+                    input.name.clone(),
+                    input.failover.as_ref().map(|f| f.input.clone()),
+                )
+            });
+            for input in inputs {
+                mixers.input_add(&name, input.create()?)?;
+            }
+            for output in declaration.outputs {
+                mixers.output_add(&name, output.create()?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds the mixers from the state file at `path`, if it exists, and keeps writing the
+    /// state of the mixers to it whenever it changes from then on. Scheduled actions that were
+    /// due more than [`schedule::MISSED_GRACE_MS`] before the restore are dropped.
+    pub async fn state_restore(&mut self, path: &Path) -> Result<(), Error> {
+        let mut mixers = self.mixers.lock().await;
+        if path.exists() {
+            mixers.restore(State::load(path)?);
+        }
+        mixers.state_path = Some(path.to_path_buf());
+        mixers.persist()
+    }
+}
+
+/// Runs the periodic housekeeping of every mixer, such as failing over inputs and running
+/// scheduled actions.
+async fn supervise(mixers: Arc<Mutex<Mixers>>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(250));
+    loop {
+        interval.tick().await;
+
+        let changed = {
+            let mut mixers = mixers.lock().await;
+            for (name, mixer) in mixers.mixers.iter_mut() {
+                if let Err(e) = mixer.tick() {
+                    tracing::warn!("{}: tick failed: {}", name, e);
+                }
+            }
+            mixers.schedule_run(schedule::now());
+            mixers.state_changed()
+        };
+
+        // The state file is written with the mixers unlocked.
+        if let Some((path, state)) = changed {
+            let saved = state.clone();
+            match tokio::task::spawn_blocking(move || state.save(&path)).await {
+                Ok(Ok(())) => mixers.lock().await.persisted = Some(saved),
+                Ok(Err(e)) => tracing::warn!("{}", e),
+                Err(e) => tracing::warn!("writing the state file failed: {}", e),
+            }
+        }
+    }
+}
+
+/// Orders inputs so that each comes after the input it fails over to, which must exist by the
+/// time it is added. Inputs whose fallback is missing, or that fail over in a cycle, go last.
+fn fallbacks_first<T, F>(mut pending: Vec<T>, names: F) -> Vec<T>
+where
+    F: Fn(&T) -> (String, Option<String>),
+{
+    let mut ordered = Vec::with_capacity(pending.len());
+    let mut added = HashSet::new();
+    while !pending.is_empty() {
+        let (ready, waiting): (Vec<T>, Vec<T>) =
+            pending.into_iter().partition(|input| match names(input).1 {
+                Some(fallback) => added.contains(&fallback),
+                None => true,
+            });
+        if ready.is_empty() {
+            ordered.extend(waiting);
+            break;
+        }
+
+        for input in ready {
+            added.insert(names(&input).0);
+            ordered.push(input);
+        }
+        pending = waiting;
+    }
+
+    ordered
 }
 
 impl Default for Server {
@@ -145,8 +270,17 @@ impl Default for Server {
     }
 }
 
+#[derive(Default)]
 pub struct Mixers {
     pub mixers: HashMap<String, Mixer>,
+    /// File the state of the mixers is written to, see [`state`](state/index.html).
+    state_path: Option<PathBuf>,
+    /// The state last written to `state_path`.
+    persisted: Option<State>,
+    /// Actions waiting to run, by mixer.
+    schedules: HashMap<String, Vec<Scheduled>>,
+    /// Id of the next scheduled action.
+    schedule_id: u64,
 }
 
 impl Mixers {
@@ -176,6 +310,15 @@ impl Mixers {
         Ok(())
     }
 
+    /// Tears down the mixer named `name` and removes it.
+    pub fn mixer_remove(&mut self, name: &str) -> Result<(), Error> {
+        let mut mixer = self.mixers.remove(name).ok_or(Error::NotFound)?;
+        self.schedules.remove(name);
+
+        mixer.stop()?;
+        Ok(())
+    }
+
     pub fn input_add(&mut self, mixer: &str, input: Input) -> Result<(), Error> {
         match self.mixers.get_mut(mixer) {
             Some(m) => m.input_add(input).map_err(Error::Mixer),
@@ -206,6 +349,231 @@ impl Mixers {
         mixer.output_remove(output)?;
         Ok(())
     }
+
+    /// Schedules `action` to run on `mixer` at `at`, in milliseconds since the unix epoch. Actions
+    /// scheduled in the past run right away. Scheduled actions are only kept across restarts
+    /// when the server saves its state, see [`Server::state_restore`].
+    pub fn schedule_add(
+        &mut self,
+        mixer: &str,
+        at: u64,
+        action: Action,
+    ) -> Result<Scheduled, Error> {
+        if !self.mixers.contains_key(mixer) {
+            return Err(Error::NotFound);
+        }
+
+        self.schedule_id += 1;
+        let scheduled = Scheduled {
+            id: self.schedule_id,
+            at,
+            action,
+        };
+        self.schedules
+            .entry(mixer.to_string())
+            .or_default()
+            .push(scheduled.clone());
+        Ok(scheduled)
+    }
+
+    /// Returns the actions scheduled on `mixer`, in the order they run.
+    pub fn schedule(&self, mixer: &str) -> Result<Vec<Scheduled>, Error> {
+        if !self.mixers.contains_key(mixer) {
+            return Err(Error::NotFound);
+        }
+
+        let mut schedule = self.schedules.get(mixer).cloned().unwrap_or_default();
+        schedule.sort_by_key(|scheduled| (scheduled.at, scheduled.id));
+        Ok(schedule)
+    }
+
+    pub fn schedule_remove(&mut self, mixer: &str, id: u64) -> Result<(), Error> {
+        let schedule = self.schedules.get_mut(mixer).ok_or(Error::NotFound)?;
+        let index = schedule
+            .iter()
+            .position(|scheduled| scheduled.id == id)
+            .ok_or(Error::NotFound)?;
+
+        schedule.remove(index);
+        Ok(())
+    }
+
+    pub fn schedule_clear(&mut self, mixer: &str) -> Result<(), Error> {
+        if !self.mixers.contains_key(mixer) {
+            return Err(Error::NotFound);
+        }
+
+        self.schedules.remove(mixer);
+        Ok(())
+    }
+
+    /// Runs the actions that are due at `now`, in milliseconds since the unix epoch. Failed
+    /// actions are logged and dropped.
+    pub fn schedule_run(&mut self, now: u64) {
+        let mut due = Vec::new();
+        for (mixer, schedule) in self.schedules.iter_mut() {
+            let (ready, waiting) = schedule
+                .drain(..)
+                .partition(|scheduled| scheduled.at <= now);
+            *schedule = waiting;
+            due.extend(
+                ready
+                    .into_iter()
+                    .map(|scheduled| (mixer.clone(), scheduled)),
+            );
+        }
+        due.sort_by_key(|(_, scheduled)| (scheduled.at, scheduled.id));
+
+        for (mixer, scheduled) in due {
+            tracing::info!("{}: running scheduled action {}", mixer, scheduled.id);
+            if let Err(e) = scheduled.action.run(self, &mixer) {
+                tracing::warn!("{}: scheduled action {} failed: {}", mixer, scheduled.id, e);
+            }
+        }
+    }
+
+    /// Rebuilds the mixers described by `state`. Parts that fail to rebuild are logged and
+    /// skipped, so one broken input does not keep the rest of the production down.
+    pub fn restore(&mut self, state: State) {
+        for mixer_state in state.mixers {
+            let name = mixer_state.config.name.clone();
+            if let Err(e) = self.mixer_create(mixer_state.config) {
+                tracing::warn!("{}: failed to restore mixer: {}", name, e);
+                continue;
+            }
+
+            let inputs = fallbacks_first(mixer_state.inputs, |input| {
+                let failover = input.config.failover.as_ref();
+                (input.config.name.clone(), failover.map(|f| f.input.clone()))
+            });
+            for mut input in inputs {
+                let input_name = input.config.name.clone();
+                if input.encrypted {
+                    let var = state::passphrase_var(&name, &input_name);
+                    match std::env::var(&var) {
+                        Ok(passphrase) => {
+                            if let Some(srt) = input.config.srt.as_mut() {
+                                srt.passphrase = Some(passphrase);
+                            }
+                        }
+                        Err(_) => {
+                            tracing::warn!(
+                                "{}: not restoring input {}, set {} to its passphrase",
+                                name,
+                                input_name,
+                                var
+                            );
+                            continue;
+                        }
+                    }
+                }
+
+                // Inputs can not fail over to an input that was not restored.
+                let fallback = input.config.failover.as_ref().map(|f| f.input.clone());
+                if let Some(fallback) = fallback {
+                    if !self.mixers[&name].inputs.contains_key(&fallback) {
+                        tracing::warn!(
+                            "{}: restoring input {} without failover, {} was not restored",
+                            name,
+                            input_name,
+                            fallback
+                        );
+                        input.config.failover = None;
+                    }
+                }
+                let result = input::create(&input.input_type, input.config, &input.location)
+                    .and_then(|i| self.input_add(&name, i));
+                if let Err(e) = result {
+                    tracing::warn!("{}: failed to restore input {}: {}", name, input_name, e);
+                }
+            }
+
+            for output in mixer_state.outputs {
+                let output_name = output.config.name.clone();
+                let result = output::create(&output.output_type, output.config, &output.location)
+                    .and_then(|o| self.output_add(&name, o));
+                if let Err(e) = result {
+                    tracing::warn!("{}: failed to restore output {}: {}", name, output_name, e);
+                }
+            }
+
+            if !mixer_state.schedule.is_empty() {
+                let last = mixer_state.schedule.iter().map(|s| s.scheduled.id).max();
+                self.schedule_id = self.schedule_id.max(last.unwrap_or(0));
+
+                // Actions that should have run long before the restart are stale by now.
+                let cutoff = schedule::now().saturating_sub(schedule::MISSED_GRACE_MS);
+                let (missed, waiting): (Vec<_>, Vec<_>) = mixer_state
+                    .schedule
+                    .into_iter()
+                    .filter_map(|saved| saved.restore(&name))
+                    .partition(|scheduled| scheduled.at < cutoff);
+                for scheduled in missed {
+                    tracing::warn!(
+                        "{}: dropping scheduled action {} missed at {}",
+                        name,
+                        scheduled.id,
+                        scheduled.at
+                    );
+                }
+                if !waiting.is_empty() {
+                    self.schedules.insert(name.clone(), waiting);
+                }
+            }
+
+            let mixer = match self.mixers.get_mut(&name) {
+                Some(mixer) => mixer,
+                None => continue,
+            };
+            for scene in mixer_state.scenes {
+                let scene_name = scene.name.clone();
+                if let Err(e) = mixer.scene_add(scene) {
+                    tracing::warn!("{}: failed to restore scene {}: {}", name, scene_name, e);
+                }
+            }
+            for (input, route) in mixer_state.audio_routes {
+                if let Err(e) = mixer.input_set_audio_route(&input, route) {
+                    tracing::warn!(
+                        "{}: failed to restore audio route of {}: {}",
+                        name,
+                        input,
+                        e
+                    );
+                }
+            }
+            if let Some(active) = mixer_state.active {
+                if let Err(e) = mixer.input_set_active(&active) {
+                    tracing::warn!("{}: failed to restore program {}: {}", name, active, e);
+                }
+            }
+            if let Some(preview) = mixer_state.preview {
+                if let Err(e) = mixer.input_set_preview(&preview) {
+                    tracing::warn!("{}: failed to restore preview {}: {}", name, preview, e);
+                }
+            }
+        }
+    }
+
+    /// The state of the mixers and the state file to write it to, if one is set and the state
+    /// changed since it was last written.
+    fn state_changed(&self) -> Option<(PathBuf, State)> {
+        let path = self.state_path.clone()?;
+        let state = State::capture(self);
+        if self.persisted.as_ref() == Some(&state) {
+            return None;
+        }
+        Some((path, state))
+    }
+
+    /// Writes the state of the mixers to the state file, if one is set and the state changed
+    /// since it was last written.
+    pub fn persist(&mut self) -> Result<(), Error> {
+        if let Some((path, state)) = self.state_changed() {
+            state.save(&path)?;
+            self.persisted = Some(state);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -213,14 +581,32 @@ mod tests {
     use super::*;
     use crate::{
         http::{
-            input::CreateRequest as InputCreateRequest, mixer::CreateRequest as MixerCreateRequest,
+            input::{
+                AudioRouteRequest, CreateRequest as InputCreateRequest, Input as InputResponse,
+                PlaylistItemRequest, UpdateRequest as InputUpdateRequest,
+            },
+            mixer::{
+                BackgroundRequest, CreateRequest as MixerCreateRequest, LayoutRequest, TakeRequest,
+                TransitionRequest, UpdateRequest as MixerUpdateRequest, VideoUpdate,
+            },
             output::CreateRequest as OutputCreateRequest,
+            scene::CreateRequest as SceneCreateRequest,
+            schedule::CreateRequest as ScheduleCreateRequest,
+        },
+        input::{
+            playlist::Status as PlaylistStatus, rtmp::Status as RTMPStatus, FailoverConfig, Input,
+            PlaylistConfig, RTMPConfig, Recovery, SRTConfig, SRTMode,
+        },
+        mixer::{
+            layout::{Corner, Geometry},
+            transition::Direction,
+            AudioMode, AudioRoute, AudioStatus, Background, Event, HealthState, Layout, LayoutType,
+            Levels, Pattern, Scene, SceneInput, Stats, Tally, TransitionType,
         },
-        input::Input,
-        mixer,
-        output::{Config as OutputConfig, EncoderConfig, Output},
-        AudioConfig, VideoConfig,
+        output::{multiview::Sink, Bus, Config as OutputConfig, EncoderConfig, Output},
+        AudioChannels, AudioConfig, AudioFormat, AudioMixConfig, AudioRate, VideoConfig,
     };
+    use gst::prelude::*;
     use warp::{http::StatusCode, test::request};
 
     fn setup_server() -> Server {
@@ -233,6 +619,30 @@ mod tests {
         }
     }
 
+    /// Writes `seconds` of silent mono WAV to the temporary directory and returns its URI.
+    fn silence_uri(name: &str, seconds: u32) -> String {
+        let rate: u32 = 8000;
+        let data = rate * 2 * seconds;
+        let mut wav = Vec::with_capacity(44 + data as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&rate.to_le_bytes());
+        wav.extend_from_slice(&(rate * 2).to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data.to_le_bytes());
+        wav.resize(44 + data as usize, 0);
+
+        let path = std::env::temp_dir().join(format!("switcher_{}.wav", name));
+        std::fs::write(&path, wav).expect("failed to write wav");
+        format!("file://{}", path.display())
+    }
+
     #[tokio::test]
     async fn test_mixer_create() {
         let server = setup_server();
@@ -245,6 +655,8 @@ mod tests {
                 name: "test_mixer_create".to_string(),
                 video: VideoConfig::default(),
                 audio: AudioConfig::default(),
+                audio_mix: AudioMixConfig::default(),
+                background: Background::default(),
             })
             .reply(&api)
             .await;
@@ -253,6 +665,52 @@ mod tests {
         assert_eq!(1, server.mixers.lock().await.mixers.len());
     }
 
+    #[tokio::test]
+    async fn test_mixer_create_audio_format() {
+        let server = setup_server();
+        let api = filters::mixer_create(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers")
+            .json(&MixerCreateRequest {
+                name: "test_mixer_create_audio_format".to_string(),
+                video: VideoConfig::default(),
+                audio: AudioConfig::default(),
+                audio_mix: AudioMixConfig {
+                    rate: AudioRate::Hz44100,
+                    channels: AudioChannels::Surround51,
+                    format: AudioFormat::F32LE,
+                },
+                background: Background::default(),
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get("test_mixer_create_audio_format").unwrap();
+        let caps = mixer.config().audio_mix.caps();
+        let structure = caps.structure(0).expect("caps without a structure");
+        assert_eq!(Ok(44100), structure.get::<i32>("rate"));
+        assert_eq!(Ok(6), structure.get::<i32>("channels"));
+        assert_eq!(Ok("F32LE"), structure.get::<&str>("format"));
+        drop(mixers);
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers")
+            .json(&serde_json::json!({
+                "name": "test_mixer_create_audio_rate",
+                "audio_mix": { "rate": 22050 },
+            }))
+            .reply(&api)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn test_mixer_list() {
         let mut server = setup_server();
@@ -260,6 +718,7 @@ mod tests {
             name: "test_mixer_list".to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
@@ -280,6 +739,7 @@ mod tests {
             name: "test_mixer_get".to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
@@ -298,156 +758,302 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_mixer_debug() {
+    async fn test_mixer_update() {
+        let mixer_name = "test_mixer_update";
         let mut server = setup_server();
         let config = MixerConfig {
-            name: "test_mixer_debug".to_string(),
+            name: mixer_name.to_string(),
             audio: AudioConfig::default(),
-            video: VideoConfig::default(),
+            video: VideoConfig {
+                width: 1280,
+                height: 720,
+                ..VideoConfig::default()
+            },
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
-        let api = filters::mixer_debug(Arc::clone(&server.mixers));
+
+        let input_config = crate::input::Config {
+            name: "inset".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig {
+                xpos: 640,
+                ypos: 360,
+                width: 640,
+                height: 360,
+                ..VideoConfig::default()
+            },
+            record: false,
+            ..Default::default()
+        };
+        server
+            .input_add(
+                mixer_name,
+                Input::create_test(input_config).expect("failed to create test input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::mixer_update(Arc::clone(&server.mixers));
 
         let resp = request()
-            .method("GET")
-            .path("/mixers/test_mixer_debug/debug")
+            .method("PUT")
+            .path("/mixers/test_mixer_update")
+            .json(&MixerUpdateRequest {
+                video: VideoUpdate {
+                    width: Some(1920),
+                    height: Some(1080),
+                    framerate: Some(25),
+                    ..VideoUpdate::default()
+                },
+            })
             .reply(&api)
             .await;
 
         assert_eq!(StatusCode::OK, resp.status());
-        assert_ne!(resp.body().len(), 0);
+
+        {
+            let mixers = server.mixers.lock().await;
+            let mixer = mixers.mixers.get(mixer_name).unwrap();
+            let video = mixer.config().video;
+            assert_eq!(
+                (1920, 1080, 25),
+                (video.width, video.height, video.framerate)
+            );
+            let inset = mixer.inputs.get("inset").unwrap().config().video;
+            assert_eq!((960, 540), (inset.xpos, inset.ypos));
+            assert_eq!((960, 540), (inset.width, inset.height));
+        }
+
+        // Fields that are left out keep their value.
+        let resp = request()
+            .method("PUT")
+            .path("/mixers/test_mixer_update")
+            .json(&serde_json::json!({ "video": { "framerate": 50 } }))
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let resp = request()
+            .method("PUT")
+            .path("/mixers/test_mixer_update")
+            .json(&MixerUpdateRequest {
+                video: VideoUpdate {
+                    width: Some(0),
+                    ..VideoUpdate::default()
+                },
+            })
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+
+        let mixers = server.mixers.lock().await;
+        let video = mixers.mixers.get(mixer_name).unwrap().config().video;
+        assert_eq!(
+            (1920, 1080, 50),
+            (video.width, video.height, video.framerate)
+        );
     }
 
     #[tokio::test]
-    async fn test_input_list() {
+    async fn test_mixer_remove() {
+        let mixer_name = "test_mixer_remove";
         let mut server = setup_server();
         let config = MixerConfig {
-            name: "test_input_list".to_string(),
+            name: mixer_name.to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
-        let api = filters::input_list(Arc::clone(&server.mixers));
+
+        let input_config = crate::input::Config {
+            name: "test".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+
+        server
+            .input_add(
+                mixer_name,
+                Input::create_test(input_config).expect("failed to create test input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::mixer_remove(Arc::clone(&server.mixers));
 
         let resp = request()
-            .method("GET")
-            .path("/mixers/test_input_list/inputs")
+            .method("DELETE")
+            .path("/mixers/test_mixer_remove")
             .reply(&api)
             .await;
 
         assert_eq!(StatusCode::OK, resp.status());
-        assert_ne!(resp.body().len(), 0);
+        assert_eq!(0, server.mixers.lock().await.mixers.len());
+
+        let resp = request()
+            .method("DELETE")
+            .path("/mixers/test_mixer_remove")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
     }
 
     #[tokio::test]
-    async fn test_input_add() {
+    async fn test_mixer_tally() {
+        let mixer_name = "test_mixer_tally";
         let mut server = setup_server();
         let config = MixerConfig {
-            name: "test_input_add".to_string(),
+            name: mixer_name.to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
-        let api = filters::input_add(Arc::clone(&server.mixers).clone());
 
-        let resp = request()
-            .method("POST")
-            .path("/mixers/test_input_add/inputs")
-            .json(&InputCreateRequest {
-                name: "test".to_string(),
-                input_type: "URI".to_string(),
-                location: "http://nowhere".to_string(),
+        for name in &["first", "second"] {
+            let input_config = crate::input::Config {
+                name: name.to_string(),
                 audio: AudioConfig::default(),
                 video: VideoConfig::default(),
                 record: false,
-            })
+                ..Default::default()
+            };
+            server
+                .input_add(
+                    mixer_name,
+                    Input::create_fake(input_config).expect("failed to create fakesrc"),
+                )
+                .await
+                .expect("Failed to add input");
+        }
+
+        let mut events = {
+            let mut mixers = server.mixers.lock().await;
+            let mixer = mixers.mixers.get_mut(mixer_name).unwrap();
+            let events = mixer.subscribe();
+            mixer
+                .input_set_active("first")
+                .expect("failed to set active input");
+            mixer
+                .input_set_preview("second")
+                .expect("failed to set preview input");
+            events
+        };
+
+        let api = filters::mixer_tally(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_mixer_tally/tally")
             .reply(&api)
             .await;
+        assert_eq!(StatusCode::OK, resp.status());
 
-        assert_eq!(resp.status(), StatusCode::CREATED);
-        assert_eq!(
-            1,
-            server
-                .mixers
-                .lock()
-                .await
-                .mixers
-                .get("test_input_add")
-                .unwrap()
-                .inputs
-                .len()
-        );
+        let tally: Vec<Tally> = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(2, tally.len());
+        assert_eq!("first", tally[0].input);
+        assert!(tally[0].program);
+        assert!(!tally[0].preview);
+        assert_eq!("second", tally[1].input);
+        assert!(!tally[1].program);
+        assert!(tally[1].preview);
+
+        let mut pushed = None;
+        while let Ok(event) = events.try_recv() {
+            if let Event::TallyChanged { tally } = event {
+                pushed = Some(tally);
+            }
+        }
+        assert_eq!(Some(tally), pushed);
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/missing/tally")
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
     }
 
     #[tokio::test]
-    async fn test_input_get() {
-        let mixer_name = "test_input_get";
+    async fn test_mixer_stats() {
+        let mixer_name = "test_mixer_stats";
         let mut server = setup_server();
         let config = MixerConfig {
             name: mixer_name.to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
 
-        let input_config = crate::input::Config {
-            name: "fakesrc".to_string(),
-            audio: AudioConfig::default(),
-            video: VideoConfig::default(),
-            record: false,
-        };
-
-        server
-            .input_add(
-                mixer_name,
-                Input::create_fake(input_config).expect("failed to create fakesrc"),
-            )
-            .await
-            .expect("Failed to add input");
+        let api = filters::mixer_stats(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_mixer_stats/stats")
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
 
-        let api = filters::input_get(Arc::clone(&server.mixers));
+        let stats: Stats = serde_json::from_slice(resp.body()).expect("failed to parse stats");
+        assert!(stats
+            .queues
+            .iter()
+            .any(|queue| queue.element == "mixer_test_mixer_stats_video_queue"));
 
         let resp = request()
             .method("GET")
-            .path("/mixers/test_input_get/inputs/fakesrc")
+            .path("/mixers/missing/stats")
             .reply(&api)
             .await;
-
-        assert_eq!(StatusCode::OK, resp.status());
-        assert_ne!(resp.body().len(), 0);
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
     }
 
     #[tokio::test]
-    async fn test_input_remove() {
-        let mixer_name = "test_input_remove";
+    async fn test_mixer_events() {
+        let mixer_name = "test_mixer_events";
         let mut server = setup_server();
         let config = MixerConfig {
             name: mixer_name.to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
 
+        let mut events = server
+            .mixers
+            .lock()
+            .await
+            .mixers
+            .get(mixer_name)
+            .unwrap()
+            .subscribe();
+
         let input_config = crate::input::Config {
             name: "fakesrc".to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
             record: false,
+            ..Default::default()
         };
 
         server
@@ -458,46 +1064,106 @@ mod tests {
             .await
             .expect("Failed to add input");
 
-        let api = filters::input_remove(Arc::clone(&server.mixers));
+        server
+            .mixers
+            .lock()
+            .await
+            .mixers
+            .get_mut(mixer_name)
+            .unwrap()
+            .input_set_active("fakesrc")
+            .expect("failed to set active input");
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert!(received.contains(&Event::InputAdded {
+            input: "fakesrc".to_string()
+        }));
+        assert!(received.contains(&Event::ProgramChanged {
+            input: Some("fakesrc".to_string())
+        }));
+
+        let api = filters::mixer_events(Arc::clone(&server.mixers));
 
         let resp = request()
-            .method("DELETE")
-            .path("/mixers/test_input_remove/inputs/fakesrc")
+            .method("GET")
+            .path("/mixers/missing/events")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
+    }
+
+    #[tokio::test]
+    async fn test_mixer_levels() {
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: "test_mixer_levels".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+        let api = filters::mixer_levels(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_mixer_levels/levels")
             .reply(&api)
             .await;
 
         assert_eq!(StatusCode::OK, resp.status());
-        assert_eq!(
-            0,
-            server
-                .mixers
+        let _: Levels = serde_json::from_slice(resp.body()).expect("failed to parse levels");
+
+        let mixers = Arc::clone(&server.mixers);
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            mixers
                 .lock()
                 .await
-                .mixers
-                .get(mixer_name)
-                .unwrap()
-                .inputs
-                .len()
-        );
+                .mixer_remove("test_mixer_levels")
+                .expect("failed to remove mixer");
+        });
+
+        // The stream ends, and the whole body arrives, once the mixer is removed.
+        let api = filters::mixer_levels_stream(Arc::clone(&server.mixers));
+        let resp = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            request()
+                .method("GET")
+                .path("/mixers/test_mixer_levels/levels/stream")
+                .reply(&api),
+        )
+        .await
+        .expect("levels stream did not end");
+
+        assert_eq!(StatusCode::OK, resp.status());
+        assert!(String::from_utf8_lossy(resp.body()).contains("data:"));
     }
 
     #[tokio::test]
-    async fn test_output_list() {
+    async fn test_mixer_debug() {
         let mut server = setup_server();
         let config = MixerConfig {
-            name: "test_output_list".to_string(),
+            name: "test_mixer_debug".to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
-        let api = filters::output_list(Arc::clone(&server.mixers));
+        let api = filters::mixer_debug(Arc::clone(&server.mixers));
 
         let resp = request()
             .method("GET")
-            .path("/mixers/test_output_list/outputs")
+            .path("/mixers/test_mixer_debug/debug")
             .reply(&api)
             .await;
 
@@ -506,140 +1172,2267 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_output_add() {
+    async fn test_mixer_transition() {
+        let mixer_name = "test_mixer_transition";
         let mut server = setup_server();
         let config = MixerConfig {
-            name: "test_output_add".to_string(),
+            name: mixer_name.to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
-        let api = filters::output_add(Arc::clone(&server.mixers));
+
+        let input_config = crate::input::Config {
+            name: "fakesrc".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+
+        server
+            .input_add(
+                mixer_name,
+                Input::create_fake(input_config).expect("failed to create fakesrc"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::mixer_transition(Arc::clone(&server.mixers));
 
         let resp = request()
             .method("POST")
-            .path("/mixers/test_output_add/outputs")
-            .json(&OutputCreateRequest {
-                name: "test".to_string(),
-                output_type: "Fake".to_string(),
-                location: "http://nowhere".to_string(),
-                audio: AudioConfig::default(),
-                video: VideoConfig::default(),
-                encoder: EncoderConfig::default(),
+            .path("/mixers/test_mixer_transition/transition")
+            .json(&TransitionRequest {
+                input: "fakesrc".to_string(),
+                transition_type: TransitionType::Crossfade,
+                duration_ms: 100,
+                direction: Direction::default(),
             })
             .reply(&api)
             .await;
 
-        assert_eq!(resp.status(), StatusCode::CREATED);
-        assert_eq!(
-            1,
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_mixer_transition/transition")
+            .json(&TransitionRequest {
+                input: "fakesrc".to_string(),
+                transition_type: TransitionType::Push,
+                duration_ms: 100,
+                direction: Direction::Up,
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_mixer_transition/transition")
+            .json(&TransitionRequest {
+                input: "fakesrc".to_string(),
+                transition_type: TransitionType::Wipe,
+                duration_ms: 100,
+                direction: Direction::Left,
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_mixer_transition/transition")
+            .json(&TransitionRequest {
+                input: "missing".to_string(),
+                transition_type: TransitionType::Crossfade,
+                duration_ms: 100,
+                direction: Direction::default(),
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
+
+        // Transitions without a duration take the default duration instead of cutting.
+        let transition: TransitionRequest =
+            serde_json::from_str(r#"{"input": "fakesrc", "transition_type": "Crossfade"}"#)
+                .expect("failed to parse transition");
+        assert_eq!(1000, transition.duration_ms);
+    }
+
+    #[tokio::test]
+    async fn test_mixer_cut() {
+        let mixer_name = "test_mixer_cut";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let api = filters::mixer_cut(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_mixer_cut/cut")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+
+        for name in &["first", "second"] {
+            let input_config = crate::input::Config {
+                name: name.to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                record: false,
+                ..Default::default()
+            };
+
             server
-                .mixers
-                .lock()
+                .input_add(
+                    mixer_name,
+                    Input::create_test(input_config).expect("failed to create test input"),
+                )
                 .await
-                .mixers
-                .get("test_output_add")
-                .unwrap()
-                .outputs
-                .len()
-        );
+                .expect("Failed to add input");
+        }
+
+        {
+            let mut mixers = server.mixers.lock().await;
+            let mixer = mixers.mixers.get_mut(mixer_name).unwrap();
+            mixer.input_set_active("first").unwrap();
+            mixer.input_set_preview("second").unwrap();
+        }
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_mixer_cut/cut")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get(mixer_name).unwrap();
+        assert_eq!(Some("second".to_string()), mixer.program());
+        assert_eq!(Some("first".to_string()), mixer.preview());
     }
 
     #[tokio::test]
-    async fn test_output_get() {
-        let mixer_name = "test_output_get";
+    async fn test_mixer_take() {
+        let mixer_name = "test_mixer_take";
         let mut server = setup_server();
         let config = MixerConfig {
             name: mixer_name.to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
 
-        let output_config = OutputConfig {
-            name: "fake".to_string(),
+        let input_config = crate::input::Config {
+            name: "fakesrc".to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
-            encoder: EncoderConfig::default(),
-            mux: None,
+            record: false,
+            ..Default::default()
         };
 
         server
-            .output_add(
+            .input_add(
                 mixer_name,
-                Output::create_fake(output_config).expect("failed to create fake output"),
+                Input::create_fake(input_config).expect("failed to create fakesrc"),
             )
             .await
-            .expect("Failed to add output");
+            .expect("Failed to add input");
 
-        let api = filters::output_get(Arc::clone(&server.mixers));
+        let api = filters::input_set_preview(Arc::clone(&server.mixers));
 
         let resp = request()
-            .method("GET")
-            .path("/mixers/test_output_get/outputs/fake")
+            .method("POST")
+            .path("/mixers/test_mixer_take/set_preview_input/fakesrc")
             .reply(&api)
             .await;
 
         assert_eq!(StatusCode::OK, resp.status());
-        assert_ne!(resp.body().len(), 0);
+
+        let api = filters::mixer_take(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_mixer_take/take")
+            .json(&TakeRequest {
+                transition_type: TransitionType::Crossfade,
+                duration_ms: 100,
+                direction: Direction::default(),
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get(mixer_name).unwrap();
+        assert_eq!(Some("fakesrc".to_string()), mixer.program());
+        assert_eq!(None, mixer.preview());
     }
 
     #[tokio::test]
-    async fn test_output_remove() {
-        let mixer_name = "test_output_remove";
+    async fn test_mixer_layout() {
+        use gst::prelude::*;
+
+        let mixer_name = "test_mixer_layout";
         let mut server = setup_server();
         let config = MixerConfig {
             name: mixer_name.to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
+            ..Default::default()
         };
         server
             .mixer_create(config)
             .await
             .expect("failed to create mixer");
 
-        let output_config = OutputConfig {
-            name: "fake".to_string(),
+        for name in ["main", "inset"].iter() {
+            let input_config = crate::input::Config {
+                name: name.to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                record: false,
+                ..Default::default()
+            };
+
+            server
+                .input_add(
+                    mixer_name,
+                    Input::create_test(input_config).expect("failed to create test input"),
+                )
+                .await
+                .expect("Failed to add input");
+        }
+
+        let api = filters::mixer_layout(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_mixer_layout/layout")
+            .json(&LayoutRequest {
+                layout: Layout {
+                    layout_type: LayoutType::PictureInPicture,
+                    corner: Corner::TopLeft,
+                    margin: 10,
+                    scale: 0.5,
+                },
+                inputs: vec!["main".to_string(), "inset".to_string()],
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+
+        // The layout is stacked above the input on program.
+        {
+            let mixers = server.mixers.lock().await;
+            let mixer = mixers.mixers.get(mixer_name).unwrap();
+            let zorder = |name: &str| {
+                let pad = mixer.inputs.get(name).unwrap().compositor_pad().unwrap();
+                pad.property::<u32>("zorder")
+            };
+            assert_eq!(1001, zorder("main"));
+            assert_eq!(1002, zorder("inset"));
+        }
+
+        let layout = Layout {
+            layout_type: LayoutType::PictureInPicture,
+            corner: Corner::TopLeft,
+            margin: 10,
+            scale: 0.5,
+        };
+        let video = VideoConfig::default();
+        assert_eq!(
+            Geometry {
+                xpos: 10,
+                ypos: 10,
+                width: video.width / 2,
+                height: video.height / 2,
+            },
+            layout.geometry(2, video.width, video.height)[1]
+        );
+
+        for (scale, margin) in [(0.0, 10), (1.5, 10), (0.5, -10)].iter() {
+            let resp = request()
+                .method("POST")
+                .path("/mixers/test_mixer_layout/layout")
+                .json(&LayoutRequest {
+                    layout: Layout {
+                        scale: *scale,
+                        margin: *margin,
+                        ..layout.clone()
+                    },
+                    inputs: vec!["main".to_string(), "inset".to_string()],
+                })
+                .reply(&api)
+                .await;
+
+            assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mixer_background() {
+        let mixer_name = "test_mixer_background";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
             audio: AudioConfig::default(),
             video: VideoConfig::default(),
-            encoder: EncoderConfig::default(),
-            mux: None,
+            background: Background::None,
+            ..Default::default()
         };
-
         server
-            .output_add(
-                mixer_name,
-                Output::create_fake(output_config).expect("failed to create fake output"),
-            )
+            .mixer_create(config)
             .await
-            .expect("Failed to add output");
-
-        let api = filters::output_remove(Arc::clone(&server.mixers));
+            .expect("failed to create mixer");
+        let api = filters::mixer_background(Arc::clone(&server.mixers));
 
         let resp = request()
-            .method("DELETE")
-            .path("/mixers/test_output_remove/outputs/fake")
+            .method("PUT")
+            .path("/mixers/test_mixer_background/background")
+            .json(&BackgroundRequest {
+                background: Background::Pattern {
+                    pattern: Pattern::Smpte,
+                },
+            })
             .reply(&api)
             .await;
+        assert_eq!(StatusCode::OK, resp.status());
 
+        let color = Background::Color {
+            red: 16,
+            green: 32,
+            blue: 64,
+            alpha: 255,
+        };
+        let resp = request()
+            .method("PUT")
+            .path("/mixers/test_mixer_background/background")
+            .json(&BackgroundRequest {
+                background: color.clone(),
+            })
+            .reply(&api)
+            .await;
         assert_eq!(StatusCode::OK, resp.status());
-        assert_eq!(
-            0,
-            server
-                .mixers
-                .lock()
-                .await
-                .mixers
-                .get(mixer_name)
-                .unwrap()
-                .outputs
-                .len()
-        );
+
+        let resp = request()
+            .method("PUT")
+            .path("/mixers/test_mixer_background/background")
+            .json(&BackgroundRequest {
+                background: Background::Image {
+                    location: "./missing_slate.png".to_string(),
+                },
+            })
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
+
+        let config = server.mixers.lock().await.mixer_config(mixer_name).unwrap();
+        assert_eq!(color, config.background);
+    }
+
+    #[tokio::test]
+    async fn test_input_failover() {
+        let mixer_name = "test_input_failover";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let inputs = vec![
+            ("slate", None),
+            (
+                "primary",
+                Some(FailoverConfig {
+                    input: "slate".to_string(),
+                    timeout_ms: None,
+                    recovery: Recovery::Manual,
+                }),
+            ),
+        ];
+        for (name, failover) in inputs {
+            let input_config = crate::input::Config {
+                name: name.to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                record: false,
+                failover,
+                ..Default::default()
+            };
+
+            server
+                .input_add(
+                    mixer_name,
+                    Input::create_fake(input_config).expect("failed to create fakesrc"),
+                )
+                .await
+                .expect("Failed to add input");
+        }
+
+        let orphan = crate::input::Config {
+            name: "orphan".to_string(),
+            failover: Some(FailoverConfig {
+                input: "missing".to_string(),
+                timeout_ms: None,
+                recovery: Recovery::Automatic,
+            }),
+            ..Default::default()
+        };
+        assert!(server
+            .input_add(
+                mixer_name,
+                Input::create_fake(orphan).expect("failed to create fakesrc"),
+            )
+            .await
+            .is_err());
+
+        {
+            let mut mixers = server.mixers.lock().await;
+            let mixer = mixers.mixers.get_mut(mixer_name).unwrap();
+            mixer
+                .input_set_active("primary")
+                .expect("failed to set active input");
+            mixer.emit(Event::InputEos {
+                input: "primary".to_string(),
+            });
+            mixer.tick().expect("tick failed");
+            assert_eq!(Some("slate".to_string()), mixer.program());
+        }
+
+        let api = filters::input_failover_return(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_failover/inputs/primary/failover/return")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get(mixer_name).unwrap();
+        assert_eq!(Some("primary".to_string()), mixer.program());
+    }
+
+    #[tokio::test]
+    async fn test_input_set_audio_route() {
+        let mixer_name = "test_input_set_audio_route";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        for name in ["camera", "mic", "music"].iter() {
+            let input_config = crate::input::Config {
+                name: name.to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                record: false,
+                ..Default::default()
+            };
+
+            server
+                .input_add(
+                    mixer_name,
+                    Input::create_fake(input_config).expect("failed to create fakesrc"),
+                )
+                .await
+                .expect("Failed to add input");
+        }
+
+        let api = filters::input_set_audio_route(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("PUT")
+            .path("/mixers/test_input_set_audio_route/inputs/camera/audio")
+            .json(&AudioRouteRequest {
+                mode: AudioMode::FollowVideo,
+                audio_source: Some("mic".to_string()),
+            })
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let resp = request()
+            .method("PUT")
+            .path("/mixers/test_input_set_audio_route/inputs/music/audio")
+            .json(&AudioRouteRequest {
+                mode: AudioMode::On,
+                audio_source: None,
+            })
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+
+        server
+            .mixers
+            .lock()
+            .await
+            .mixers
+            .get_mut(mixer_name)
+            .unwrap()
+            .input_set_active("camera")
+            .expect("failed to set active input");
+
+        let api = filters::mixer_audio(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_input_set_audio_route/audio")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+        let routing: Vec<AudioStatus> =
+            serde_json::from_slice(resp.body()).expect("failed to parse audio routing");
+        let on_air: Vec<&str> = routing
+            .iter()
+            .filter(|status| status.on_air)
+            .map(|status| status.input.as_str())
+            .collect();
+        assert_eq!(vec!["mic", "music"], on_air);
+    }
+
+    #[tokio::test]
+    async fn test_input_list() {
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: "test_input_list".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+        let api = filters::input_list(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_input_list/inputs")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_ne!(resp.body().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_input_add() {
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: "test_input_add".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+        let api = filters::input_add(Arc::clone(&server.mixers).clone());
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_add/inputs")
+            .json(&InputCreateRequest {
+                name: "test".to_string(),
+                input_type: "URI".to_string(),
+                location: "http://nowhere".to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                record: false,
+                ..Default::default()
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(
+            1,
+            server
+                .mixers
+                .lock()
+                .await
+                .mixers
+                .get("test_input_add")
+                .unwrap()
+                .inputs
+                .len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_input_playlist() {
+        let mixer_name = "test_input_playlist";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        // Broken items are skipped, so the first item has to play for the position to stay.
+        let first = silence_uri(mixer_name, 10);
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_playlist/inputs")
+            .json(&InputCreateRequest {
+                name: "playlist".to_string(),
+                input_type: "Playlist".to_string(),
+                location: "".to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                record: false,
+                playlist: Some(PlaylistConfig {
+                    items: vec![first.clone()],
+                    looping: true,
+                    shuffle: false,
+                }),
+                ..Default::default()
+            })
+            .reply(&filters::input_add(Arc::clone(&server.mixers)))
+            .await;
+        assert_eq!(StatusCode::CREATED, resp.status());
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_playlist/inputs/playlist/playlist/items")
+            .json(&PlaylistItemRequest {
+                uri: "file:///nowhere/second.mp4".to_string(),
+                position: None,
+            })
+            .reply(&filters::playlist_add(Arc::clone(&server.mixers)))
+            .await;
+        assert_eq!(StatusCode::CREATED, resp.status());
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_input_playlist/inputs/playlist/playlist")
+            .reply(&filters::playlist_get(Arc::clone(&server.mixers)))
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+        let status: PlaylistStatus =
+            serde_json::from_slice(resp.body()).expect("failed to parse playlist");
+        assert_eq!(
+            vec![first, "file:///nowhere/second.mp4".to_string()],
+            status.items
+        );
+        assert_eq!(Some(0), status.position);
+        assert!(status.looping);
+
+        let resp = request()
+            .method("DELETE")
+            .path("/mixers/test_input_playlist/inputs/playlist/playlist/items/1")
+            .reply(&filters::playlist_remove(Arc::clone(&server.mixers)))
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let resp = request()
+            .method("DELETE")
+            .path("/mixers/test_input_playlist/inputs/playlist/playlist/items/5")
+            .reply(&filters::playlist_remove(Arc::clone(&server.mixers)))
+            .await;
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
+
+        let mut mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get_mut(mixer_name).unwrap();
+        assert_eq!(1, mixer.playlist("playlist").unwrap().status().items.len());
+    }
+
+    #[tokio::test]
+    async fn test_input_playlist_error() {
+        let mixer_name = "test_input_playlist_error";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let good = silence_uri(mixer_name, 10);
+        let input_config = crate::input::Config {
+            name: "playlist".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            playlist: Some(PlaylistConfig {
+                items: vec!["file:///nowhere/broken.mp4".to_string(), good.clone()],
+                looping: false,
+                shuffle: false,
+            }),
+            ..Default::default()
+        };
+        server
+            .input_add(
+                mixer_name,
+                Input::create_playlist(input_config).expect("failed to create playlist"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        // The broken item fails while it starts, the good one follows without intervention.
+        let mut status = None;
+        for _ in 0..50 {
+            let mut mixers = server.mixers.lock().await;
+            let mixer = mixers.mixers.get_mut(mixer_name).unwrap();
+            let current = mixer.playlist("playlist").unwrap().status();
+            if current.position == Some(1) {
+                status = Some(current);
+                break;
+            }
+            drop(mixers);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(Some(1), status.expect("playlist did not advance").position);
+
+        let mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get(mixer_name).unwrap();
+        assert_eq!(good, mixer.inputs["playlist"].location());
+    }
+
+    #[tokio::test]
+    async fn test_input_get() {
+        let mixer_name = "test_input_get";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "fakesrc".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+
+        server
+            .input_add(
+                mixer_name,
+                Input::create_fake(input_config).expect("failed to create fakesrc"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::input_get(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_input_get/inputs/fakesrc")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_ne!(resp.body().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_input_health() {
+        let mixer_name = "test_input_health";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "fakesrc".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            stall_timeout_ms: Some(0),
+            ..Default::default()
+        };
+
+        server
+            .input_add(
+                mixer_name,
+                Input::create_fake(input_config).expect("failed to create fakesrc"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::input_get(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_input_health/inputs/fakesrc")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+        let input: InputResponse =
+            serde_json::from_slice(resp.body()).expect("failed to parse input");
+        let health = input.health.expect("input has no health");
+        assert_eq!(HealthState::Stalled, health.state);
+        assert_eq!(0.0, health.fps);
+
+        // The deprecated failover timeout still sets the stall timeout.
+        let legacy_config = crate::input::Config {
+            name: "legacy".to_string(),
+            failover: Some(
+                serde_json::from_str(r#"{"input": "fakesrc", "timeout_ms": 0}"#)
+                    .expect("failed to parse failover"),
+            ),
+            ..Default::default()
+        };
+        server
+            .input_add(
+                mixer_name,
+                Input::create_fake(legacy_config).expect("failed to create fakesrc"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let mixers = server.mixers.lock().await;
+        let health = mixers.mixers[mixer_name]
+            .input_health("legacy")
+            .expect("input has no health");
+        assert_eq!(HealthState::Stalled, health.state);
+    }
+
+    #[tokio::test]
+    async fn test_input_rtmp() {
+        use std::io::{Read, Write};
+
+        let mixer_name = "test_input_rtmp";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let create = |name: &str| InputCreateRequest {
+            name: name.to_string(),
+            input_type: "RTMP".to_string(),
+            location: "".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            rtmp: Some(RTMPConfig {
+                address: "0.0.0.0".to_string(),
+                port: 19350,
+                app: "live".to_string(),
+                stream_key: Some("studio".to_string()),
+            }),
+            ..Default::default()
+        };
+
+        let api = filters::input_add(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_rtmp/inputs")
+            .json(&create("studio"))
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::CREATED, resp.status());
+
+        // Two inputs can not wait for the same stream.
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_rtmp/inputs")
+            .json(&create("other"))
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+
+        let api = filters::input_get(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_input_rtmp/inputs/studio")
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+        let input: InputResponse =
+            serde_json::from_slice(resp.body()).expect("failed to parse input");
+        assert_eq!("RTMP", input.input_type);
+        assert_eq!("rtmp://0.0.0.0:19350/live/studio", input.location);
+        assert_eq!(Some(RTMPStatus::default()), input.connection);
+
+        // The listener answers the RTMP handshake, echoing our random bytes back.
+        let mut socket =
+            std::net::TcpStream::connect(("127.0.0.1", 19350)).expect("failed to connect");
+        let c1: Vec<u8> = (0..1536).map(|i| (i % 251) as u8).collect();
+        socket.write_all(&[3]).unwrap();
+        socket.write_all(&c1).unwrap();
+        let mut s0s1s2 = vec![0; 1 + 2 * 1536];
+        socket.read_exact(&mut s0s1s2).unwrap();
+        assert_eq!(3, s0s1s2[0]);
+        assert_eq!(c1, s0s1s2[1 + 1536..]);
+    }
+
+    #[tokio::test]
+    async fn test_input_rtmp_publish() {
+        use std::io::{Read, Write};
+
+        let mixer_name = "test_input_rtmp_publish";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let api = filters::input_add(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_rtmp_publish/inputs")
+            .json(&InputCreateRequest {
+                name: "studio".to_string(),
+                input_type: "RTMP".to_string(),
+                location: "".to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                record: false,
+                rtmp: Some(RTMPConfig {
+                    address: "127.0.0.1".to_string(),
+                    port: 19351,
+                    app: "live".to_string(),
+                    stream_key: Some("studio".to_string()),
+                }),
+                ..Default::default()
+            })
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::CREATED, resp.status());
+
+        // AMF0 values and a command message that fits in one chunk.
+        let string = |value: &str| {
+            let mut data = (value.len() as u16).to_be_bytes().to_vec();
+            data.extend_from_slice(value.as_bytes());
+            data
+        };
+        let command = |stream_id: u32, values: Vec<Vec<u8>>| {
+            let payload = values.concat();
+            let mut data = vec![0x03, 0, 0, 0];
+            data.extend_from_slice(&(payload.len() as u32).to_be_bytes()[1..]);
+            data.push(20);
+            data.extend_from_slice(&stream_id.to_le_bytes());
+            data.extend_from_slice(&payload);
+            data
+        };
+
+        let mut socket =
+            std::net::TcpStream::connect(("127.0.0.1", 19351)).expect("failed to connect");
+        socket.write_all(&[3]).unwrap();
+        socket.write_all(&[0; 1536]).unwrap();
+        let mut s0s1s2 = vec![0; 1 + 2 * 1536];
+        socket.read_exact(&mut s0s1s2).unwrap();
+        socket.write_all(&s0s1s2[1..1 + 1536]).unwrap();
+
+        let connect = command(
+            0,
+            vec![
+                [vec![0x02], string("connect")].concat(),
+                [vec![0x00], 1f64.to_be_bytes().to_vec()].concat(),
+                [vec![0x03], string("app"), vec![0x02], string("live")].concat(),
+                vec![0x00, 0x00, 0x09],
+            ],
+        );
+        let publish = command(
+            1,
+            vec![
+                [vec![0x02], string("publish")].concat(),
+                [vec![0x00], 2f64.to_be_bytes().to_vec()].concat(),
+                vec![0x05],
+                [vec![0x02], string("studio")].concat(),
+                [vec![0x02], string("live")].concat(),
+            ],
+        );
+        socket.write_all(&connect).unwrap();
+        socket.write_all(&publish).unwrap();
+
+        let mut status = None;
+        for _ in 0..100 {
+            let connection = {
+                let mixers = server.mixers.lock().await;
+                mixers.mixers[mixer_name].inputs["studio"].rtmp_status()
+            };
+            if connection.as_ref().is_some_and(|status| status.connected) {
+                status = connection;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        let status = status.expect("publisher did not connect");
+        assert_eq!(
+            Some(socket.local_addr().unwrap().to_string()),
+            status.client
+        );
+
+        // The listener stops with its last input, freeing the port.
+        server
+            .mixers
+            .lock()
+            .await
+            .input_remove(mixer_name, "studio")
+            .expect("failed to remove input");
+        std::net::TcpListener::bind(("127.0.0.1", 19351)).expect("listener still running");
+    }
+
+    #[tokio::test]
+    async fn test_input_srt() {
+        let mixer_name = "test_input_srt";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let create = |location: &str| InputCreateRequest {
+            name: "remote".to_string(),
+            input_type: "SRT".to_string(),
+            location: location.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            srt: Some(SRTConfig {
+                mode: SRTMode::Listener,
+                latency_ms: 500,
+                passphrase: None,
+            }),
+            ..Default::default()
+        };
+
+        let api = filters::input_add(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_srt/inputs")
+            .json(&create("udp://:19360"))
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_input_srt/inputs")
+            .json(&create("srt://:19360"))
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::CREATED, resp.status());
+
+        let api = filters::input_get(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_input_srt/inputs/remote")
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+        let input: InputResponse =
+            serde_json::from_slice(resp.body()).expect("failed to parse input");
+        assert_eq!("SRT", input.input_type);
+        assert_eq!("srt://:19360", input.location);
+        assert_eq!(None, input.connection);
+
+        let mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get(mixer_name).unwrap();
+        let input = mixer.inputs.get("remote").unwrap();
+        assert_eq!(Some(500), input.config().srt.map(|srt| srt.latency_ms));
+    }
+
+    #[tokio::test]
+    async fn test_input_srt_stats() {
+        let mixer_name = "test_input_srt_stats";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let passphrase = "correct horse battery";
+        let input_config = crate::input::Config {
+            name: "remote".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            srt: Some(SRTConfig {
+                mode: SRTMode::Listener,
+                latency_ms: 125,
+                passphrase: Some(passphrase.to_string()),
+            }),
+            ..Default::default()
+        };
+        server
+            .input_add(
+                mixer_name,
+                Input::create_srt(input_config, "srt://:19361").expect("failed to create input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        // The passphrase stays out of logs and of the state file.
+        {
+            let mixers = server.mixers.lock().await;
+            let config = mixers.mixers[mixer_name].inputs["remote"].config();
+            assert!(!format!("{:?}", config).contains(passphrase));
+            let state = State::capture(&mixers);
+            let json = serde_json::to_string(&state).expect("failed to serialize state");
+            assert!(!json.contains(passphrase));
+            assert!(state.mixers[0].inputs[0].encrypted);
+        }
+
+        let sender = gst::parse::launch(&format!(
+            "videotestsrc is-live=true ! video/x-raw,width=320,height=240 \
+             ! x264enc tune=zerolatency ! mpegtsmux \
+             ! srtsink uri=srt://127.0.0.1:19361 mode=caller passphrase=\"{}\"",
+            passphrase
+        ))
+        .expect("failed to create sender");
+        sender
+            .set_state(gst::State::Playing)
+            .expect("failed to start sender");
+
+        let mut stats = None;
+        for _ in 0..100 {
+            let received = {
+                let mixers = server.mixers.lock().await;
+                mixers.mixers[mixer_name].inputs["remote"].srt_stats()
+            };
+            if received
+                .as_ref()
+                .is_some_and(|stats| stats.packets_received > 0)
+            {
+                stats = received;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        sender
+            .set_state(gst::State::Null)
+            .expect("failed to stop sender");
+
+        let stats = stats.expect("no packets received over SRT");
+        assert!(stats.packets_received > 0);
+        assert!(stats.bytes_received > 0);
+    }
+
+    #[tokio::test]
+    async fn test_input_update() {
+        let mixer_name = "test_input_update";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "test".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+
+        server
+            .input_add(
+                mixer_name,
+                Input::create_test(input_config).expect("failed to create test input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::input_update(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("PUT")
+            .path("/mixers/test_input_update/inputs/test")
+            .json(&InputUpdateRequest {
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                audio_offset_ms: Some(120),
+                video_offset_ms: None,
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let mixers = server.mixers.lock().await;
+        let config = mixers
+            .mixers
+            .get(mixer_name)
+            .unwrap()
+            .inputs
+            .get("test")
+            .unwrap()
+            .config();
+        assert_eq!(120, config.audio_offset_ms);
+        assert_eq!(0, config.video_offset_ms);
+        drop(mixers);
+
+        let resp = request()
+            .method("PUT")
+            .path("/mixers/test_input_update/inputs/test")
+            .json(&InputUpdateRequest {
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                audio_offset_ms: None,
+                video_offset_ms: Some(-40),
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+    }
+
+    #[tokio::test]
+    async fn test_input_remove() {
+        let mixer_name = "test_input_remove";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "fakesrc".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+
+        server
+            .input_add(
+                mixer_name,
+                Input::create_fake(input_config).expect("failed to create fakesrc"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::input_remove(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("DELETE")
+            .path("/mixers/test_input_remove/inputs/fakesrc")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_eq!(
+            0,
+            server
+                .mixers
+                .lock()
+                .await
+                .mixers
+                .get(mixer_name)
+                .unwrap()
+                .inputs
+                .len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_output_list() {
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: "test_output_list".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+        let api = filters::output_list(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_output_list/outputs")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_ne!(resp.body().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_output_add() {
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: "test_output_add".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+        let api = filters::output_add(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_output_add/outputs")
+            .json(&OutputCreateRequest {
+                name: "test".to_string(),
+                output_type: "Fake".to_string(),
+                location: "http://nowhere".to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                encoder: EncoderConfig::default(),
+                bus: Bus::Program,
+                fixed_caps: false,
+                sink: None,
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert_eq!(
+            1,
+            server
+                .mixers
+                .lock()
+                .await
+                .mixers
+                .get("test_output_add")
+                .unwrap()
+                .outputs
+                .len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_output_add_preview() {
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: "test_output_add_preview".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+        let api = filters::output_add(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_output_add_preview/outputs")
+            .json(&OutputCreateRequest {
+                name: "test".to_string(),
+                output_type: "Fake".to_string(),
+                location: "http://nowhere".to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                encoder: EncoderConfig::default(),
+                bus: Bus::Preview,
+                fixed_caps: false,
+                sink: None,
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get("test_output_add_preview").unwrap();
+        let output = mixer.outputs.get("test").unwrap();
+        assert_eq!(Bus::Preview, output.config().bus);
+    }
+
+    #[tokio::test]
+    async fn test_output_get() {
+        let mixer_name = "test_output_get";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let output_config = OutputConfig {
+            name: "fake".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            encoder: EncoderConfig::default(),
+            mux: None,
+            bus: Bus::Program,
+            fixed_caps: false,
+            sink: None,
+        };
+
+        server
+            .output_add(
+                mixer_name,
+                Output::create_fake(output_config).expect("failed to create fake output"),
+            )
+            .await
+            .expect("Failed to add output");
+
+        let api = filters::output_get(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_output_get/outputs/fake")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_ne!(resp.body().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_output_remove() {
+        let mixer_name = "test_output_remove";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let output_config = OutputConfig {
+            name: "fake".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            encoder: EncoderConfig::default(),
+            mux: None,
+            bus: Bus::Program,
+            fixed_caps: false,
+            sink: None,
+        };
+
+        server
+            .output_add(
+                mixer_name,
+                Output::create_fake(output_config).expect("failed to create fake output"),
+            )
+            .await
+            .expect("Failed to add output");
+
+        let api = filters::output_remove(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("DELETE")
+            .path("/mixers/test_output_remove/outputs/fake")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_eq!(
+            0,
+            server
+                .mixers
+                .lock()
+                .await
+                .mixers
+                .get(mixer_name)
+                .unwrap()
+                .outputs
+                .len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scene_add() {
+        let mixer_name = "test_scene_add";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "fakesrc".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+
+        server
+            .input_add(
+                mixer_name,
+                Input::create_fake(input_config).expect("failed to create fakesrc"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::scene_add(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_scene_add/scenes")
+            .json(&SceneCreateRequest {
+                name: "wide".to_string(),
+                inputs: None,
+            })
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::CREATED, resp.status());
+
+        let mixers = server.mixers.lock().await;
+        let scene = mixers
+            .mixers
+            .get(mixer_name)
+            .unwrap()
+            .scenes
+            .get("wide")
+            .expect("scene was not saved");
+        assert!(scene.inputs.contains_key("fakesrc"));
+    }
+
+    #[tokio::test]
+    async fn test_scene_apply() {
+        use gst::prelude::*;
+
+        let mixer_name = "test_scene_apply";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "test".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+
+        server
+            .input_add(
+                mixer_name,
+                Input::create_test(input_config).expect("failed to create test input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let scene_input = SceneInput {
+            zorder: 5,
+            xpos: 10,
+            ypos: 20,
+            width: 320,
+            height: 180,
+            alpha: 0.5,
+            volume: 1.0,
+        };
+        server
+            .mixers
+            .lock()
+            .await
+            .mixers
+            .get_mut(mixer_name)
+            .unwrap()
+            .scene_add(Scene {
+                name: "corner".to_string(),
+                inputs: vec![("test".to_string(), scene_input)]
+                    .into_iter()
+                    .collect(),
+            })
+            .expect("failed to add scene");
+
+        let api = filters::scene_apply(Arc::clone(&server.mixers));
+
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_scene_apply/scenes/corner/apply")
+            .reply(&api)
+            .await;
+
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let mixers = server.mixers.lock().await;
+        let input = mixers
+            .mixers
+            .get(mixer_name)
+            .unwrap()
+            .inputs
+            .get("test")
+            .unwrap();
+        let pad = input.compositor_pad().expect("input has no compositor pad");
+        assert_eq!(5, pad.property::<u32>("zorder"));
+
+        // Scenes change what is on air, not the configuration of the inputs.
+        let config = input.config();
+        assert_eq!(0, config.video.xpos);
+        assert_eq!(1920, config.video.width);
+        assert_eq!(1.0, config.video.alpha);
+    }
+
+    #[tokio::test]
+    async fn test_state_restore() {
+        let mixer_name = "test_state_restore";
+        let path = std::env::temp_dir().join("switcher_test_state_restore.json");
+        let _ = std::fs::remove_file(&path);
+
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "test".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+        server
+            .input_add(
+                mixer_name,
+                Input::create_test(input_config).expect("failed to create test input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let output_config = OutputConfig {
+            name: "fake".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            encoder: EncoderConfig::default(),
+            mux: None,
+            bus: Bus::Program,
+            fixed_caps: false,
+            sink: None,
+        };
+        server
+            .output_add(
+                mixer_name,
+                Output::create_fake(output_config).expect("failed to create fake output"),
+            )
+            .await
+            .expect("Failed to add output");
+
+        server
+            .mixers
+            .lock()
+            .await
+            .mixers
+            .get_mut(mixer_name)
+            .unwrap()
+            .input_set_active("test")
+            .expect("failed to set active input");
+        server
+            .mixers
+            .lock()
+            .await
+            .mixers
+            .get_mut(mixer_name)
+            .unwrap()
+            .input_set_audio_route(
+                "test",
+                AudioRoute {
+                    mode: AudioMode::On,
+                    audio_source: None,
+                },
+            )
+            .expect("failed to set audio route");
+
+        // Only the action still to come survives, the one missed long ago is dropped.
+        let now = schedule::now();
+        let mut ids = Vec::new();
+        for at in [now - 60_000, now + 60_000] {
+            let scheduled = server
+                .mixers
+                .lock()
+                .await
+                .schedule_add(
+                    mixer_name,
+                    at,
+                    Action::SetActive {
+                        input: "test".to_string(),
+                    },
+                )
+                .expect("failed to schedule action");
+            ids.push(scheduled.id);
+        }
+
+        server
+            .state_restore(&path)
+            .await
+            .expect("failed to write state");
+        server
+            .mixers
+            .lock()
+            .await
+            .mixer_remove(mixer_name)
+            .expect("failed to remove mixer");
+
+        let mut restored = setup_server();
+        restored
+            .state_restore(&path)
+            .await
+            .expect("failed to restore state");
+        let _ = std::fs::remove_file(&path);
+
+        let mixers = restored.mixers.lock().await;
+        let mixer = mixers.mixers.get(mixer_name).expect("mixer not restored");
+        assert!(mixer.inputs.contains_key("test"));
+        assert!(mixer.outputs.contains_key("fake"));
+        assert_eq!(Some("test".to_string()), mixer.program());
+        let routing = mixer.audio_routing();
+        assert_eq!(1, routing.len());
+        assert_eq!(AudioMode::On, routing[0].route.mode);
+        let schedule = mixers.schedule(mixer_name).unwrap();
+        assert_eq!(
+            vec![ids[1]],
+            schedule.iter().map(|s| s.id).collect::<Vec<u64>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_state_encrypted() {
+        let mixer_name = "test_state_encrypted";
+        let passphrase = "correct horse battery";
+        let srt = SRTConfig {
+            mode: SRTMode::Caller,
+            latency_ms: 125,
+            passphrase: Some(passphrase.to_string()),
+        };
+
+        let server = setup_server();
+        let mut mixers = server.mixers.lock().await;
+        mixers
+            .mixer_create(MixerConfig {
+                name: mixer_name.to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                ..Default::default()
+            })
+            .expect("failed to create mixer");
+
+        let remote = crate::input::Config {
+            name: "remote".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            srt: Some(srt.clone()),
+            ..Default::default()
+        };
+        mixers
+            .input_add(
+                mixer_name,
+                Input::create_srt(remote, "srt://127.0.0.1:19362").expect("failed to create input"),
+            )
+            .expect("Failed to add input");
+        let cam = crate::input::Config {
+            name: "cam".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            failover: Some(FailoverConfig {
+                input: "remote".to_string(),
+                timeout_ms: None,
+                recovery: Recovery::Automatic,
+            }),
+            ..Default::default()
+        };
+        mixers
+            .input_add(
+                mixer_name,
+                Input::create_test(cam).expect("failed to create test input"),
+            )
+            .expect("Failed to add input");
+        mixers
+            .schedule_add(
+                mixer_name,
+                schedule::now() + 3_600_000,
+                Action::InputAdd {
+                    input: InputCreateRequest {
+                        name: "later".to_string(),
+                        input_type: "SRT".to_string(),
+                        location: "srt://127.0.0.1:19363".to_string(),
+                        audio: AudioConfig::default(),
+                        video: VideoConfig::default(),
+                        record: false,
+                        srt: Some(srt),
+                        ..Default::default()
+                    },
+                },
+            )
+            .expect("failed to schedule action");
+
+        // Passphrases of scheduled inputs stay out of the state file too.
+        let json = serde_json::to_string(&State::capture(&mixers)).expect("failed to serialize");
+        assert!(!json.contains(passphrase));
+        drop(mixers);
+        let state: State = serde_json::from_str(&json).expect("failed to parse state");
+        assert!(state.mixers[0].inputs.iter().any(|input| input.encrypted));
+        assert!(state.mixers[0].schedule[0].encrypted);
+
+        // Without their passphrases, encrypted inputs and the actions adding them are left out.
+        // Inputs failing over to them are restored without failover.
+        let restored = setup_server();
+        let mut mixers = restored.mixers.lock().await;
+        mixers.restore(state.clone());
+        let mixer = &mixers.mixers[mixer_name];
+        assert!(!mixer.inputs.contains_key("remote"));
+        assert_eq!(None, mixer.inputs["cam"].config().failover);
+        assert!(mixers.schedule(mixer_name).unwrap().is_empty());
+        drop(mixers);
+
+        std::env::set_var(state::passphrase_var(mixer_name, "remote"), passphrase);
+        std::env::set_var(state::passphrase_var(mixer_name, "later"), passphrase);
+        let restored = setup_server();
+        let mut mixers = restored.mixers.lock().await;
+        mixers.restore(state);
+        let mixer = &mixers.mixers[mixer_name];
+        assert_eq!(
+            Some(passphrase.to_string()),
+            mixer.inputs["remote"]
+                .config()
+                .srt
+                .and_then(|srt| srt.passphrase)
+        );
+        assert_eq!(
+            Some("remote".to_string()),
+            mixer.inputs["cam"].config().failover.map(|f| f.input)
+        );
+        match &mixers.schedule(mixer_name).unwrap()[0].action {
+            Action::InputAdd { input } => assert_eq!(
+                Some(passphrase),
+                input.srt.as_ref().and_then(|srt| srt.passphrase.as_deref())
+            ),
+            action => panic!("unexpected action {:?}", action),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schedule() {
+        let mixer_name = "test_schedule";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "test".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+        server
+            .input_add(
+                mixer_name,
+                Input::create_test(input_config).expect("failed to create test input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::schedule_add(Arc::clone(&server.mixers));
+        for &(at, delay_ms) in &[(Some(0), Some(1000)), (None, Some(u64::MAX))] {
+            let resp = request()
+                .method("POST")
+                .path("/mixers/test_schedule/schedule")
+                .json(&ScheduleCreateRequest {
+                    at,
+                    delay_ms,
+                    action: Action::SetActive {
+                        input: "test".to_string(),
+                    },
+                })
+                .reply(&api)
+                .await;
+            assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+        }
+
+        let mut ids = Vec::new();
+        for &(at, delay_ms) in &[(None, Some(3_600_000)), (Some(0), None)] {
+            let resp = request()
+                .method("POST")
+                .path("/mixers/test_schedule/schedule")
+                .json(&ScheduleCreateRequest {
+                    at,
+                    delay_ms,
+                    action: Action::SetActive {
+                        input: "test".to_string(),
+                    },
+                })
+                .reply(&api)
+                .await;
+            assert_eq!(StatusCode::CREATED, resp.status());
+            let scheduled: Scheduled =
+                serde_json::from_slice(resp.body()).expect("failed to parse scheduled action");
+            ids.push(scheduled.id);
+        }
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_schedule/schedule")
+            .reply(&filters::schedule_list(Arc::clone(&server.mixers)))
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+        let schedule: Vec<Scheduled> =
+            serde_json::from_slice(resp.body()).expect("failed to parse schedule");
+        assert_eq!(
+            vec![ids[1], ids[0]],
+            schedule.iter().map(|s| s.id).collect::<Vec<u64>>()
+        );
+
+        let resp = request()
+            .method("DELETE")
+            .path(&format!("/mixers/test_schedule/schedule/{}", ids[0]))
+            .reply(&filters::schedule_remove(Arc::clone(&server.mixers)))
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+
+        let mut mixers = server.mixers.lock().await;
+        mixers.schedule_run(schedule::now());
+        assert!(mixers.schedule(mixer_name).unwrap().is_empty());
+        assert_eq!(
+            Some("test".to_string()),
+            mixers.mixers.get(mixer_name).unwrap().program()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let mixer_name = "test_snapshot";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = crate::input::Config {
+            name: "fake".to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+        server
+            .input_add(
+                mixer_name,
+                Input::create_fake(input_config).expect("failed to create fake input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::input_snapshot(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_snapshot/inputs/fake/snapshot.jpg")
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, resp.status());
+
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_snapshot/inputs/nothing/snapshot.png")
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::NOT_FOUND, resp.status());
+
+        // Wait for the background to reach program.
+        let api = filters::mixer_snapshot(Arc::clone(&server.mixers));
+        let mut resp = None;
+        for _ in 0..50 {
+            let reply = request()
+                .method("GET")
+                .path("/mixers/test_snapshot/snapshot.jpg?width=160")
+                .reply(&api)
+                .await;
+            if reply.status() != StatusCode::SERVICE_UNAVAILABLE {
+                resp = Some(reply);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        let resp = resp.expect("no frame reached program");
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_eq!("image/jpeg", resp.headers()["Content-Type"]);
+        assert_ne!(0, resp.body().len());
+
+        for width in ["0", "-20"] {
+            let resp = request()
+                .method("GET")
+                .path(&format!(
+                    "/mixers/test_snapshot/snapshot.png?width={}",
+                    width
+                ))
+                .reply(&api)
+                .await;
+            assert_eq!(StatusCode::BAD_REQUEST, resp.status());
+        }
+
+        // Snapshots are not scaled up, the width is read from the PNG header.
+        let resp = request()
+            .method("GET")
+            .path("/mixers/test_snapshot/snapshot.png?width=100000")
+            .reply(&api)
+            .await;
+        assert_eq!(StatusCode::OK, resp.status());
+        let mut width = [0u8; 4];
+        width.copy_from_slice(&resp.body()[16..20]);
+        assert_eq!(VideoConfig::default().width, i32::from_be_bytes(width));
+    }
+
+    #[tokio::test]
+    async fn test_output_multiview() {
+        let mixer_name = "test_output_multiview";
+        let mut server = setup_server();
+        let config = MixerConfig {
+            name: mixer_name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            ..Default::default()
+        };
+        server
+            .mixer_create(config)
+            .await
+            .expect("failed to create mixer");
+
+        let input_config = |name: &str| crate::input::Config {
+            name: name.to_string(),
+            audio: AudioConfig::default(),
+            video: VideoConfig::default(),
+            record: false,
+            ..Default::default()
+        };
+        server
+            .input_add(
+                mixer_name,
+                Input::create_test(input_config("first")).expect("failed to create test input"),
+            )
+            .await
+            .expect("Failed to add input");
+
+        let api = filters::output_add(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("POST")
+            .path("/mixers/test_output_multiview/outputs")
+            .json(&OutputCreateRequest {
+                name: "multiview".to_string(),
+                output_type: "Multiview".to_string(),
+                location: "".to_string(),
+                audio: AudioConfig::default(),
+                video: VideoConfig::default(),
+                encoder: EncoderConfig::default(),
+                bus: Bus::Program,
+                fixed_caps: false,
+                sink: Some(Sink::Fake),
+            })
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        // Inputs coming and going after the multiview get their tiles added and removed.
+        server
+            .input_add(
+                mixer_name,
+                Input::create_test(input_config("second")).expect("failed to create test input"),
+            )
+            .await
+            .expect("Failed to add input");
+        let mut mixers = server.mixers.lock().await;
+        mixers
+            .mixers
+            .get_mut(mixer_name)
+            .unwrap()
+            .input_set_active("second")
+            .expect("failed to set active input");
+        mixers
+            .input_remove(mixer_name, "first")
+            .expect("Failed to remove input");
+
+        let mixer = mixers.mixers.get(mixer_name).unwrap();
+        let output = mixer.outputs.get("multiview").expect("multiview missing");
+        assert_eq!("Multiview", output.output_type());
+        assert_eq!(Some(Sink::Fake), output.config().sink);
+        drop(mixers);
+
+        let api = filters::output_remove(Arc::clone(&server.mixers));
+        let resp = request()
+            .method("DELETE")
+            .path("/mixers/test_output_multiview/outputs/multiview")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_config_apply() {
+        let mixer_name = "test_config_apply";
+        let config: Config = toml::from_str(&format!(
+            r#"
+            [[mixers]]
+            name = "{}"
+
+            [[mixers.inputs]]
+            name = "test"
+            input_type = "Test"
+            location = ""
+            failover = {{ input = "backup" }}
+
+            [[mixers.inputs]]
+            name = "backup"
+            input_type = "Fake"
+            location = ""
+
+            [[mixers.outputs]]
+            name = "fake"
+            output_type = "Fake"
+            location = ""
+            "#,
+            mixer_name
+        ))
+        .expect("failed to parse config");
+
+        let mut duplicate = config.clone();
+        duplicate.mixers.push(config.mixers[0].clone());
+        assert!(duplicate.validate().is_err());
+
+        let mut server = setup_server();
+        server
+            .config_apply(config)
+            .await
+            .expect("failed to apply config");
+
+        let mixers = server.mixers.lock().await;
+        let mixer = mixers.mixers.get(mixer_name).expect("mixer not created");
+        assert!(mixer.inputs.contains_key("test"));
+        assert!(mixer.inputs.contains_key("backup"));
+        assert!(mixer.outputs.contains_key("fake"));
     }
 }
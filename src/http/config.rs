@@ -0,0 +1,116 @@
+use super::{
+    input::CreateRequest as InputCreateRequest, mixer::CreateRequest as MixerCreateRequest,
+    output::CreateRequest as OutputCreateRequest, Error,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, path::Path};
+
+/// Input types [`InputCreateRequest`](../input/struct.CreateRequest.html) accepts.
+const INPUT_TYPES: &[&str] = &["URI", "RTMP", "SRT", "Playlist", "Test", "Fake"];
+
+/// Output types [`OutputCreateRequest`](../output/struct.CreateRequest.html) accepts.
+const OUTPUT_TYPES: &[&str] = &["RTMP", "Auto", "Fake", "File", "Multiview"];
+
+/// Mixers to build on startup, declared in a TOML, YAML or JSON file. Mixers, inputs and outputs
+/// take the same fields as the HTTP requests that create them.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub mixers: Vec<MixerDeclaration>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MixerDeclaration {
+    #[serde(flatten)]
+    pub mixer: MixerCreateRequest,
+    #[serde(default)]
+    pub inputs: Vec<InputCreateRequest>,
+    #[serde(default)]
+    pub outputs: Vec<OutputCreateRequest>,
+}
+
+impl Config {
+    /// Reads the config from `path`, in the format named by its extension: `toml`, `yaml`, `yml`
+    /// or `json`.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+        let config = match extension {
+            "toml" => toml::from_str(&data).map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => serde_yaml_ng::from_str(&data).map_err(|e| e.to_string()),
+            "json" => serde_json::from_str(&data).map_err(|e| e.to_string()),
+            _ => Err(format!("unsupported config format '{}'", extension)),
+        };
+
+        config.map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Checks the declarations for mistakes that would only surface while building the mixers,
+    /// such as duplicate names, unknown types and failovers to inputs that do not exist.
+    pub fn validate(&self) -> Result<(), Error> {
+        let re = Regex::new(r"^[a-zA-Z0-9-_]+$").map_err(|_| Error::Unknown)?;
+        let mut mixers = HashSet::new();
+
+        for declaration in self.mixers.iter() {
+            let mixer = &declaration.mixer.name;
+            if !re.is_match(mixer) {
+                return Err(Error::Config(format!("invalid mixer name '{}'", mixer)));
+            }
+            if !mixers.insert(mixer) {
+                return Err(Error::Config(format!(
+                    "mixer '{}' is declared twice",
+                    mixer
+                )));
+            }
+
+            let mut inputs = HashSet::new();
+            for input in declaration.inputs.iter() {
+                if !INPUT_TYPES.contains(&input.input_type.as_str()) {
+                    return Err(Error::Config(format!(
+                        "{}: input '{}' has unknown type '{}'",
+                        mixer, input.name, input.input_type
+                    )));
+                }
+                if !inputs.insert(&input.name) {
+                    return Err(Error::Config(format!(
+                        "{}: input '{}' is declared twice",
+                        mixer, input.name
+                    )));
+                }
+            }
+
+            for input in declaration.inputs.iter() {
+                if let Some(failover) = input.failover.as_ref() {
+                    if !inputs.contains(&failover.input) {
+                        return Err(Error::Config(format!(
+                            "{}: input '{}' fails over to unknown input '{}'",
+                            mixer, input.name, failover.input
+                        )));
+                    }
+                }
+            }
+
+            let mut outputs = HashSet::new();
+            for output in declaration.outputs.iter() {
+                if !OUTPUT_TYPES.contains(&output.output_type.as_str()) {
+                    return Err(Error::Config(format!(
+                        "{}: output '{}' has unknown type '{}'",
+                        mixer, output.name, output.output_type
+                    )));
+                }
+                if !outputs.insert(&output.name) {
+                    return Err(Error::Config(format!(
+                        "{}: output '{}' is declared twice",
+                        mixer, output.name
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
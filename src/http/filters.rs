@@ -1,4 +1,5 @@
-use super::{input, mixer, output, recover};
+use super::{input, mixer, output, recover, scene, schedule, snapshot};
+use crate::mixer::ImageFormat;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use warp::*;
@@ -17,17 +18,51 @@ pub fn routes(
     mixer_list(mixers.clone())
         .or(mixer_get(mixers.clone()))
         .or(mixer_create(mixers.clone()))
+        .or(mixer_update(mixers.clone()))
+        .or(mixer_remove(mixers.clone()))
         .or(mixer_debug(mixers.clone()))
+        .or(mixer_events(mixers.clone()))
+        .or(mixer_levels(mixers.clone()))
+        .or(mixer_levels_stream(mixers.clone()))
+        .or(mixer_audio(mixers.clone()))
+        .or(mixer_tally(mixers.clone()))
+        .or(mixer_stats(mixers.clone()))
+        .or(mixer_transition(mixers.clone()))
+        .or(mixer_cut(mixers.clone()))
+        .or(mixer_take(mixers.clone()))
+        .or(mixer_layout(mixers.clone()))
+        .or(mixer_background(mixers.clone()))
+        .or(mixer_snapshot(mixers.clone()))
         .or(input_list(mixers.clone()))
         .or(input_get(mixers.clone()))
         .or(input_add(mixers.clone()))
         .or(input_update(mixers.clone()))
         .or(input_remove(mixers.clone()))
         .or(input_set_active(mixers.clone()))
+        .or(input_set_preview(mixers.clone()))
+        .or(input_failover_return(mixers.clone()))
+        .or(input_set_audio_route(mixers.clone()))
+        .or(input_snapshot(mixers.clone()))
+        .or(playlist_get(mixers.clone()))
+        .or(playlist_update(mixers.clone()))
+        .or(playlist_add(mixers.clone()))
+        .or(playlist_remove(mixers.clone()))
+        .or(playlist_skip(mixers.clone()))
+        .or(playlist_previous(mixers.clone()))
         .or(output_list(mixers.clone()))
         .or(output_get(mixers.clone()))
         .or(output_add(mixers.clone()))
-        .or(output_remove(mixers))
+        .or(output_remove(mixers.clone()))
+        .or(scene_list(mixers.clone()))
+        .or(scene_get(mixers.clone()))
+        .or(scene_add(mixers.clone()))
+        .or(scene_update(mixers.clone()))
+        .or(scene_remove(mixers.clone()))
+        .or(scene_apply(mixers.clone()))
+        .or(schedule_list(mixers.clone()))
+        .or(schedule_add(mixers.clone()))
+        .or(schedule_remove(mixers.clone()))
+        .or(schedule_clear(mixers))
 }
 
 /// Setup route for `POST /mixers`
@@ -64,6 +99,29 @@ pub(crate) fn mixer_get(
         .recover(recover)
 }
 
+/// Setup route for `PUT /mixers/name`
+pub(crate) fn mixer_update(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String)
+        .and(warp::put())
+        .and(mixer::UpdateRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(mixer::update)
+        .recover(recover)
+}
+
+/// Setup route for `DELETE /mixers/name`
+pub(crate) fn mixer_remove(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String)
+        .and(warp::delete())
+        .and(with_mixers(mixers))
+        .and_then(mixer::remove)
+        .recover(recover)
+}
+
 /// Setup route for `GET /mixer/name/debug`
 pub(crate) fn mixer_debug(
     mixers: Arc<Mutex<super::Mixers>>,
@@ -75,6 +133,147 @@ pub(crate) fn mixer_debug(
         .recover(recover)
 }
 
+/// Setup route for `GET /mixers/name/snapshot.jpg` and `GET /mixers/name/snapshot.png`
+pub(crate) fn mixer_snapshot(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "snapshot.jpg")
+        .map(|mixer| (mixer, ImageFormat::Jpeg))
+        .or(warp::path!("mixers" / String / "snapshot.png").map(|mixer| (mixer, ImageFormat::Png)))
+        .unify()
+        .untuple_one()
+        .and(warp::get())
+        .and(warp::query::<snapshot::Query>())
+        .and(with_mixers(mixers))
+        .and_then(snapshot::program)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/events`
+pub(crate) fn mixer_events(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "events")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(mixer::events)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/levels`
+pub(crate) fn mixer_levels(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "levels")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(mixer::levels)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/levels/stream`
+pub(crate) fn mixer_levels_stream(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "levels" / "stream")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(mixer::levels_stream)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/audio`
+pub(crate) fn mixer_audio(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "audio")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(mixer::audio)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/tally`
+pub(crate) fn mixer_tally(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "tally")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(mixer::tally)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/stats`
+pub(crate) fn mixer_stats(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "stats")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(mixer::stats)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/transition`
+pub(crate) fn mixer_transition(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "transition")
+        .and(warp::post())
+        .and(mixer::TransitionRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(mixer::transition)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/cut`
+pub(crate) fn mixer_cut(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "cut")
+        .and(warp::post())
+        .and(with_mixers(mixers))
+        .and_then(mixer::cut)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/take`
+pub(crate) fn mixer_take(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "take")
+        .and(warp::post())
+        .and(mixer::TakeRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(mixer::take)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/layout`
+pub(crate) fn mixer_layout(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "layout")
+        .and(warp::post())
+        .and(mixer::LayoutRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(mixer::layout)
+        .recover(recover)
+}
+
+/// Setup route for `PUT /mixers/name/background`
+pub(crate) fn mixer_background(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "background")
+        .and(warp::put())
+        .and(mixer::BackgroundRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(mixer::background)
+        .recover(recover)
+}
+
 /// Setup route for `POST /mixers/name/inputs`
 pub(crate) fn input_add(
     mixers: Arc<Mutex<super::Mixers>>,
@@ -143,6 +342,128 @@ pub(crate) fn input_set_active(
         .recover(recover)
 }
 
+/// Setup route for `POST /mixers/name/set_preview_input/name`
+pub(crate) fn input_set_preview(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "set_preview_input" / String)
+        .and(warp::post())
+        .and(with_mixers(mixers))
+        .and_then(input::set_preview)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/inputs/name/failover/return`
+pub(crate) fn input_failover_return(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "failover" / "return")
+        .and(warp::post())
+        .and(with_mixers(mixers))
+        .and_then(input::failover_return)
+        .recover(recover)
+}
+
+/// Setup route for `PUT /mixers/name/inputs/name/audio`
+pub(crate) fn input_set_audio_route(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "audio")
+        .and(warp::put())
+        .and(input::AudioRouteRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(input::set_audio_route)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/inputs/name/snapshot.jpg` and
+/// `GET /mixers/name/inputs/name/snapshot.png`
+pub(crate) fn input_snapshot(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "snapshot.jpg")
+        .map(|mixer, input| (mixer, input, ImageFormat::Jpeg))
+        .or(
+            warp::path!("mixers" / String / "inputs" / String / "snapshot.png")
+                .map(|mixer, input| (mixer, input, ImageFormat::Png)),
+        )
+        .unify()
+        .untuple_one()
+        .and(warp::get())
+        .and(warp::query::<snapshot::Query>())
+        .and(with_mixers(mixers))
+        .and_then(snapshot::input)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/inputs/name/playlist`
+pub(crate) fn playlist_get(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "playlist")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(input::playlist_get)
+        .recover(recover)
+}
+
+/// Setup route for `PUT /mixers/name/inputs/name/playlist`
+pub(crate) fn playlist_update(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "playlist")
+        .and(warp::put())
+        .and(input::PlaylistRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(input::playlist_update)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/inputs/name/playlist/items`
+pub(crate) fn playlist_add(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "playlist" / "items")
+        .and(warp::post())
+        .and(input::PlaylistItemRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(input::playlist_add)
+        .recover(recover)
+}
+
+/// Setup route for `DELETE /mixers/name/inputs/name/playlist/items/index`
+pub(crate) fn playlist_remove(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "playlist" / "items" / usize)
+        .and(warp::delete())
+        .and(with_mixers(mixers))
+        .and_then(input::playlist_remove)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/inputs/name/playlist/skip`
+pub(crate) fn playlist_skip(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "playlist" / "skip")
+        .and(warp::post())
+        .and(with_mixers(mixers))
+        .and_then(input::playlist_skip)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/inputs/name/playlist/previous`
+pub(crate) fn playlist_previous(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "inputs" / String / "playlist" / "previous")
+        .and(warp::post())
+        .and(with_mixers(mixers))
+        .and_then(input::playlist_previous)
+        .recover(recover)
+}
+
 /// Setup route for `GET /mixers/name/outputs`
 pub(crate) fn output_list(
     mixers: Arc<Mutex<super::Mixers>>,
@@ -187,3 +508,116 @@ pub(crate) fn output_remove(
         .and_then(output::remove)
         .recover(recover)
 }
+
+/// Setup route for `GET /mixers/name/scenes`
+pub(crate) fn scene_list(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "scenes")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(scene::list)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/scenes/name`
+pub(crate) fn scene_get(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "scenes" / String)
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(scene::get)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/scenes`
+pub(crate) fn scene_add(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "scenes")
+        .and(warp::post())
+        .and(scene::CreateRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(scene::add)
+        .recover(recover)
+}
+
+/// Setup route for `PUT /mixers/name/scenes/name`
+pub(crate) fn scene_update(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "scenes" / String)
+        .and(warp::put())
+        .and(scene::UpdateRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(scene::update)
+        .recover(recover)
+}
+
+/// Setup route for `DELETE /mixers/name/scenes/name`
+pub(crate) fn scene_remove(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "scenes" / String)
+        .and(warp::delete())
+        .and(with_mixers(mixers))
+        .and_then(scene::remove)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/scenes/name/apply`
+pub(crate) fn scene_apply(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "scenes" / String / "apply")
+        .and(warp::post())
+        .and(with_mixers(mixers))
+        .and_then(scene::apply)
+        .recover(recover)
+}
+
+/// Setup route for `GET /mixers/name/schedule`
+pub(crate) fn schedule_list(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "schedule")
+        .and(warp::get())
+        .and(with_mixers(mixers))
+        .and_then(schedule::list)
+        .recover(recover)
+}
+
+/// Setup route for `POST /mixers/name/schedule`
+pub(crate) fn schedule_add(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "schedule")
+        .and(warp::post())
+        .and(schedule::CreateRequest::from_json_body())
+        .and(with_mixers(mixers))
+        .and_then(schedule::add)
+        .recover(recover)
+}
+
+/// Setup route for `DELETE /mixers/name/schedule/id`
+pub(crate) fn schedule_remove(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "schedule" / u64)
+        .and(warp::delete())
+        .and(with_mixers(mixers))
+        .and_then(schedule::remove)
+        .recover(recover)
+}
+
+/// Setup route for `DELETE /mixers/name/schedule`
+pub(crate) fn schedule_clear(
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> impl Filter<Extract = impl Reply, Error = warp::Rejection> + Clone {
+    warp::path!("mixers" / String / "schedule")
+        .and(warp::delete())
+        .and(with_mixers(mixers))
+        .and_then(schedule::clear)
+        .recover(recover)
+}
@@ -1,5 +1,9 @@
 use super::{error, message_response, okay, Error, JsonResult};
-use crate::input::{Config as InputConfig, Input as MixerInput};
+use crate::input::{
+    rtmp::Status as RTMPStatus, srt::Stats as SRTStats, validate_offset, Config as InputConfig,
+    FailoverConfig, Input as MixerInput, PlaylistConfig, RTMPConfig, SRTConfig,
+};
+use crate::mixer::{AudioMode, AudioRoute, Health};
 use crate::{AudioConfig, VideoConfig};
 
 use serde::{Deserialize, Serialize};
@@ -9,7 +13,7 @@ use warp::{http::StatusCode, Filter};
 
 /// HTTP Request for creating a new [`input::Input`](../input/struct.Input.html)
 /// to be used by the [`mixer`](../mixer/struct.Mixer.html).
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
 pub struct CreateRequest {
     pub name: String,
     pub input_type: String,
@@ -20,6 +24,23 @@ pub struct CreateRequest {
     pub video: VideoConfig,
     #[serde(default)]
     pub record: bool,
+    #[serde(default)]
+    pub failover: Option<FailoverConfig>,
+    #[serde(default)]
+    pub stall_timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub audio_offset_ms: i64,
+    #[serde(default)]
+    pub video_offset_ms: i64,
+    /// Items of a `Playlist` input.
+    #[serde(default)]
+    pub playlist: Option<PlaylistConfig>,
+    /// Port, application and stream key an `RTMP` input listens on.
+    #[serde(default)]
+    pub rtmp: Option<RTMPConfig>,
+    /// Mode, latency and passphrase of an `SRT` input.
+    #[serde(default)]
+    pub srt: Option<SRTConfig>,
 }
 
 impl CreateRequest {
@@ -30,6 +51,25 @@ impl CreateRequest {
         // (and to reject huge payloads)...
         warp::body::content_length_limit(1024 * 16).and(warp::body::json())
     }
+
+    /// Creates the input described by this request.
+    pub fn create(self) -> Result<MixerInput, Error> {
+        let config = InputConfig {
+            name: self.name,
+            video: self.video,
+            audio: self.audio,
+            record: self.record,
+            failover: self.failover,
+            stall_timeout_ms: self.stall_timeout_ms,
+            audio_offset_ms: self.audio_offset_ms,
+            video_offset_ms: self.video_offset_ms,
+            playlist: self.playlist,
+            rtmp: self.rtmp,
+            srt: self.srt,
+        };
+
+        create(&self.input_type, config, &self.location)
+    }
 }
 
 /// HTTP Request for update a [`input::Input`](../input/struct.Input.html)
@@ -38,6 +78,10 @@ impl CreateRequest {
 pub struct UpdateRequest {
     pub audio: AudioConfig,
     pub video: VideoConfig,
+    #[serde(default)]
+    pub audio_offset_ms: Option<i64>,
+    #[serde(default)]
+    pub video_offset_ms: Option<i64>,
 }
 
 impl UpdateRequest {
@@ -50,12 +94,76 @@ impl UpdateRequest {
     }
 }
 
+/// HTTP Request for changing the audio routing of an [`input::Input`](../input/struct.Input.html).
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AudioRouteRequest {
+    #[serde(default)]
+    pub mode: AudioMode,
+    #[serde(default)]
+    pub audio_source: Option<String>,
+}
+
+impl AudioRouteRequest {
+    /// Constructs a new `AudioRouteRequest` from a json body.
+    /// This function consumes the http request body through warp::body::json().
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
+/// HTTP Request for replacing the items and options of a
+/// [`input::Playlist`](../input/struct.Playlist.html).
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PlaylistRequest {
+    #[serde(flatten)]
+    pub playlist: PlaylistConfig,
+}
+
+impl PlaylistRequest {
+    /// Constructs a new `PlaylistRequest` from a json body.
+    /// This function consumes the http request body through warp::body::json().
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
+/// HTTP Request for adding an item to a [`input::Playlist`](../input/struct.Playlist.html).
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PlaylistItemRequest {
+    pub uri: String,
+    /// Where to insert the item, at the end when not set.
+    #[serde(default)]
+    pub position: Option<usize>,
+}
+
+impl PlaylistItemRequest {
+    /// Constructs a new `PlaylistItemRequest` from a json body.
+    /// This function consumes the http request body through warp::body::json().
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
 /// HTTP Response for a [`input::Input`](../input/struct.Input.html)
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Input {
     pub name: String,
     pub input_type: String,
     pub location: String,
+    pub failover: Option<FailoverConfig>,
+    pub health: Option<Health>,
+    /// Whether an encoder is publishing, for `RTMP` inputs.
+    #[serde(default)]
+    pub connection: Option<RTMPStatus>,
+    /// Connection statistics, for `SRT` inputs once they started.
+    #[serde(default)]
+    pub srt: Option<SRTStats>,
 }
 
 /// HTTP Handler for creating an [`input::Input`](../input/struct.Input.html)
@@ -68,21 +176,7 @@ pub async fn add(
     mixers: Arc<Mutex<super::Mixers>>,
 ) -> JsonResult {
     let mut mixers = mixers.lock().await;
-    let config = InputConfig {
-        name: input.name.clone(),
-        video: input.video,
-        audio: input.audio,
-        record: input.record,
-    };
-
-    let input = match input.input_type.as_str() {
-        "URI" => MixerInput::create_uri(config, &input.location).map_err(super::Error::Mixer),
-        "Fake" => MixerInput::create_fake(config).map_err(super::Error::Mixer),
-        "Test" => MixerInput::create_test(config).map_err(super::Error::Mixer),
-        _ => Err(super::Error::Unknown),
-    };
-
-    let input = match input {
+    let input = match input.create() {
         Err(e) => return error(e),
         Ok(i) => i,
     };
@@ -93,6 +187,23 @@ pub async fn add(
     }
 }
 
+/// Creates an input of `input_type`, as named by [`CreateRequest`](struct.CreateRequest.html).
+pub(crate) fn create(
+    input_type: &str,
+    config: InputConfig,
+    location: &str,
+) -> Result<MixerInput, super::Error> {
+    match input_type {
+        "URI" => MixerInput::create_uri(config, location).map_err(super::Error::Mixer),
+        "RTMP" => MixerInput::create_rtmp(config).map_err(super::Error::Mixer),
+        "SRT" => MixerInput::create_srt(config, location).map_err(super::Error::Mixer),
+        "Playlist" => MixerInput::create_playlist(config).map_err(super::Error::Mixer),
+        "Fake" => MixerInput::create_fake(config).map_err(super::Error::Mixer),
+        "Test" => MixerInput::create_test(config).map_err(super::Error::Mixer),
+        _ => Err(super::Error::Unknown),
+    }
+}
+
 /// HTTP Handler for listing [`input::Input`](../input/struct.Input.html)'s associated with
 /// a given mixer.
 #[tracing::instrument(skip(mixers))]
@@ -110,6 +221,10 @@ pub async fn list(mixer_name: String, mixers: Arc<Mutex<super::Mixers>>) -> Json
             name: input.name(),
             input_type: input.input_type(),
             location: input.location(),
+            failover: input.config().failover,
+            health: mixer.input_health(&input.name()),
+            connection: input.rtmp_status(),
+            srt: input.srt_stats(),
         })
         .collect();
     okay(&inputs)
@@ -138,6 +253,10 @@ pub async fn get(
         name: input.name(),
         input_type: input.input_type(),
         location: input.location(),
+        failover: input.config().failover,
+        health: mixer.input_health(&input.name()),
+        connection: input.rtmp_status(),
+        srt: input.srt_stats(),
     };
 
     okay(&input)
@@ -163,6 +282,16 @@ pub async fn update(
         None => return error(Error::NotFound),
     };
 
+    // Rejects the request before any of it is applied.
+    for offset in [request.audio_offset_ms, request.video_offset_ms]
+        .iter()
+        .flatten()
+    {
+        if let Err(e) = validate_offset(*offset) {
+            return error(Error::Mixer(e));
+        }
+    }
+
     if input.set_volume(request.audio.volume, true).is_err() {
         return message_response("set_volume failed", StatusCode::INTERNAL_SERVER_ERROR);
     }
@@ -193,6 +322,29 @@ pub async fn update(
         return message_response("set_alpha failed", StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    if let Some(offset) = request.audio_offset_ms {
+        if input.set_audio_offset(offset, true).is_err() {
+            return message_response(
+                "set_audio_offset failed",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            );
+        }
+    }
+
+    if let Some(offset) = request.video_offset_ms {
+        if input.set_video_offset(offset, true).is_err() {
+            return message_response(
+                "set_video_offset failed",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            );
+        }
+    }
+
+    // The preview bus shows inputs that are not on preview with their configured geometry.
+    if mixer.preview_layout().is_err() {
+        return message_response("preview_layout failed", StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
     message_response("Input updated", StatusCode::OK)
 }
 
@@ -244,3 +396,210 @@ pub async fn set_active(
         Err(e) => error(Error::Mixer(e)),
     }
 }
+
+/// HTTP Handler for putting an [`input::Input`](../input/struct.Input.html) on the preview bus.
+///
+/// The program bus is not affected, use the mixer `cut` or `take` handlers to swap preview and
+/// program.
+#[tracing::instrument(skip(mixers))]
+pub async fn set_preview(
+    mixer_name: String,
+    input_name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer.input_set_preview(&input_name) {
+        Ok(_) => message_response(
+            &format!("Input '{}' set to preview", input_name),
+            StatusCode::OK,
+        ),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for putting an [`input::Input`](../input/struct.Input.html) that failed over
+/// back on program.
+#[tracing::instrument(skip(mixers))]
+pub async fn failover_return(
+    mixer_name: String,
+    input_name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer.failover_return(&input_name) {
+        Ok(_) => message_response("Input returned to program", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for changing the audio routing of an [`input::Input`](../input/struct.Input.html)
+/// associated with a given mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn set_audio_route(
+    mixer_name: String,
+    input_name: String,
+    request: AudioRouteRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    let route = AudioRoute {
+        mode: request.mode,
+        audio_source: request.audio_source,
+    };
+
+    match mixer.input_set_audio_route(&input_name, route) {
+        Ok(_) => message_response("Audio route updated", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for retrieving the items of a [`input::Playlist`](../input/struct.Playlist.html)
+/// and the one playing.
+#[tracing::instrument(skip(mixers))]
+pub async fn playlist_get(
+    mixer_name: String,
+    input_name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer.playlist(&input_name) {
+        Ok(playlist) => okay(playlist.status()),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for replacing the items and options of a
+/// [`input::Playlist`](../input/struct.Playlist.html).
+#[tracing::instrument(skip(mixers))]
+pub async fn playlist_update(
+    mixer_name: String,
+    input_name: String,
+    request: PlaylistRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer
+        .playlist(&input_name)
+        .and_then(|playlist| playlist.set(request.playlist))
+    {
+        Ok(_) => message_response("Playlist updated", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for adding an item to a [`input::Playlist`](../input/struct.Playlist.html).
+#[tracing::instrument(skip(mixers))]
+pub async fn playlist_add(
+    mixer_name: String,
+    input_name: String,
+    request: PlaylistItemRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer
+        .playlist(&input_name)
+        .and_then(|playlist| playlist.insert(request.position, &request.uri))
+    {
+        Ok(_) => message_response("Playlist item added", StatusCode::CREATED),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for removing an item from a [`input::Playlist`](../input/struct.Playlist.html).
+#[tracing::instrument(skip(mixers))]
+pub async fn playlist_remove(
+    mixer_name: String,
+    input_name: String,
+    index: usize,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer
+        .playlist(&input_name)
+        .and_then(|playlist| playlist.remove(index))
+    {
+        Ok(_) => message_response("Playlist item removed", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for skipping to the next item of a
+/// [`input::Playlist`](../input/struct.Playlist.html).
+#[tracing::instrument(skip(mixers))]
+pub async fn playlist_skip(
+    mixer_name: String,
+    input_name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer
+        .playlist(&input_name)
+        .and_then(|playlist| playlist.skip())
+    {
+        Ok(_) => message_response("Skipped to the next item", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for going back to the previous item of a
+/// [`input::Playlist`](../input/struct.Playlist.html).
+#[tracing::instrument(skip(mixers))]
+pub async fn playlist_previous(
+    mixer_name: String,
+    input_name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer
+        .playlist(&input_name)
+        .and_then(|playlist| playlist.previous())
+    {
+        Ok(_) => message_response("Went back to the previous item", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
@@ -1,5 +1,11 @@
-use super::{error, message_response, okay, JsonResult};
-use crate::{mixer::Config as MixerConfig, AudioConfig, VideoConfig};
+use super::{error, message_response, okay, Error, JsonResult};
+use crate::{
+    mixer::{
+        transition::Direction, Background, Config as MixerConfig, Layout, Transition,
+        TransitionType,
+    },
+    AudioConfig, AudioMixConfig, Format, VideoConfig,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     convert::Infallible,
@@ -8,7 +14,11 @@ use std::{
     sync::Arc,
 };
 use tokio::sync::Mutex;
-use warp::{http::StatusCode, reply, Filter, Reply};
+use tokio_stream::{
+    wrappers::{BroadcastStream, IntervalStream},
+    StreamExt,
+};
+use warp::{http::StatusCode, reply, sse, Filter, Rejection, Reply};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct CreateRequest {
@@ -17,6 +27,10 @@ pub struct CreateRequest {
     pub video: VideoConfig,
     #[serde(default)]
     pub audio: AudioConfig,
+    #[serde(default)]
+    pub audio_mix: AudioMixConfig,
+    #[serde(default)]
+    pub background: Background,
 }
 
 impl CreateRequest {
@@ -25,6 +39,121 @@ impl CreateRequest {
         // (and to reject huge payloads)...
         warp::body::content_length_limit(1024 * 16).and(warp::body::json())
     }
+
+    /// The config of the mixer described by this request.
+    pub fn config(self) -> MixerConfig {
+        MixerConfig {
+            name: self.name,
+            video: self.video,
+            audio: self.audio,
+            audio_mix: self.audio_mix,
+            background: self.background,
+        }
+    }
+}
+
+/// HTTP Request for changing the video format of a mixer while it is running.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UpdateRequest {
+    pub video: VideoUpdate,
+}
+
+/// Video format fields of an [`UpdateRequest`](struct.UpdateRequest.html). Fields that are left
+/// out keep their current value.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct VideoUpdate {
+    #[serde(default)]
+    pub width: Option<i32>,
+    #[serde(default)]
+    pub height: Option<i32>,
+    #[serde(default)]
+    pub framerate: Option<i32>,
+    #[serde(default)]
+    pub format: Option<Format>,
+}
+
+impl VideoUpdate {
+    /// Returns `video` with the fields of this update applied.
+    pub fn merge(self, video: VideoConfig) -> VideoConfig {
+        VideoConfig {
+            width: self.width.unwrap_or(video.width),
+            height: self.height.unwrap_or(video.height),
+            framerate: self.framerate.unwrap_or(video.framerate),
+            format: self.format.unwrap_or(video.format.clone()),
+            ..video
+        }
+    }
+}
+
+impl UpdateRequest {
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TransitionRequest {
+    pub input: String,
+    pub transition_type: TransitionType,
+    #[serde(default = "Transition::duration_ms_default")]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+impl TransitionRequest {
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TakeRequest {
+    pub transition_type: TransitionType,
+    #[serde(default = "Transition::duration_ms_default")]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+impl TakeRequest {
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LayoutRequest {
+    pub layout: Layout,
+    pub inputs: Vec<String>,
+}
+
+impl LayoutRequest {
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BackgroundRequest {
+    #[serde(flatten)]
+    pub background: Background,
+}
+
+impl BackgroundRequest {
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -32,16 +161,14 @@ pub struct Mixer {
     pub name: String,
     pub input_count: usize,
     pub output_count: usize,
+    pub program: Option<String>,
+    pub preview: Option<String>,
+    pub video: VideoConfig,
+    pub background: Background,
 }
 
 pub async fn create(mixer: CreateRequest, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
-    let config = MixerConfig {
-        name: mixer.name,
-        video: mixer.video,
-        audio: mixer.audio,
-    };
-
-    match mixers.lock().await.mixer_create(config) {
+    match mixers.lock().await.mixer_create(mixer.config()) {
         Ok(_) => message_response("Mixer created.", StatusCode::CREATED),
         Err(e) => error(e),
     }
@@ -54,11 +181,120 @@ pub async fn get(name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult
             name: m.name(),
             input_count: m.input_count(),
             output_count: m.output_count(),
+            program: m.program(),
+            preview: m.preview(),
+            video: m.config().video,
+            background: m.config().background,
         }),
         None => message_response("Mixer not found", StatusCode::NOT_FOUND),
     }
 }
 
+pub async fn update(
+    name: String,
+    request: UpdateRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(name.as_str()) {
+        Some(m) => m,
+        None => return error(Error::NotFound),
+    };
+
+    let video = request.video.merge(mixer.config().video);
+    match mixer.video_set(&video) {
+        Ok(_) => message_response("Mixer updated", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+pub async fn remove(name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    match mixers.mixer_remove(name.as_str()) {
+        Ok(_) => message_response("Mixer removed", StatusCode::OK),
+        Err(e) => error(e),
+    }
+}
+
+/// HTTP Handler streaming the [`mixer::Event`](../mixer/enum.Event.html)'s of a mixer as
+/// Server-Sent Events. Each event is sent as JSON, tagged with its `event_type`.
+pub async fn events(
+    name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> Result<warp::reply::Response, Rejection> {
+    let receiver = match mixers.lock().await.mixers.get(name.as_str()) {
+        Some(m) => m.subscribe(),
+        None => return Err(warp::reject::custom(Error::NotFound)),
+    };
+
+    // Subscribers that fall too far behind skip the events they missed.
+    let stream = BroadcastStream::new(receiver).filter_map(|event| {
+        event
+            .ok()
+            .map(|event| sse::Event::default().json_data(event))
+    });
+
+    Ok(sse::reply(sse::keep_alive().stream(stream)).into_response())
+}
+
+/// HTTP Handler for retrieving the latest audio [`mixer::Levels`](../mixer/struct.Levels.html)
+/// of a mixer and its inputs.
+pub async fn levels(name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mixers = mixers.lock().await;
+    match mixers.mixers.get(name.as_str()) {
+        Some(m) => okay(m.meter().snapshot()),
+        None => error(Error::NotFound),
+    }
+}
+
+/// HTTP Handler streaming the audio [`mixer::Levels`](../mixer/struct.Levels.html) of a mixer as
+/// Server-Sent Events, every 100 ms. The stream ends once the mixer is removed.
+pub async fn levels_stream(
+    name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> Result<warp::reply::Response, Rejection> {
+    let meter = match mixers.lock().await.mixers.get(name.as_str()) {
+        Some(m) => m.meter(),
+        None => return Err(warp::reject::custom(Error::NotFound)),
+    };
+
+    let interval = tokio::time::interval(std::time::Duration::from_millis(100));
+    let open = meter.clone();
+    let stream = IntervalStream::new(interval)
+        .take_while(move |_| !open.is_closed())
+        .map(move |_| sse::Event::default().json_data(meter.snapshot()));
+
+    Ok(sse::reply(sse::keep_alive().stream(stream)).into_response())
+}
+
+/// HTTP Handler for retrieving the audio routing of every input on a mixer.
+pub async fn audio(name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mixers = mixers.lock().await;
+    match mixers.mixers.get(name.as_str()) {
+        Some(m) => okay(m.audio_routing()),
+        None => error(Error::NotFound),
+    }
+}
+
+/// HTTP Handler for retrieving the performance statistics of a mixer.
+pub async fn stats(name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mixers = mixers.lock().await;
+    match mixers.mixers.get(name.as_str()) {
+        Some(m) => okay(m.stats()),
+        None => error(Error::NotFound),
+    }
+}
+
+/// HTTP Handler for retrieving the tally of every input on a mixer. Changes are pushed as
+/// `TallyChanged` events.
+pub async fn tally(name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mixers = mixers.lock().await;
+    match mixers.mixers.get(name.as_str()) {
+        Some(m) => okay(m.tally()),
+        None => error(Error::NotFound),
+    }
+}
+
 pub async fn debug(
     name: String,
     mixers: Arc<Mutex<super::Mixers>>,
@@ -101,7 +337,110 @@ pub async fn list(mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
             name: m.name(),
             input_count: m.input_count(),
             output_count: m.output_count(),
+            program: m.program(),
+            preview: m.preview(),
+            video: m.config().video,
+            background: m.config().background,
         })
         .collect();
     okay(&mixers)
 }
+
+pub async fn transition(
+    name: String,
+    request: TransitionRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(name.as_str()) {
+        Some(m) => m,
+        None => return error(Error::NotFound),
+    };
+
+    let transition = Transition {
+        transition_type: request.transition_type,
+        duration_ms: request.duration_ms,
+        direction: request.direction,
+    };
+
+    match mixer.input_transition(&request.input, &transition) {
+        Ok(_) => message_response(
+            &format!("Transition to input '{}' started", request.input),
+            StatusCode::OK,
+        ),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+pub async fn layout(
+    name: String,
+    request: LayoutRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(name.as_str()) {
+        Some(m) => m,
+        None => return error(Error::NotFound),
+    };
+
+    match mixer.layout_apply(&request.layout, &request.inputs) {
+        Ok(_) => message_response(
+            &format!("Layout {:?} applied", request.layout.layout_type),
+            StatusCode::OK,
+        ),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+pub async fn background(
+    name: String,
+    request: BackgroundRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(name.as_str()) {
+        Some(m) => m,
+        None => return error(Error::NotFound),
+    };
+
+    match mixer.background_set(request.background) {
+        Ok(_) => message_response("Background updated", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+pub async fn cut(name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(name.as_str()) {
+        Some(m) => m,
+        None => return error(Error::NotFound),
+    };
+
+    match mixer.cut() {
+        Ok(_) => message_response("Preview and program swapped", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+pub async fn take(
+    name: String,
+    request: TakeRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(name.as_str()) {
+        Some(m) => m,
+        None => return error(Error::NotFound),
+    };
+
+    let transition = Transition {
+        transition_type: request.transition_type,
+        duration_ms: request.duration_ms,
+        direction: request.direction,
+    };
+
+    match mixer.take(&transition) {
+        Ok(_) => message_response("Preview taken to program", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
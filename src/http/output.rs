@@ -1,6 +1,6 @@
 use super::{error, message_response, okay, Error, JsonResult};
 use crate::{
-    output::{Config as OutputConfig, EncoderConfig, Output as MixerOutput},
+    output::{multiview::Sink, Bus, Config as OutputConfig, EncoderConfig, Output as MixerOutput},
     AudioConfig, VideoConfig,
 };
 use serde::{Deserialize, Serialize};
@@ -10,7 +10,7 @@ use warp::{http::StatusCode, Filter};
 
 /// HTTP Request for creating a new [`output::Output`](../input/struct.Output.html)
 /// to be used by the [`mixer`](../mixer/struct.Mixer.html).
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct CreateRequest {
     pub name: String,
     pub output_type: String,
@@ -21,6 +21,13 @@ pub struct CreateRequest {
     pub video: VideoConfig,
     #[serde(default)]
     pub encoder: EncoderConfig,
+    #[serde(default)]
+    pub bus: Bus,
+    #[serde(default)]
+    pub fixed_caps: bool,
+    /// Where the grid of a `Multiview` output goes.
+    #[serde(default)]
+    pub sink: Option<Sink>,
 }
 
 impl CreateRequest {
@@ -31,6 +38,22 @@ impl CreateRequest {
         // (and to reject huge payloads)...
         warp::body::content_length_limit(1024 * 16).and(warp::body::json())
     }
+
+    /// Creates the output described by this request.
+    pub fn create(self) -> Result<MixerOutput, Error> {
+        let config = OutputConfig {
+            name: self.name,
+            video: self.video,
+            audio: self.audio,
+            encoder: self.encoder,
+            mux: None,
+            bus: self.bus,
+            fixed_caps: self.fixed_caps,
+            sink: self.sink,
+        };
+
+        create(&self.output_type, config, &self.location)
+    }
 }
 
 /// HTTP Response for a [`output::Output`](../input/struct.Output.html)
@@ -39,6 +62,8 @@ pub struct Output {
     pub name: String,
     pub output_type: String,
     pub location: String,
+    pub bus: Bus,
+    pub fixed_caps: bool,
 }
 
 /// HTTP Handler for listing [`output::Output`](../output/struct.Output.html)'s associated with
@@ -58,6 +83,8 @@ pub async fn list(mixer_name: String, mixers: Arc<Mutex<super::Mixers>>) -> Json
             name: output.name(),
             output_type: output.output_type(),
             location: output.location(),
+            bus: output.config().bus,
+            fixed_caps: output.config().fixed_caps,
         })
         .collect();
     okay(&outputs)
@@ -74,22 +101,7 @@ pub async fn add(
 ) -> JsonResult {
     let mut mixers = mixers.lock().await;
 
-    let config = OutputConfig {
-        name: output.name.clone(),
-        video: output.video,
-        audio: output.audio,
-        encoder: output.encoder,
-        mux: None,
-    };
-
-    let output = match output.output_type.as_str() {
-        "RTMP" => MixerOutput::create_rtmp(config, &output.location).map_err(super::Error::Mixer),
-        "Fake" => MixerOutput::create_fake(config).map_err(super::Error::Mixer),
-        "Auto" => MixerOutput::create_auto(config).map_err(super::Error::Mixer),
-        _ => Err(super::Error::Unknown),
-    };
-
-    let output = match output {
+    let output = match output.create() {
         Err(e) => return error(e),
         Ok(i) => i,
     };
@@ -100,6 +112,22 @@ pub async fn add(
     }
 }
 
+/// Creates an output of `output_type`, as named by [`CreateRequest`](struct.CreateRequest.html).
+pub(crate) fn create(
+    output_type: &str,
+    config: OutputConfig,
+    location: &str,
+) -> Result<MixerOutput, super::Error> {
+    match output_type {
+        "RTMP" => MixerOutput::create_rtmp(config, location).map_err(super::Error::Mixer),
+        "Fake" => MixerOutput::create_fake(config).map_err(super::Error::Mixer),
+        "Auto" => MixerOutput::create_auto(config).map_err(super::Error::Mixer),
+        "File" => MixerOutput::create_file(config, location).map_err(super::Error::Mixer),
+        "Multiview" => MixerOutput::create_multiview(config).map_err(super::Error::Mixer),
+        _ => Err(super::Error::Unknown),
+    }
+}
+
 /// HTTP Handler for retrieving an [`output::Output`](../output/struct.Output.html) associated with
 /// a given mixer.
 #[tracing::instrument(skip(mixers))]
@@ -123,6 +151,8 @@ pub async fn get(
         name: output.name(),
         output_type: output.output_type(),
         location: output.location(),
+        bus: output.config().bus,
+        fixed_caps: output.config().fixed_caps,
     };
 
     okay(&output)
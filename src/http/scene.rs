@@ -0,0 +1,187 @@
+use super::{error, message_response, okay, Error, JsonResult};
+use crate::mixer::{Scene, SceneInput};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Mutex;
+use warp::{http::StatusCode, Filter};
+
+/// HTTP Request for saving a new [`mixer::Scene`](../mixer/struct.Scene.html).
+///
+/// When `inputs` is omitted the current properties of every input on the mixer are captured.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CreateRequest {
+    pub name: String,
+    #[serde(default)]
+    pub inputs: Option<HashMap<String, SceneInput>>,
+}
+
+impl CreateRequest {
+    /// Constructs a new `CreateRequest` from a json body.
+    /// This function consumes the http request body through warp::body::json().
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
+/// HTTP Request for updating a [`mixer::Scene`](../mixer/struct.Scene.html).
+///
+/// When `inputs` is omitted the current properties of every input on the mixer are captured.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UpdateRequest {
+    #[serde(default)]
+    pub inputs: Option<HashMap<String, SceneInput>>,
+}
+
+impl UpdateRequest {
+    /// Constructs a new `UpdateRequest` from a json body.
+    /// This function consumes the http request body through warp::body::json().
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+}
+
+/// HTTP Handler for listing the [`mixer::Scene`](../mixer/struct.Scene.html)'s saved on a given
+/// mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn list(mixer_name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    let scenes: Vec<&Scene> = mixer.scenes.values().collect();
+    okay(&scenes)
+}
+
+/// HTTP Handler for retrieving a [`mixer::Scene`](../mixer/struct.Scene.html) saved on a given
+/// mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn get(
+    mixer_name: String,
+    scene_name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer.scenes.get(&scene_name) {
+        None => error(Error::NotFound),
+        Some(scene) => okay(scene),
+    }
+}
+
+/// HTTP Handler for saving a [`mixer::Scene`](../mixer/struct.Scene.html) on a given mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn add(
+    mixer_name: String,
+    request: CreateRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let re = Regex::new(r"^[a-zA-Z0-9-_]+$").map_err(|_| Error::Unknown)?;
+    if !re.is_match(request.name.as_str()) {
+        return error(Error::InvalidName);
+    }
+
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    let scene = match request.inputs {
+        Some(inputs) => Scene {
+            name: request.name,
+            inputs,
+        },
+        None => mixer.scene_capture(&request.name),
+    };
+
+    match mixer.scene_add(scene) {
+        Ok(_) => message_response("Scene created.", StatusCode::CREATED),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for updating a [`mixer::Scene`](../mixer/struct.Scene.html) saved on a given
+/// mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn update(
+    mixer_name: String,
+    scene_name: String,
+    request: UpdateRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    let scene = match request.inputs {
+        Some(inputs) => Scene {
+            name: scene_name,
+            inputs,
+        },
+        None => mixer.scene_capture(&scene_name),
+    };
+
+    match mixer.scene_update(scene) {
+        Ok(_) => message_response("Scene updated", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for removing a [`mixer::Scene`](../mixer/struct.Scene.html) from a given mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn remove(
+    mixer_name: String,
+    scene_name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer.scene_remove(&scene_name) {
+        Ok(_) => message_response("Scene removed", StatusCode::OK),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
+
+/// HTTP Handler for applying a [`mixer::Scene`](../mixer/struct.Scene.html) to the inputs of a
+/// given mixer.
+///
+/// All properties of the scene take effect on the same frame, viewers never see a partially
+/// applied scene.
+#[tracing::instrument(skip(mixers))]
+pub async fn apply(
+    mixer_name: String,
+    scene_name: String,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    let mixer = match mixers.mixers.get_mut(&mixer_name) {
+        None => return error(Error::NotFound),
+        Some(mixer) => mixer,
+    };
+
+    match mixer.scene_apply(&scene_name) {
+        Ok(_) => message_response(
+            &format!("Scene '{}' applied", scene_name),
+            StatusCode::OK,
+        ),
+        Err(e) => error(Error::Mixer(e)),
+    }
+}
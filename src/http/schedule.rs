@@ -0,0 +1,207 @@
+use super::{
+    error, input::CreateRequest as InputCreateRequest, message_response, okay,
+    output::CreateRequest as OutputCreateRequest, Error, JsonResult,
+};
+use crate::{
+    output::{Bus, EncoderConfig},
+    AudioConfig, VideoConfig,
+};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Mutex;
+use warp::{http::StatusCode, reply, Filter};
+
+/// How late a restored action may be and still run, in milliseconds. Actions that were due
+/// longer ago when the state is restored are dropped.
+pub const MISSED_GRACE_MS: u64 = 10_000;
+
+/// An action on a mixer, run by the scheduler at the time it was scheduled for.
+///
+/// Scheduled actions live in memory. They survive a restart only when the server is started
+/// with `--state`, in which case they are saved with the rest of the state.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "action")]
+pub enum Action {
+    /// Puts `input` on program.
+    SetActive {
+        input: String,
+    },
+    InputAdd {
+        input: InputCreateRequest,
+    },
+    InputRemove {
+        input: String,
+    },
+    OutputAdd {
+        output: OutputCreateRequest,
+    },
+    OutputRemove {
+        output: String,
+    },
+    SceneApply {
+        scene: String,
+    },
+    /// Adds a `File` output named `output`. Recordings go to
+    /// `./recordings/{mixer}_{output}_{timestamp}.mkv` unless a location is given. Remove the
+    /// output to stop recording.
+    RecordStart {
+        output: String,
+        #[serde(default)]
+        location: Option<String>,
+    },
+}
+
+/// An action waiting for its time to come.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Scheduled {
+    pub id: u64,
+    /// When the action runs, in milliseconds since the unix epoch.
+    pub at: u64,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// HTTP Request for scheduling an [`Action`](enum.Action.html) on a mixer, either at an
+/// absolute time or after a delay.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CreateRequest {
+    /// When to run the action, in milliseconds since the unix epoch.
+    #[serde(default)]
+    pub at: Option<u64>,
+    /// How long from now to run the action.
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+impl CreateRequest {
+    /// Constructs a new `CreateRequest` from a json body.
+    /// This function consumes the http request body through warp::body::json().
+    pub fn from_json_body() -> impl Filter<Extract = (Self,), Error = warp::Rejection> + Clone {
+        // When accepting a body, we want a JSON body
+        // (and to reject huge payloads)...
+        warp::body::content_length_limit(1024 * 16).and(warp::body::json())
+    }
+
+    /// The time the action runs at, in milliseconds since the unix epoch.
+    pub fn at(&self, now: u64) -> Result<u64, Error> {
+        match (self.at, self.delay_ms) {
+            (Some(at), None) => Ok(at),
+            (None, Some(delay)) => now
+                .checked_add(delay)
+                .ok_or_else(|| Error::Schedule("'delay_ms' is too large".to_string())),
+            _ => Err(Error::Schedule(
+                "set exactly one of 'at' and 'delay_ms'".to_string(),
+            )),
+        }
+    }
+}
+
+impl Action {
+    /// Runs the action on `mixer`.
+    pub(crate) fn run(self, mixers: &mut super::Mixers, mixer: &str) -> Result<(), Error> {
+        match self {
+            Action::SetActive { input } => {
+                let mixer = mixers.mixers.get_mut(mixer).ok_or(Error::NotFound)?;
+                mixer.input_set_active(&input)?;
+                Ok(())
+            }
+            Action::InputAdd { input } => mixers.input_add(mixer, input.create()?),
+            Action::InputRemove { input } => mixers.input_remove(mixer, &input),
+            Action::OutputAdd { output } => mixers.output_add(mixer, output.create()?),
+            Action::OutputRemove { output } => mixers.output_remove(mixer, &output),
+            Action::SceneApply { scene } => {
+                let mixer = mixers.mixers.get_mut(mixer).ok_or(Error::NotFound)?;
+                mixer.scene_apply(&scene)?;
+                Ok(())
+            }
+            Action::RecordStart { output, location } => {
+                let location = location
+                    .unwrap_or_else(|| format!("./recordings/{}_{}_{}.mkv", mixer, output, now()));
+                if let Some(dir) = Path::new(&location).parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| {
+                        Error::Schedule(format!("creating {}: {}", dir.display(), e))
+                    })?;
+                }
+                let request = OutputCreateRequest {
+                    name: output,
+                    output_type: "File".to_string(),
+                    location,
+                    audio: AudioConfig::default(),
+                    video: VideoConfig::default(),
+                    encoder: EncoderConfig::default(),
+                    bus: Bus::default(),
+                    fixed_caps: false,
+                    sink: None,
+                };
+                mixers.output_add(mixer, request.create()?)
+            }
+        }
+    }
+}
+
+/// The current time, in milliseconds since the unix epoch.
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// HTTP Handler for scheduling an [`Action`](enum.Action.html) on a given mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn add(
+    mixer_name: String,
+    request: CreateRequest,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> JsonResult {
+    let at = match request.at(now()) {
+        Ok(at) => at,
+        Err(e) => return error(e),
+    };
+
+    let mut mixers = mixers.lock().await;
+    match mixers.schedule_add(&mixer_name, at, request.action) {
+        Ok(scheduled) => Ok(reply::with_status(
+            reply::json(&scheduled),
+            StatusCode::CREATED,
+        )),
+        Err(e) => error(e),
+    }
+}
+
+/// HTTP Handler for listing the actions scheduled on a given mixer, in the order they run.
+#[tracing::instrument(skip(mixers))]
+pub async fn list(mixer_name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mixers = mixers.lock().await;
+    match mixers.schedule(&mixer_name) {
+        Ok(schedule) => okay(&schedule),
+        Err(e) => error(e),
+    }
+}
+
+/// HTTP Handler for cancelling a scheduled action.
+#[tracing::instrument(skip(mixers))]
+pub async fn remove(mixer_name: String, id: u64, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    match mixers.schedule_remove(&mixer_name, id) {
+        Ok(_) => message_response("Scheduled action removed", StatusCode::OK),
+        Err(e) => error(e),
+    }
+}
+
+/// HTTP Handler for cancelling every action scheduled on a given mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn clear(mixer_name: String, mixers: Arc<Mutex<super::Mixers>>) -> JsonResult {
+    let mut mixers = mixers.lock().await;
+    match mixers.schedule_clear(&mixer_name) {
+        Ok(_) => message_response("Schedule cleared", StatusCode::OK),
+        Err(e) => error(e),
+    }
+}
@@ -0,0 +1,80 @@
+use super::Error;
+use crate::mixer::{snapshot, ImageFormat};
+
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use warp::{reject, reply::Response, Rejection, Reply};
+
+/// Query parameters of the snapshot handlers.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Query {
+    /// Width to scale the snapshot to, keeping its aspect ratio. Defaults to the width of the
+    /// frame, which is also the widest a snapshot gets, up to
+    /// [`MAX_WIDTH`](../../mixer/snapshot/constant.MAX_WIDTH.html).
+    #[serde(default)]
+    pub width: Option<i32>,
+}
+
+/// HTTP Handler for a still of the program output of a given mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn program(
+    mixer_name: String,
+    format: ImageFormat,
+    query: Query,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> Result<Response, Rejection> {
+    snapshot::validate_width(query.width).map_err(|e| reject::custom(Error::Mixer(e)))?;
+    let sample = {
+        let mixers = mixers.lock().await;
+        let mixer = mixers
+            .mixers
+            .get(&mixer_name)
+            .ok_or_else(|| reject::custom(Error::NotFound))?;
+        mixer
+            .snapshot(None)
+            .map_err(|e| reject::custom(Error::Mixer(e)))?
+    };
+
+    encode(sample, format, query.width).await
+}
+
+/// HTTP Handler for a still of an [`input::Input`](../input/struct.Input.html) associated with
+/// a given mixer.
+#[tracing::instrument(skip(mixers))]
+pub async fn input(
+    mixer_name: String,
+    input_name: String,
+    format: ImageFormat,
+    query: Query,
+    mixers: Arc<Mutex<super::Mixers>>,
+) -> Result<Response, Rejection> {
+    snapshot::validate_width(query.width).map_err(|e| reject::custom(Error::Mixer(e)))?;
+    let sample = {
+        let mixers = mixers.lock().await;
+        let mixer = mixers
+            .mixers
+            .get(&mixer_name)
+            .ok_or_else(|| reject::custom(Error::NotFound))?;
+        mixer
+            .snapshot(Some(&input_name))
+            .map_err(|e| reject::custom(Error::Mixer(e)))?
+    };
+
+    encode(sample, format, query.width).await
+}
+
+/// Encodes the frame off the async runtime, the mixers stay unlocked meanwhile.
+async fn encode(
+    sample: gst::Sample,
+    format: ImageFormat,
+    width: Option<i32>,
+) -> Result<Response, Rejection> {
+    let image = tokio::task::spawn_blocking(move || snapshot::encode(&sample, format, width))
+        .await
+        .map_err(|_| reject::custom(Error::Unknown))?
+        .map_err(|e| reject::custom(Error::Mixer(e)))?;
+
+    Ok(warp::reply::with_header(image, "Content-Type", format.content_type()).into_response())
+}
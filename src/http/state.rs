@@ -0,0 +1,203 @@
+use super::{
+    schedule::{Action, Scheduled},
+    Error, Mixers,
+};
+use crate::{
+    input::Config as InputConfig,
+    mixer::{AudioRoute, Config as MixerConfig, Scene},
+    output::Config as OutputConfig,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// Declarative state of every mixer on the server, enough to rebuild them after a restart.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct State {
+    pub mixers: Vec<MixerState>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MixerState {
+    pub config: MixerConfig,
+    pub inputs: Vec<InputState>,
+    pub outputs: Vec<OutputState>,
+    #[serde(default)]
+    pub scenes: Vec<Scene>,
+    /// The input on program.
+    #[serde(default)]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub preview: Option<String>,
+    /// Audio routing of the inputs that don't use the default route, by input name.
+    #[serde(default)]
+    pub audio_routes: BTreeMap<String, AudioRoute>,
+    /// Actions waiting to run, see [`schedule`](../schedule/index.html).
+    #[serde(default)]
+    pub schedule: Vec<ScheduledState>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct InputState {
+    pub input_type: String,
+    pub location: String,
+    pub config: InputConfig,
+    /// The input is encrypted. Its passphrase is not saved, it is read from
+    /// [`passphrase_var`](fn.passphrase_var.html) on restore.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ScheduledState {
+    #[serde(flatten)]
+    pub scheduled: Scheduled,
+    /// The action adds an encrypted input. Its passphrase is not saved, it is read from
+    /// [`passphrase_var`](fn.passphrase_var.html) on restore.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
+}
+
+impl ScheduledState {
+    /// Leaves out the passphrase of the input the action adds.
+    fn capture(mut scheduled: Scheduled) -> Self {
+        let passphrase = match &mut scheduled.action {
+            Action::InputAdd { input } => input.srt.as_mut().and_then(|srt| srt.passphrase.take()),
+            _ => None,
+        };
+        Self {
+            scheduled,
+            encrypted: passphrase.is_some(),
+        }
+    }
+
+    /// The scheduled action, with the passphrase of the input it adds read from its
+    /// [`passphrase_var`](fn.passphrase_var.html). Without a passphrase the action is dropped,
+    /// rather than adding the input unencrypted.
+    pub fn restore(self, mixer: &str) -> Option<Scheduled> {
+        let mut scheduled = self.scheduled;
+        if let (true, Action::InputAdd { input }) = (self.encrypted, &mut scheduled.action) {
+            let var = passphrase_var(mixer, &input.name);
+            match std::env::var(&var) {
+                Ok(passphrase) => {
+                    if let Some(srt) = input.srt.as_mut() {
+                        srt.passphrase = Some(passphrase);
+                    }
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        "{}: dropping scheduled action {}, set {} to the passphrase of input {}",
+                        mixer,
+                        scheduled.id,
+                        var,
+                        input.name
+                    );
+                    return None;
+                }
+            }
+        }
+        Some(scheduled)
+    }
+}
+
+/// Environment variable the passphrase of the encrypted input `input` of `mixer` is read from on
+/// restore. It is named `SRT_PASSPHRASE_{MIXER}_{INPUT}` in upper case, with `-` turned into `_`,
+/// such as `SRT_PASSPHRASE_STUDIO_REMOTE_CAM` for input `remote-cam` of mixer `studio`.
+pub fn passphrase_var(mixer: &str, input: &str) -> String {
+    format!("SRT_PASSPHRASE_{}_{}", mixer, input)
+        .chars()
+        .map(|c| match c {
+            '-' => '_',
+            c => c.to_ascii_uppercase(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct OutputState {
+    pub output_type: String,
+    pub location: String,
+    pub config: OutputConfig,
+}
+
+impl State {
+    /// Captures the state of `mixers`. Everything is sorted by name, so capturing unchanged
+    /// mixers twice gives the same state.
+    pub fn capture(mixers: &Mixers) -> Self {
+        let mut states: Vec<MixerState> = mixers
+            .mixers
+            .iter()
+            .map(|(name, mixer)| {
+                let mut inputs: Vec<InputState> = mixer
+                    .inputs
+                    .values()
+                    .map(|input| {
+                        let mut config = input.config();
+                        let passphrase = config.srt.as_mut().and_then(|srt| srt.passphrase.take());
+                        InputState {
+                            input_type: input.input_type(),
+                            location: input.location(),
+                            config,
+                            encrypted: passphrase.is_some(),
+                        }
+                    })
+                    .collect();
+                inputs.sort_by(|a, b| a.config.name.cmp(&b.config.name));
+
+                let mut outputs: Vec<OutputState> = mixer
+                    .outputs
+                    .values()
+                    .map(|output| OutputState {
+                        output_type: output.output_type(),
+                        location: output.location(),
+                        config: output.config(),
+                    })
+                    .collect();
+                outputs.sort_by(|a, b| a.config.name.cmp(&b.config.name));
+
+                let mut scenes: Vec<Scene> = mixer.scenes.values().cloned().collect();
+                scenes.sort_by(|a, b| a.name.cmp(&b.name));
+
+                let audio_routes = mixer
+                    .audio_routing()
+                    .into_iter()
+                    .filter(|status| status.route != AudioRoute::default())
+                    .map(|status| (status.input, status.route))
+                    .collect();
+
+                MixerState {
+                    config: mixer.config(),
+                    inputs,
+                    outputs,
+                    scenes,
+                    active: mixer.program(),
+                    preview: mixer.preview(),
+                    audio_routes,
+                    schedule: mixers
+                        .schedule(name)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(ScheduledState::capture)
+                        .collect(),
+                }
+            })
+            .collect();
+        states.sort_by(|a, b| a.config.name.cmp(&b.config.name));
+
+        Self { mixers: states }
+    }
+
+    /// Reads the state from the JSON file at `path`.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let data = std::fs::read(path).map_err(|e| Error::State(e.to_string()))?;
+        serde_json::from_slice(&data).map_err(|e| Error::State(e.to_string()))
+    }
+
+    /// Writes the state as JSON to `path`. The file is replaced in a single step, so a crash
+    /// while writing leaves the previous state in place.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let data = serde_json::to_vec_pretty(self).map_err(|e| Error::State(e.to_string()))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, data).map_err(|e| Error::State(e.to_string()))?;
+        std::fs::rename(&tmp, path).map_err(|e| Error::State(e.to_string()))
+    }
+}
@@ -1,26 +1,208 @@
 pub mod fake;
+pub mod playlist;
+pub mod rtmp;
+pub mod srt;
 pub mod test;
 pub mod uri;
 
 use crate::{mixer::Error as MixerError, AudioConfig, Result, VideoConfig};
 pub use fake::Fake;
+pub use playlist::Playlist;
+pub use rtmp::RTMP;
 use serde::{Deserialize, Serialize};
+pub use srt::SRT;
 pub use test::Test;
 pub use uri::URI;
 
 use gst::prelude::*;
 use gstreamer as gst;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// Longest audio or video offset of an input.
+pub const MAX_OFFSET_MS: i64 = 10_000;
+
+/// Room a delayed queue keeps on top of its offset, the default size of a `queue`.
+const QUEUE_HEADROOM_MS: u64 = 1_000;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
 pub struct Config {
     pub name: String,
     pub video: VideoConfig,
     pub audio: AudioConfig,
     pub record: bool,
+    #[serde(default)]
+    pub failover: Option<FailoverConfig>,
+    /// Time without a frame after which the input is considered stalled. Defaults to 2 seconds.
+    #[serde(default)]
+    pub stall_timeout_ms: Option<u64>,
+    /// Delay applied to the audio of the input, to bring it in sync with the video. Up to
+    /// [`MAX_OFFSET_MS`](constant.MAX_OFFSET_MS.html), audio that is late is synced by delaying
+    /// the video instead.
+    #[serde(default)]
+    pub audio_offset_ms: i64,
+    /// Delay applied to the video of the input, to bring it in sync with the audio. Up to
+    /// [`MAX_OFFSET_MS`](constant.MAX_OFFSET_MS.html), video that is late is synced by delaying
+    /// the audio instead.
+    #[serde(default)]
+    pub video_offset_ms: i64,
+    /// Media played by a `Playlist` input, unused by the other inputs.
+    #[serde(default)]
+    pub playlist: Option<PlaylistConfig>,
+    /// Where an `RTMP` input listens for a publisher, unused by the other inputs.
+    #[serde(default)]
+    pub rtmp: Option<RTMPConfig>,
+    /// How an `SRT` input connects, unused by the other inputs.
+    #[serde(default)]
+    pub srt: Option<SRTConfig>,
+}
+
+/// Rules for replacing an input on program with a fallback input when it fails.
+///
+/// An input has failed when it reports an error, reaches the end of its stream or stalls. It has
+/// recovered once it has produced frames for the length of its stall timeout again.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct FailoverConfig {
+    /// Name of the input put on program while this input has failed. It must have been added
+    /// before this input.
+    pub input: String,
+    /// Deprecated, set `stall_timeout_ms` on the input instead. That takes precedence when both
+    /// are set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub recovery: Recovery,
+}
+
+/// What happens once an input that failed over has recovered.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum Recovery {
+    /// Switch back to the recovered input.
+    #[default]
+    Automatic,
+    /// Stay on the fallback input until an operator switches back.
+    Manual,
+}
+
+/// Media played back-to-back by a [`Playlist`](playlist/struct.Playlist.html) input.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct PlaylistConfig {
+    /// URIs of the media, in the order they play.
+    #[serde(default)]
+    pub items: Vec<String>,
+    /// Start over from the first item once the last one ended.
+    #[serde(default, rename = "loop")]
+    pub looping: bool,
+    /// Play the items in a random order, reshuffled on every pass.
+    #[serde(default)]
+    pub shuffle: bool,
+}
+
+/// Where an [`RTMP`](rtmp/struct.RTMP.html) input listens for an encoder to publish to it.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RTMPConfig {
+    /// Address to listen on, every interface by default.
+    #[serde(default = "RTMPConfig::address_default")]
+    pub address: String,
+    #[serde(default = "RTMPConfig::port_default")]
+    pub port: u16,
+    #[serde(default = "RTMPConfig::app_default")]
+    pub app: String,
+    /// Defaults to the name of the input.
+    #[serde(default)]
+    pub stream_key: Option<String>,
+}
+
+impl RTMPConfig {
+    fn address_default() -> String {
+        "0.0.0.0".to_string()
+    }
+
+    fn port_default() -> u16 {
+        1935
+    }
+
+    fn app_default() -> String {
+        "live".to_string()
+    }
+}
+
+impl Default for RTMPConfig {
+    fn default() -> Self {
+        Self {
+            address: Self::address_default(),
+            port: Self::port_default(),
+            app: Self::app_default(),
+            stream_key: None,
+        }
+    }
+}
+
+/// How an [`SRT`](srt/struct.SRT.html) input connects to its sender.
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct SRTConfig {
+    #[serde(default)]
+    pub mode: SRTMode,
+    /// Time SRT has to recover lost packets before they are played.
+    #[serde(default = "SRTConfig::latency_default")]
+    pub latency_ms: i32,
+    /// Encrypts the stream, the sender has to use the same passphrase. It is left out of logs
+    /// and of the state file, encrypted inputs are restored with the passphrase set in the
+    /// environment, see [`passphrase_var`](../http/state/fn.passphrase_var.html).
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+impl std::fmt::Debug for SRTConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SRTConfig")
+            .field("mode", &self.mode)
+            .field("latency_ms", &self.latency_ms)
+            .field(
+                "passphrase",
+                &self.passphrase.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+impl SRTConfig {
+    fn latency_default() -> i32 {
+        125
+    }
+}
+
+impl Default for SRTConfig {
+    fn default() -> Self {
+        Self {
+            mode: SRTMode::default(),
+            latency_ms: Self::latency_default(),
+            passphrase: None,
+        }
+    }
+}
+
+/// Whether an `SRT` input waits for the sender to connect, or connects to the sender.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum SRTMode {
+    Caller,
+    #[default]
+    Listener,
+}
+
+impl SRTMode {
+    /// The name `srtsrc` knows the mode by.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SRTMode::Caller => "caller",
+            SRTMode::Listener => "listener",
+        }
+    }
 }
 
 pub enum Input {
     URI(URI),
+    RTMP(RTMP),
+    SRT(SRT),
+    Playlist(Playlist),
     Test(Test),
     Fake(Fake),
 }
@@ -30,6 +212,18 @@ impl Input {
         URI::create(config, uri).map(Self::URI)
     }
 
+    pub fn create_srt(config: Config, location: &str) -> Result<Self> {
+        SRT::create(config, location).map(Self::SRT)
+    }
+
+    pub fn create_rtmp(config: Config) -> Result<Self> {
+        RTMP::create(config).map(Self::RTMP)
+    }
+
+    pub fn create_playlist(config: Config) -> Result<Self> {
+        Playlist::create(config).map(Self::Playlist)
+    }
+
     pub fn create_test(config: Config) -> Result<Self> {
         Test::create(config).map(Self::Test)
     }
@@ -41,6 +235,9 @@ impl Input {
     pub fn name(&self) -> String {
         match self {
             Input::URI(input) => input.name(),
+            Input::RTMP(input) => input.name(),
+            Input::SRT(input) => input.name(),
+            Input::Playlist(input) => input.name(),
             Input::Test(input) => input.name(),
             Input::Fake(input) => input.name(),
         }
@@ -49,6 +246,9 @@ impl Input {
     pub fn location(&self) -> String {
         match self {
             Input::URI(input) => input.location.clone(),
+            Input::RTMP(input) => input.location(),
+            Input::SRT(input) => input.location.clone(),
+            Input::Playlist(input) => input.location(),
             Input::Test(_) => "".to_string(),
             Input::Fake(_) => "".to_string(),
         }
@@ -57,27 +257,52 @@ impl Input {
     pub fn input_type(&self) -> String {
         match self {
             Input::URI(_) => "URI".to_string(),
+            Input::RTMP(_) => "RTMP".to_string(),
+            Input::SRT(_) => "SRT".to_string(),
+            Input::Playlist(_) => "Playlist".to_string(),
             Input::Test(_) => "Test".to_string(),
             Input::Fake(_) => "Fake".to_string(),
         }
     }
 
+    /// Links this input to the mixers of the program bus. The audio of the input is converted to
+    /// `audio_caps`, the master audio format of the mixer.
     pub fn link(
         &mut self,
         pipeline: gst::Pipeline,
         audio: gst::Element,
         video: gst::Element,
+        audio_caps: &gst::Caps,
     ) -> Result<()> {
         match self {
-            Input::URI(input) => input.link(pipeline, audio, video),
-            Input::Test(input) => input.link(pipeline, audio, video),
+            Input::URI(input) => input.link(pipeline, audio, video, audio_caps),
+            Input::RTMP(input) => input.link(pipeline, audio, video, audio_caps),
+            Input::SRT(input) => input.link(pipeline, audio, video, audio_caps),
+            Input::Playlist(input) => input.link(pipeline, audio, video, audio_caps),
+            Input::Test(input) => input.link(pipeline, audio, video, audio_caps),
             Input::Fake(input) => input.link(pipeline, audio, video),
         }
     }
 
+    /// Links this input to the compositor of the preview bus. Must be called after
+    /// [`link`](#method.link).
+    pub fn link_preview(&mut self, video: gst::Element) -> Result<()> {
+        match self {
+            Input::URI(input) => input.link_preview(video),
+            Input::RTMP(input) => input.link_preview(video),
+            Input::SRT(input) => input.link_preview(video),
+            Input::Playlist(input) => input.link_preview(video),
+            Input::Test(input) => input.link_preview(video),
+            Input::Fake(_) => Ok(()),
+        }
+    }
+
     pub fn unlink(&self) -> Result<()> {
         match self {
             Input::URI(input) => input.unlink(),
+            Input::RTMP(input) => input.unlink(),
+            Input::SRT(input) => input.unlink(),
+            Input::Playlist(input) => input.unlink(),
             Input::Test(input) => input.unlink(),
             Input::Fake(input) => input.unlink(),
         }
@@ -86,6 +311,9 @@ impl Input {
     pub fn set_state(&mut self, state: gst::State) -> Result<()> {
         match self {
             Input::URI(input) => input.set_state(state),
+            Input::RTMP(input) => input.set_state(state),
+            Input::SRT(input) => input.set_state(state),
+            Input::Playlist(input) => input.set_state(state),
             Input::Test(input) => input.set_state(state),
             Input::Fake(input) => input.set_state(state),
         }
@@ -94,6 +322,9 @@ impl Input {
     pub fn set_volume(&mut self, volume: f64, update_config: bool) -> Result<()> {
         match self {
             Input::URI(input) => input.set_volume(volume, update_config),
+            Input::RTMP(input) => input.set_volume(volume, update_config),
+            Input::SRT(input) => input.set_volume(volume, update_config),
+            Input::Playlist(input) => input.set_volume(volume, update_config),
             Input::Test(input) => input.set_volume(volume, update_config),
             Input::Fake(input) => input.set_volume(volume, update_config),
         }
@@ -102,6 +333,9 @@ impl Input {
     pub fn set_zorder(&mut self, zorder: u32, update_config: bool) -> Result<()> {
         match self {
             Input::URI(input) => input.set_zorder(zorder, update_config),
+            Input::RTMP(input) => input.set_zorder(zorder, update_config),
+            Input::SRT(input) => input.set_zorder(zorder, update_config),
+            Input::Playlist(input) => input.set_zorder(zorder, update_config),
             Input::Test(input) => input.set_zorder(zorder, update_config),
             Input::Fake(input) => input.set_zorder(zorder, update_config),
         }
@@ -110,6 +344,9 @@ impl Input {
     pub fn set_width(&mut self, width: i32, update_config: bool) -> Result<()> {
         match self {
             Input::URI(input) => input.set_width(width, update_config),
+            Input::RTMP(input) => input.set_width(width, update_config),
+            Input::SRT(input) => input.set_width(width, update_config),
+            Input::Playlist(input) => input.set_width(width, update_config),
             Input::Test(input) => input.set_width(width, update_config),
             Input::Fake(input) => input.set_width(width, update_config),
         }
@@ -118,6 +355,9 @@ impl Input {
     pub fn set_height(&mut self, height: i32, update_config: bool) -> Result<()> {
         match self {
             Input::URI(input) => input.set_height(height, update_config),
+            Input::RTMP(input) => input.set_height(height, update_config),
+            Input::SRT(input) => input.set_height(height, update_config),
+            Input::Playlist(input) => input.set_height(height, update_config),
             Input::Test(input) => input.set_height(height, update_config),
             Input::Fake(input) => input.set_height(height, update_config),
         }
@@ -126,6 +366,9 @@ impl Input {
     pub fn set_xpos(&mut self, xpos: i32, update_config: bool) -> Result<()> {
         match self {
             Input::URI(input) => input.set_xpos(xpos, update_config),
+            Input::RTMP(input) => input.set_xpos(xpos, update_config),
+            Input::SRT(input) => input.set_xpos(xpos, update_config),
+            Input::Playlist(input) => input.set_xpos(xpos, update_config),
             Input::Test(input) => input.set_xpos(xpos, update_config),
             Input::Fake(input) => input.set_xpos(xpos, update_config),
         }
@@ -134,6 +377,9 @@ impl Input {
     pub fn set_ypos(&mut self, ypos: i32, update_config: bool) -> Result<()> {
         match self {
             Input::URI(input) => input.set_ypos(ypos, update_config),
+            Input::RTMP(input) => input.set_ypos(ypos, update_config),
+            Input::SRT(input) => input.set_ypos(ypos, update_config),
+            Input::Playlist(input) => input.set_ypos(ypos, update_config),
             Input::Test(input) => input.set_ypos(ypos, update_config),
             Input::Fake(input) => input.set_ypos(ypos, update_config),
         }
@@ -142,18 +388,134 @@ impl Input {
     pub fn set_alpha(&mut self, alpha: f64, update_config: bool) -> Result<()> {
         match self {
             Input::URI(input) => input.set_alpha(alpha, update_config),
+            Input::RTMP(input) => input.set_alpha(alpha, update_config),
+            Input::SRT(input) => input.set_alpha(alpha, update_config),
+            Input::Playlist(input) => input.set_alpha(alpha, update_config),
             Input::Test(input) => input.set_alpha(alpha, update_config),
             Input::Fake(input) => input.set_alpha(alpha, update_config),
         }
     }
 
+    pub fn set_audio_offset(&mut self, offset_ms: i64, update_config: bool) -> Result<()> {
+        match self {
+            Input::URI(input) => input.set_audio_offset(offset_ms, update_config),
+            Input::RTMP(input) => input.set_audio_offset(offset_ms, update_config),
+            Input::SRT(input) => input.set_audio_offset(offset_ms, update_config),
+            Input::Playlist(input) => input.set_audio_offset(offset_ms, update_config),
+            Input::Test(input) => input.set_audio_offset(offset_ms, update_config),
+            Input::Fake(input) => input.set_audio_offset(offset_ms, update_config),
+        }
+    }
+
+    pub fn set_video_offset(&mut self, offset_ms: i64, update_config: bool) -> Result<()> {
+        match self {
+            Input::URI(input) => input.set_video_offset(offset_ms, update_config),
+            Input::RTMP(input) => input.set_video_offset(offset_ms, update_config),
+            Input::SRT(input) => input.set_video_offset(offset_ms, update_config),
+            Input::Playlist(input) => input.set_video_offset(offset_ms, update_config),
+            Input::Test(input) => input.set_video_offset(offset_ms, update_config),
+            Input::Fake(input) => input.set_video_offset(offset_ms, update_config),
+        }
+    }
+
     pub fn config(&self) -> Config {
         match self {
             Input::URI(input) => input.config(),
+            Input::RTMP(input) => input.config(),
+            Input::SRT(input) => input.config(),
+            Input::Playlist(input) => input.config(),
             Input::Test(input) => input.config(),
             Input::Fake(input) => input.config(),
         }
     }
+
+    /// Returns the compositor sink pad this input is linked to, if it has been linked.
+    pub fn compositor_pad(&self) -> Option<gst::Pad> {
+        match self {
+            Input::URI(input) => input.compositor_pad(),
+            Input::RTMP(input) => input.compositor_pad(),
+            Input::SRT(input) => input.compositor_pad(),
+            Input::Playlist(input) => input.compositor_pad(),
+            Input::Test(input) => input.compositor_pad(),
+            Input::Fake(input) => input.compositor_pad(),
+        }
+    }
+
+    /// Returns the pad the video of this input leaves it by, towards the compositor.
+    pub fn video_src_pad(&self) -> Option<gst::Pad> {
+        match self {
+            Input::URI(input) => input.video_src_pad(),
+            Input::RTMP(input) => input.video_src_pad(),
+            Input::SRT(input) => input.video_src_pad(),
+            Input::Playlist(input) => input.video_src_pad(),
+            Input::Test(input) => input.video_src_pad(),
+            Input::Fake(input) => input.video_src_pad(),
+        }
+    }
+
+    /// Returns the pad the audio of this input leaves it by, towards the audio mixer.
+    pub fn audio_src_pad(&self) -> Option<gst::Pad> {
+        match self {
+            Input::URI(input) => input.audio_src_pad(),
+            Input::RTMP(input) => input.audio_src_pad(),
+            Input::SRT(input) => input.audio_src_pad(),
+            Input::Playlist(input) => input.audio_src_pad(),
+            Input::Test(input) => input.audio_src_pad(),
+            Input::Fake(input) => input.audio_src_pad(),
+        }
+    }
+
+    /// Returns the preview compositor sink pad this input is linked to, if it has been linked.
+    pub fn preview_pad(&self) -> Option<gst::Pad> {
+        match self {
+            Input::URI(input) => input.preview_pad(),
+            Input::RTMP(input) => input.preview_pad(),
+            Input::SRT(input) => input.preview_pad(),
+            Input::Playlist(input) => input.preview_pad(),
+            Input::Test(input) => input.preview_pad(),
+            Input::Fake(_) => None,
+        }
+    }
+
+    /// Returns the tee the converted video of this input is split on, if it has video.
+    pub fn video_tee(&self) -> Option<gst::Element> {
+        match self {
+            Input::URI(input) => Some(input.video_tee()),
+            Input::RTMP(input) => Some(input.video_tee()),
+            Input::SRT(input) => Some(input.video_tee()),
+            Input::Playlist(input) => Some(input.video_tee()),
+            Input::Test(input) => Some(input.video_tee()),
+            Input::Fake(_) => None,
+        }
+    }
+
+    /// Returns whether an encoder is publishing to this input, for `RTMP` inputs.
+    pub fn rtmp_status(&self) -> Option<rtmp::Status> {
+        match self {
+            Input::RTMP(input) => Some(input.status()),
+            _ => None,
+        }
+    }
+
+    /// Returns the connection statistics of `SRT` inputs.
+    pub fn srt_stats(&self) -> Option<srt::Stats> {
+        match self {
+            Input::SRT(input) => input.stats(),
+            _ => None,
+        }
+    }
+
+    /// Returns the `volume` element controlling this input's level, if it has one.
+    pub fn volume_element(&self) -> Option<gst::Element> {
+        match self {
+            Input::URI(input) => Some(input.volume_element()),
+            Input::RTMP(input) => Some(input.volume_element()),
+            Input::SRT(input) => Some(input.volume_element()),
+            Input::Playlist(input) => Some(input.volume_element()),
+            Input::Test(_) => None,
+            Input::Fake(_) => None,
+        }
+    }
 }
 
 fn set_peer_pad_property(pad: &gst::Pad, property: &str, value: &dyn ToValue) -> Result<()> {
@@ -165,6 +527,50 @@ fn set_peer_pad_property(pad: &gst::Pad, property: &str, value: &dyn ToValue) ->
     Ok(())
 }
 
+/// Checks that `offset_ms` can be used as an audio or video offset. Offsets only delay, the mixers
+/// would drop buffers moved earlier as late.
+pub fn validate_offset(offset_ms: i64) -> Result<()> {
+    if !(0..=MAX_OFFSET_MS).contains(&offset_ms) {
+        return Err(MixerError::InvalidOffset(offset_ms, MAX_OFFSET_MS));
+    }
+
+    Ok(())
+}
+
+/// Delays the buffers leaving the queue `elem` by `offset_ms`, on top of any offset set upstream.
+/// The queue is grown to hold the delayed buffers, so that it does not block its source while
+/// the mixers wait for them.
+fn set_src_pad_offset(elem: &gst::Element, offset_ms: i64) -> Result<()> {
+    validate_offset(offset_ms)?;
+    let pad = elem.static_pad("src").ok_or_else(|| {
+        MixerError::Gstreamer("Failed to get static src pad for element".to_string())
+    })?;
+
+    if offset_ms > 0 {
+        elem.set_property("max-size-buffers", 0u32);
+        elem.set_property("max-size-bytes", 0u32);
+        elem.set_property(
+            "max-size-time",
+            (offset_ms as u64 + QUEUE_HEADROOM_MS) * 1_000_000,
+        );
+    }
+    pad.set_offset(offset_ms * 1_000_000);
+
+    Ok(())
+}
+
+/// Removes those of `elements` that are in `pipeline`. An input whose linking failed part way,
+/// for example before its preview branch was added, holds elements that never were.
+fn remove_added(pipeline: &gst::Pipeline, elements: &[&gst::Element]) -> Result<()> {
+    for element in elements {
+        if element.parent().as_ref() == Some(pipeline.upcast_ref()) {
+            pipeline.remove(*element)?;
+        }
+    }
+
+    Ok(())
+}
+
 fn release_request_pad(elem: &gst::Element) -> Result<()> {
     let pad = elem.static_pad("src").ok_or_else(|| {
         MixerError::Gstreamer("Failed to get static src pad for element".to_string())
@@ -104,7 +104,27 @@ impl Fake {
         Ok(())
     }
 
+    pub fn set_audio_offset(&mut self, _offset_ms: i64, _update_config: bool) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn set_video_offset(&mut self, _offset_ms: i64, _update_config: bool) -> Result<()> {
+        Ok(())
+    }
+
     pub fn config(&self) -> Config {
         self.config.clone()
     }
+
+    pub fn compositor_pad(&self) -> Option<gst::Pad> {
+        self.video.static_pad("src")?.peer()
+    }
+
+    pub fn video_src_pad(&self) -> Option<gst::Pad> {
+        self.video.static_pad("src")
+    }
+
+    pub fn audio_src_pad(&self) -> Option<gst::Pad> {
+        self.audio.static_pad("src")
+    }
 }
@@ -0,0 +1,783 @@
+use super::{Config, PlaylistConfig};
+use crate::{gst_create_element, mixer, Result};
+
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Where a playlist is in its items.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Status {
+    pub items: Vec<String>,
+    /// Index of the item playing, `None` before the first item and once the playlist ran out.
+    pub position: Option<usize>,
+    #[serde(rename = "loop")]
+    pub looping: bool,
+    pub shuffle: bool,
+}
+
+/// Plays a list of URIs back-to-back on a single compositor pad.
+///
+/// Every item is decoded by its own `uridecodebin`, which is replaced by the next one when all
+/// of its streams reached EOS. The EOS itself is dropped, so the mixer only sees a continuous
+/// stream. An item whose source posts an error is skipped the same way. Once the playlist runs
+/// out, or every item failed in a row, the input stops producing frames until an item is added.
+pub struct Playlist {
+    pub name: String,
+    config: Config,
+    pipeline: Option<gst::Pipeline>,
+    state: Arc<Mutex<State>>,
+    player: Option<Player>,
+    /// Watches the pipeline bus for errors of the current source while linked.
+    error_handler: Mutex<Option<gst::glib::SignalHandlerId>>,
+    audio_convert: gst::Element,
+    audio_volume: gst::Element,
+    audio_level: gst::Element,
+    audio_resample: gst::Element,
+    audio_capsfilter: gst::Element,
+    audio_queue: gst::Element,
+    video_convert: gst::Element,
+    video_scale: gst::Element,
+    video_rate: gst::Element,
+    video_capsfilter: gst::Element,
+    video_tee: gst::Element,
+    video_queue: gst::Element,
+    preview_queue: gst::Element,
+}
+
+struct State {
+    queue: Queue,
+    /// The `uridecodebin` playing the current item.
+    source: Option<gst::Element>,
+    /// Identifies the current source, EOS from the sources it replaced is ignored.
+    serial: u64,
+    /// Streams of the current source that have not reached EOS yet.
+    streams: u32,
+    /// Serial of the last source that posted an error, its further errors are ignored.
+    failed: u64,
+    /// Items that failed in a row since an item last played.
+    failures: usize,
+}
+
+/// The items of a playlist and where playback is in them.
+struct Queue {
+    config: PlaylistConfig,
+    position: Option<usize>,
+    /// Index of the item played once the current one ends.
+    next: usize,
+}
+
+impl Queue {
+    fn new(mut config: PlaylistConfig) -> Self {
+        if config.shuffle {
+            fastrand::shuffle(&mut config.items);
+        }
+
+        Self {
+            config,
+            position: None,
+            next: 0,
+        }
+    }
+
+    /// Moves on to the next item and returns its URI, `None` once the playlist ran out.
+    fn advance(&mut self) -> Option<String> {
+        if self.next >= self.config.items.len() {
+            if !self.config.looping || self.config.items.is_empty() {
+                self.position = None;
+                self.next = self.config.items.len();
+                return None;
+            }
+            if self.config.shuffle {
+                fastrand::shuffle(&mut self.config.items);
+            }
+            self.next = 0;
+        }
+
+        self.position = Some(self.next);
+        self.next += 1;
+        self.config.items.get(self.next - 1).cloned()
+    }
+
+    /// Moves back to the item before the current one and returns its URI.
+    fn back(&mut self) -> Option<String> {
+        let last = self.config.items.len().saturating_sub(1);
+        self.next = match self.position {
+            Some(0) if self.config.looping => last,
+            Some(position) => position.saturating_sub(1),
+            None => last,
+        };
+        self.advance()
+    }
+
+    fn insert(&mut self, index: usize, uri: String) {
+        let index = index.min(self.config.items.len());
+        self.config.items.insert(index, uri);
+        if index < self.next {
+            self.next += 1;
+        }
+        if let Some(position) = self.position.filter(|position| index <= *position) {
+            self.position = Some(position + 1);
+        }
+    }
+
+    /// Removes the item at `index`, returns whether it was the one playing.
+    fn remove(&mut self, index: usize) -> Result<bool> {
+        if index >= self.config.items.len() {
+            return Err(mixer::Error::NotFound(
+                "playlist item".to_string(),
+                index.to_string(),
+            ));
+        }
+
+        self.config.items.remove(index);
+        if index < self.next {
+            self.next -= 1;
+        }
+        match self.position {
+            Some(position) if position == index => {
+                self.position = None;
+                return Ok(true);
+            }
+            Some(position) if position > index => self.position = Some(position - 1),
+            _ => {}
+        }
+
+        Ok(false)
+    }
+
+    /// Replaces the items and options. The item playing keeps its place if it is still in the
+    /// playlist, otherwise the first item follows it.
+    fn set(&mut self, config: PlaylistConfig) {
+        let current = self
+            .position
+            .and_then(|position| self.config.items.get(position).cloned());
+        self.config = config;
+        if self.config.shuffle {
+            fastrand::shuffle(&mut self.config.items);
+        }
+
+        self.position =
+            current.and_then(|current| self.config.items.iter().position(|i| *i == current));
+        self.next = self.position.map(|position| position + 1).unwrap_or(0);
+    }
+}
+
+/// Swaps the sources of a linked playlist.
+#[derive(Clone)]
+struct Player {
+    name: String,
+    pipeline: gst::glib::WeakRef<gst::Pipeline>,
+    audio: gst::Element,
+    video: gst::Element,
+    state: Arc<Mutex<State>>,
+    /// Held while a source is replaced.
+    switch: Arc<Mutex<()>>,
+}
+
+impl Player {
+    /// Replaces the current source with one playing `uri`, or stops playback when there is
+    /// none. Must not be called from the streaming thread of the current source.
+    fn play(&self, uri: Option<String>) -> Result<()> {
+        let _switch = self.switch.lock().unwrap();
+        let (old, serial) = {
+            let mut state = self.state.lock().unwrap();
+            state.serial += 1;
+            state.streams = 0;
+            (state.source.take(), state.serial)
+        };
+
+        let pipeline = self
+            .pipeline
+            .upgrade()
+            .ok_or_else(|| mixer::Error::Gstreamer("playlist is not linked".to_string()))?;
+        if let Some(old) = old {
+            old.set_state(gst::State::Null)?;
+            pipeline.remove(&old)?;
+        }
+
+        let uri = match uri {
+            Some(uri) => uri,
+            None => return Ok(()),
+        };
+        tracing::info!(input = self.name.as_str(), uri = uri.as_str(), "playing");
+
+        let source = gst_create_element(
+            "uridecodebin",
+            &format!("input_{}_uridecodebin_{}", self.name, serial),
+        )?;
+        source.set_property("uri", &uri);
+        let player = self.clone();
+        source.connect_pad_added(move |_, pad| player.link_pad(pad, serial));
+
+        pipeline.add(&source)?;
+        self.state.lock().unwrap().source = Some(source.clone());
+        source.sync_state_with_parent()?;
+
+        Ok(())
+    }
+
+    fn link_pad(&self, pad: &gst::Pad, serial: u64) {
+        let caps = match pad.current_caps() {
+            Some(caps) => caps,
+            None => return,
+        };
+        let pad_type = match caps.structure(0) {
+            Some(structure) => structure.name().to_string(),
+            None => return,
+        };
+
+        let sink = if pad_type.starts_with("audio/x-raw") {
+            &self.audio
+        } else if pad_type.starts_with("video/x-raw") {
+            &self.video
+        } else {
+            return;
+        };
+        let sink_pad = match sink.static_pad("sink") {
+            Some(pad) => pad,
+            None => return,
+        };
+        if sink_pad.is_linked() {
+            return;
+        }
+
+        // Offset by the running time of the pipeline, so the item starts playing now instead of
+        // fast-forwarding to catch up with it.
+        let running_time = sink.current_running_time();
+        pad.set_offset(gst::format::GenericFormattedValue::Time(running_time).value());
+
+        if let Err(e) = pad.link(&sink_pad) {
+            tracing::warn!(
+                input = self.name.as_str(),
+                "failed to link {}: {}",
+                pad_type,
+                e
+            );
+            return;
+        }
+
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.serial != serial {
+                return;
+            }
+            state.streams += 1;
+            state.failures = 0;
+        }
+
+        let player = self.clone();
+        pad.add_probe(
+            gst::PadProbeType::EVENT_DOWNSTREAM,
+            move |pad, info| match info.data {
+                Some(gst::PadProbeData::Event(ref event))
+                    if event.type_() == gst::EventType::Eos =>
+                {
+                    player.stream_ended(pad, serial);
+                    gst::PadProbeReturn::Drop
+                }
+                _ => gst::PadProbeReturn::Ok,
+            },
+        );
+    }
+
+    /// Moves on to the next item once every stream of the current one ended.
+    fn stream_ended(&self, pad: &gst::Pad, serial: u64) {
+        let uri = {
+            let mut state = self.state.lock().unwrap();
+            if state.serial != serial {
+                return;
+            }
+            state.streams = state.streams.saturating_sub(1);
+            if state.streams > 0 {
+                return;
+            }
+            state.queue.advance()
+        };
+
+        // The source can not be stopped from its own streaming thread.
+        let source = match pad.parent_element() {
+            Some(source) => source,
+            None => return,
+        };
+        let player = self.clone();
+        source.call_async(move |_| {
+            if let Err(e) = player.play(uri) {
+                tracing::warn!(input = player.name.as_str(), "failed to advance: {}", e);
+            }
+        });
+    }
+
+    /// Moves on to the next item when `src`, or the source containing it, posted an error.
+    /// Stops once every item failed in a row, so a looping playlist of broken items does not
+    /// keep retrying them.
+    fn source_failed(&self, src: &gst::Object) {
+        let (source, uri) = {
+            let mut state = self.state.lock().unwrap();
+            let source = match state.source.clone() {
+                Some(source) if src.has_as_ancestor(&source) => source,
+                _ => return,
+            };
+            if state.failed == state.serial {
+                return;
+            }
+            state.failed = state.serial;
+            state.failures += 1;
+
+            let uri = if state.failures < state.queue.config.items.len() {
+                state.queue.advance()
+            } else {
+                tracing::warn!(input = self.name.as_str(), "every item failed, stopping");
+                None
+            };
+            (source, uri)
+        };
+
+        // Errors are posted from the streaming thread of the source, which can not stop it.
+        let player = self.clone();
+        source.call_async(move |_| {
+            if let Err(e) = player.play(uri) {
+                tracing::warn!(input = player.name.as_str(), "failed to advance: {}", e);
+            }
+        });
+    }
+}
+
+impl Playlist {
+    pub fn create(config: Config) -> Result<Self> {
+        let queue = Queue::new(config.playlist.clone().unwrap_or_default());
+
+        let video_convert = gst_create_element(
+            "videoconvert",
+            &format!("input_{}_video_convert", config.name),
+        )?;
+        let video_scale =
+            gst_create_element("videoscale", &format!("input_{}_video_scale", config.name))?;
+        let video_rate =
+            gst_create_element("videorate", &format!("input_{}_video_rate", config.name))?;
+        let video_caps = gst::Caps::builder("video/x-raw")
+            .field("framerate", gst::Fraction::new(config.video.framerate, 1))
+            .field("format", config.video.format.to_string())
+            .field("width", config.video.width)
+            .field("height", config.video.height)
+            .field("colorimetry", "sRGB")
+            .build();
+        let video_capsfilter = gst_create_element(
+            "capsfilter",
+            &format!("input_{}_video_capsfilter", config.name),
+        )?;
+        video_capsfilter.set_property("caps", &video_caps);
+
+        let video_tee = gst_create_element("tee", &format!("input_{}_video_tee", config.name))?;
+        video_tee.set_property("allow-not-linked", true);
+        let video_queue =
+            gst_create_element("queue", &format!("input_{}_video_queue", config.name))?;
+        let preview_queue =
+            gst_create_element("queue", &format!("input_{}_preview_queue", config.name))?;
+
+        let audio_convert = gst_create_element(
+            "audioconvert",
+            &format!("input_{}_audio_convert", config.name),
+        )?;
+        let audio_volume =
+            gst_create_element("volume", &format!("input_{}_audio_volume", config.name))?;
+        audio_volume.set_property("volume", config.audio.volume);
+        let audio_level =
+            gst_create_element("level", &format!("input_{}_audio_level", config.name))?;
+        let audio_resample = gst_create_element(
+            "audioresample",
+            &format!("input_{}_audio_resample", config.name),
+        )?;
+        let audio_capsfilter = gst_create_element(
+            "capsfilter",
+            &format!("input_{}_audio_capsfilter", config.name),
+        )?;
+        let audio_queue =
+            gst_create_element("queue", &format!("input_{}_audio_queue", config.name))?;
+
+        super::set_src_pad_offset(&audio_queue, config.audio_offset_ms)?;
+        super::set_src_pad_offset(&video_queue, config.video_offset_ms)?;
+        super::set_src_pad_offset(&preview_queue, config.video_offset_ms)?;
+
+        Ok(Self {
+            name: config.name.clone(),
+            config,
+            pipeline: None,
+            state: Arc::new(Mutex::new(State {
+                queue,
+                source: None,
+                serial: 0,
+                streams: 0,
+                failed: 0,
+                failures: 0,
+            })),
+            player: None,
+            error_handler: Mutex::new(None),
+            audio_convert,
+            audio_volume,
+            audio_level,
+            audio_resample,
+            audio_capsfilter,
+            audio_queue,
+            video_convert,
+            video_scale,
+            video_rate,
+            video_capsfilter,
+            video_tee,
+            video_queue,
+            preview_queue,
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The URI of the item playing, empty while nothing plays.
+    pub fn location(&self) -> String {
+        let state = self.state.lock().unwrap();
+        state
+            .queue
+            .position
+            .and_then(|position| state.queue.config.items.get(position).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn link(
+        &mut self,
+        pipeline: gst::Pipeline,
+        audio: gst::Element,
+        video: gst::Element,
+        audio_caps: &gst::Caps,
+    ) -> Result<()> {
+        self.audio_capsfilter.set_property("caps", audio_caps);
+
+        pipeline.add_many([
+            &self.audio_convert,
+            &self.audio_volume,
+            &self.audio_level,
+            &self.audio_resample,
+            &self.audio_capsfilter,
+            &self.audio_queue,
+            &self.video_convert,
+            &self.video_scale,
+            &self.video_rate,
+            &self.video_capsfilter,
+            &self.video_tee,
+            &self.video_queue,
+        ])?;
+
+        gst::Element::link_many([
+            &self.audio_convert,
+            &self.audio_volume,
+            &self.audio_level,
+            &self.audio_resample,
+            &self.audio_capsfilter,
+            &self.audio_queue,
+            &audio,
+        ])?;
+        gst::Element::link_many([
+            &self.video_convert,
+            &self.video_scale,
+            &self.video_rate,
+            &self.video_capsfilter,
+            &self.video_tee,
+            &self.video_queue,
+            &video,
+        ])?;
+
+        // Store the zorder the compositor picked, so it can be restored after the mixer changed
+        // it temporarily.
+        let zorder: u32 = self
+            .video_queue
+            .static_pad("src")
+            .ok_or_else(|| {
+                mixer::Error::Gstreamer("Failed to retrieve src pad for video_queue".to_string())
+            })?
+            .peer()
+            .ok_or_else(|| {
+                mixer::Error::Gstreamer("Failed to retrieve peer pad for video_queue".to_string())
+            })?
+            .property::<u32>("zorder");
+        self.config.video.zorder = Some(zorder);
+
+        self.player = Some(Player {
+            name: self.name.clone(),
+            pipeline: pipeline.downgrade(),
+            audio: self.audio_convert.clone(),
+            video: self.video_convert.clone(),
+            state: self.state.clone(),
+            switch: Arc::new(Mutex::new(())),
+        });
+        // Errors are handled in the thread posting them, before the mixer bus thread sees them.
+        if let (Some(bus), Some(player)) = (pipeline.bus(), self.player.clone()) {
+            bus.enable_sync_message_emission();
+            let handler = bus.connect_sync_message(Some("error"), move |_, msg| {
+                if let Some(src) = msg.src() {
+                    player.source_failed(src);
+                }
+            });
+            *self.error_handler.lock().unwrap() = Some(handler);
+        }
+        self.pipeline = Some(pipeline);
+
+        // An item that fails to start leaves the playlist idle, it does not fail the input.
+        let uri = self.state.lock().unwrap().queue.advance();
+        if let Err(e) = self.play(uri) {
+            tracing::warn!(
+                input = self.name.as_str(),
+                "failed to start playlist: {}",
+                e
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn link_preview(&mut self, video: gst::Element) -> Result<()> {
+        let pipeline = self.pipeline.as_ref().ok_or_else(|| {
+            mixer::Error::Gstreamer("input must be linked before the preview".to_string())
+        })?;
+
+        pipeline.add(&self.preview_queue)?;
+        gst::Element::link_many([&self.video_tee, &self.preview_queue, &video])?;
+
+        Ok(())
+    }
+
+    pub fn unlink(&self) -> Result<()> {
+        super::release_request_pad(&self.audio_queue)?;
+        super::release_request_pad(&self.video_queue)?;
+        super::release_request_pad(&self.preview_queue)?;
+
+        let handler = self.error_handler.lock().unwrap().take();
+        if let (Some(bus), Some(handler)) = (self.pipeline.as_ref().and_then(|p| p.bus()), handler)
+        {
+            bus.disconnect(handler);
+            bus.disable_sync_message_emission();
+        }
+        self.play(None)?;
+        if let Some(pipeline) = self.pipeline.as_ref() {
+            super::remove_added(
+                pipeline,
+                &[
+                    &self.audio_convert,
+                    &self.audio_volume,
+                    &self.audio_level,
+                    &self.audio_resample,
+                    &self.audio_capsfilter,
+                    &self.audio_queue,
+                    &self.video_convert,
+                    &self.video_scale,
+                    &self.video_rate,
+                    &self.video_capsfilter,
+                    &self.video_tee,
+                    &self.video_queue,
+                    &self.preview_queue,
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_state(&mut self, state: gst::State) -> Result<()> {
+        // The source may post an error while changing state, which locks the state again.
+        let source = self.state.lock().unwrap().source.clone();
+        if let Some(source) = source {
+            source.set_state(state)?;
+        }
+        self.audio_convert.set_state(state)?;
+        self.audio_volume.set_state(state)?;
+        self.audio_level.set_state(state)?;
+        self.audio_resample.set_state(state)?;
+        self.audio_capsfilter.set_state(state)?;
+        self.audio_queue.set_state(state)?;
+        self.video_convert.set_state(state)?;
+        self.video_scale.set_state(state)?;
+        self.video_rate.set_state(state)?;
+        self.video_capsfilter.set_state(state)?;
+        self.video_tee.set_state(state)?;
+        self.video_queue.set_state(state)?;
+        // The preview branch is only added once the input is linked to the preview bus.
+        if self.preview_queue.parent().is_some() {
+            self.preview_queue.set_state(state)?;
+        }
+        Ok(())
+    }
+
+    /// Where the playlist is in its items.
+    pub fn status(&self) -> Status {
+        let state = self.state.lock().unwrap();
+        Status {
+            items: state.queue.config.items.clone(),
+            position: state.queue.position,
+            looping: state.queue.config.looping,
+            shuffle: state.queue.config.shuffle,
+        }
+    }
+
+    /// Skips to the next item.
+    pub fn skip(&mut self) -> Result<()> {
+        let uri = self.state.lock().unwrap().queue.advance();
+        self.play(uri)
+    }
+
+    /// Goes back to the item before the one playing.
+    pub fn previous(&mut self) -> Result<()> {
+        let uri = self.state.lock().unwrap().queue.back();
+        self.play(uri)
+    }
+
+    /// Inserts `uri` at `index`, at the end when `index` is `None` or past the end. A playlist
+    /// that ran out starts playing again.
+    pub fn insert(&mut self, index: Option<usize>, uri: &str) -> Result<()> {
+        {
+            let mut state = self.state.lock().unwrap();
+            let index = index.unwrap_or(state.queue.config.items.len());
+            state.queue.insert(index, uri.to_string());
+        }
+        self.resume()
+    }
+
+    /// Removes the item at `index`, skipping to the next item if it is the one playing.
+    pub fn remove(&mut self, index: usize) -> Result<()> {
+        let uri = {
+            let mut state = self.state.lock().unwrap();
+            match state.queue.remove(index)? {
+                true => state.queue.advance(),
+                false => return Ok(()),
+            }
+        };
+        self.play(uri)
+    }
+
+    /// Replaces the items and options of the playlist.
+    pub fn set(&mut self, config: PlaylistConfig) -> Result<()> {
+        self.state.lock().unwrap().queue.set(config);
+        self.resume()
+    }
+
+    /// Starts the next item if nothing is playing.
+    fn resume(&mut self) -> Result<()> {
+        let uri = {
+            let mut state = self.state.lock().unwrap();
+            if state.source.is_some() {
+                return Ok(());
+            }
+            state.queue.advance()
+        };
+        self.play(uri)
+    }
+
+    fn play(&self, uri: Option<String>) -> Result<()> {
+        self.state.lock().unwrap().failures = 0;
+        match self.player.as_ref() {
+            Some(player) => player.play(uri),
+            None => Ok(()),
+        }
+    }
+
+    pub fn set_volume(&mut self, volume: f64, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.audio.volume = volume;
+        }
+        self.audio_volume.set_property("volume", volume);
+        Ok(())
+    }
+
+    pub fn set_zorder(&mut self, zorder: u32, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video.zorder = Some(zorder);
+        }
+        super::set_peer_pad_property(&self.video_queue_src()?, "zorder", &zorder)
+    }
+
+    pub fn set_width(&mut self, width: i32, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video.width = width;
+        }
+        super::set_peer_pad_property(&self.video_queue_src()?, "width", &width)
+    }
+
+    pub fn set_height(&mut self, height: i32, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video.height = height;
+        }
+        super::set_peer_pad_property(&self.video_queue_src()?, "height", &height)
+    }
+
+    pub fn set_xpos(&mut self, xpos: i32, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video.xpos = xpos;
+        }
+        super::set_peer_pad_property(&self.video_queue_src()?, "xpos", &xpos)
+    }
+
+    pub fn set_ypos(&mut self, ypos: i32, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video.ypos = ypos;
+        }
+        super::set_peer_pad_property(&self.video_queue_src()?, "ypos", &ypos)
+    }
+
+    pub fn set_alpha(&mut self, alpha: f64, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video.alpha = alpha;
+        }
+        super::set_peer_pad_property(&self.video_queue_src()?, "alpha", &alpha)
+    }
+
+    pub fn set_audio_offset(&mut self, offset_ms: i64, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.audio_offset_ms = offset_ms;
+        }
+        super::set_src_pad_offset(&self.audio_queue, offset_ms)
+    }
+
+    pub fn set_video_offset(&mut self, offset_ms: i64, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video_offset_ms = offset_ms;
+        }
+        super::set_src_pad_offset(&self.video_queue, offset_ms)?;
+        super::set_src_pad_offset(&self.preview_queue, offset_ms)
+    }
+
+    /// The config of the input, with the playlist as it is now.
+    pub fn config(&self) -> Config {
+        let mut config = self.config.clone();
+        config.playlist = Some(self.state.lock().unwrap().queue.config.clone());
+        config
+    }
+
+    pub fn compositor_pad(&self) -> Option<gst::Pad> {
+        self.video_queue.static_pad("src")?.peer()
+    }
+
+    pub fn video_src_pad(&self) -> Option<gst::Pad> {
+        self.video_queue.static_pad("src")
+    }
+
+    pub fn audio_src_pad(&self) -> Option<gst::Pad> {
+        self.audio_queue.static_pad("src")
+    }
+
+    pub fn preview_pad(&self) -> Option<gst::Pad> {
+        self.preview_queue.static_pad("src")?.peer()
+    }
+
+    pub fn volume_element(&self) -> gst::Element {
+        self.audio_volume.clone()
+    }
+
+    pub fn video_tee(&self) -> gst::Element {
+        self.video_tee.clone()
+    }
+
+    fn video_queue_src(&self) -> Result<gst::Pad> {
+        self.video_queue
+            .static_pad("src")
+            .ok_or_else(|| mixer::Error::Gstreamer("failed to retrieve src pad".to_string()))
+    }
+}
@@ -0,0 +1,82 @@
+mod amf;
+mod server;
+
+pub use server::Status;
+
+use super::{Config, RTMPConfig, URI};
+use crate::Result;
+
+use gst::prelude::*;
+use gstreamer as gst;
+use std::ops::{Deref, DerefMut};
+
+/// An input encoders such as OBS publish to over RTMP. It listens for a publish to
+/// `rtmp://host:{port}/{app}/{stream_key}` and decodes the stream the same way a
+/// [`URI`](../uri/struct.URI.html) input does, which it derefs to for everything else.
+///
+/// A publisher that reconnects continues the stream where the previous one left off. Inputs on
+/// the same address and port share its listener and are told apart by application and stream
+/// key.
+pub struct RTMP {
+    uri: URI,
+    rtmp: RTMPConfig,
+    registration: server::Registration,
+}
+
+impl RTMP {
+    pub fn create(config: Config) -> Result<Self> {
+        let rtmp = config.rtmp.clone().unwrap_or_default();
+        let stream_key = rtmp
+            .stream_key
+            .clone()
+            .unwrap_or_else(|| config.name.clone());
+        let registration = server::register(&rtmp.address, rtmp.port, &rtmp.app, &stream_key)?;
+
+        let uri = URI::create(config, "appsrc://")?;
+        let endpoint = registration.endpoint.clone();
+        uri.source().connect("source-setup", false, move |args| {
+            if let Ok(appsrc) = args[1].get::<gst::Element>() {
+                endpoint.source_setup(appsrc);
+            }
+            None
+        });
+
+        Ok(Self {
+            uri,
+            rtmp: RTMPConfig {
+                stream_key: Some(stream_key),
+                ..rtmp
+            },
+            registration,
+        })
+    }
+
+    /// The URL encoders publish to.
+    pub fn location(&self) -> String {
+        format!(
+            "rtmp://{}:{}/{}/{}",
+            self.rtmp.address,
+            self.rtmp.port,
+            self.rtmp.app,
+            self.rtmp.stream_key.as_deref().unwrap_or_default()
+        )
+    }
+
+    pub fn status(&self) -> Status {
+        self.registration.endpoint.status()
+    }
+}
+
+impl Deref for RTMP {
+    type Target = URI;
+
+    fn deref(&self) -> &URI {
+        &self.uri
+    }
+}
+
+impl DerefMut for RTMP {
+    fn deref_mut(&mut self) -> &mut URI {
+        &mut self.uri
+    }
+}
@@ -0,0 +1,280 @@
+//! The subset of AMF0 RTMP commands are encoded in.
+
+use std::convert::TryInto;
+
+/// Deepest nesting of objects and arrays decoded, deeper values count as malformed.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Number(f64),
+    Boolean(bool),
+    String(String),
+    Object(Vec<(String, Value)>),
+    Null,
+    Undefined,
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The property `key` of an object.
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(properties) => properties
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn encode(values: &[Value]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for value in values {
+        encode_value(value, &mut out);
+    }
+    out
+}
+
+fn encode_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Number(number) => {
+            out.push(0x00);
+            out.extend_from_slice(&number.to_be_bytes());
+        }
+        Value::Boolean(boolean) => {
+            out.push(0x01);
+            out.push(*boolean as u8);
+        }
+        Value::String(string) => {
+            out.push(0x02);
+            encode_key(string, out);
+        }
+        Value::Object(properties) => {
+            out.push(0x03);
+            for (key, value) in properties {
+                encode_key(key, out);
+                encode_value(value, out);
+            }
+            out.extend_from_slice(&[0x00, 0x00, 0x09]);
+        }
+        Value::Null => out.push(0x05),
+        Value::Undefined => out.push(0x06),
+        Value::Array(values) => {
+            out.push(0x0a);
+            out.extend_from_slice(&(values.len() as u32).to_be_bytes());
+            for value in values {
+                encode_value(value, out);
+            }
+        }
+    }
+}
+
+fn encode_key(key: &str, out: &mut Vec<u8>) {
+    let bytes = &key.as_bytes()[..key.len().min(u16::MAX as usize)];
+    out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Decodes the values in `data`, up to the first one that is malformed or of a type commands
+/// do not use.
+pub(crate) fn decode(data: &[u8]) -> Vec<Value> {
+    let mut reader = Reader {
+        data,
+        position: 0,
+        depth: 0,
+    };
+    let mut values = Vec::new();
+    while reader.position < data.len() {
+        match reader.value() {
+            Some(value) => values.push(value),
+            None => break,
+        }
+    }
+    values
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    /// Objects and arrays the reader is in.
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.position..self.position + length)?;
+        self.position += length;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2)
+            .map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn string(&mut self, length: usize) -> Option<String> {
+        self.take(length)
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// Reads the contents of an object or array with `read`, one level deeper.
+    fn nested<T>(&mut self, read: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn properties(&mut self) -> Option<Vec<(String, Value)>> {
+        let mut properties = Vec::new();
+        loop {
+            let length = self.u16()? as usize;
+            if length == 0 && self.data.get(self.position) == Some(&0x09) {
+                self.position += 1;
+                return Some(properties);
+            }
+            let key = self.string(length)?;
+            properties.push((key, self.value()?));
+        }
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        match self.u8()? {
+            0x00 => self
+                .take(8)
+                .map(|bytes| Value::Number(f64::from_be_bytes(bytes.try_into().unwrap()))),
+            0x01 => self.u8().map(|boolean| Value::Boolean(boolean != 0)),
+            0x02 => {
+                let length = self.u16()? as usize;
+                self.string(length).map(Value::String)
+            }
+            0x03 => self.nested(Self::properties).map(Value::Object),
+            0x05 => Some(Value::Null),
+            0x06 => Some(Value::Undefined),
+            // ECMA arrays are objects with a length hint up front.
+            0x08 => {
+                self.u32()?;
+                self.nested(Self::properties).map(Value::Object)
+            }
+            0x0a => {
+                let length = self.u32()?;
+                self.nested(|reader| {
+                    (0..length)
+                        .map(|_| reader.value())
+                        .collect::<Option<Vec<_>>>()
+                })
+                .map(Value::Array)
+            }
+            0x0b => {
+                // Dates are a number of milliseconds and a time zone nobody fills in.
+                let date = self.take(8)?;
+                self.u16()?;
+                Some(Value::Number(f64::from_be_bytes(date.try_into().unwrap())))
+            }
+            0x0c => {
+                let length = self.u32()? as usize;
+                self.string(length).map(Value::String)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let values = vec![
+            Value::String("connect".to_string()),
+            Value::Number(1.0),
+            Value::Object(vec![
+                ("app".to_string(), Value::String("live".to_string())),
+                ("fpad".to_string(), Value::Boolean(false)),
+                (
+                    "codecs".to_string(),
+                    Value::Array(vec![Value::Number(7.0), Value::Null]),
+                ),
+            ]),
+            Value::Null,
+            Value::Undefined,
+        ];
+        assert_eq!(values, decode(&encode(&values)));
+    }
+
+    #[test]
+    fn test_ecma_array() {
+        let mut data = vec![0x08, 0, 0, 0, 1];
+        data.extend_from_slice(&[0, 5]);
+        data.extend_from_slice(b"width");
+        data.push(0x00);
+        data.extend_from_slice(&1280f64.to_be_bytes());
+        data.extend_from_slice(&[0, 0, 0x09]);
+
+        let values = decode(&data);
+        assert_eq!(1, values.len());
+        assert_eq!(
+            Some(1280.0),
+            values[0].get("width").and_then(Value::as_number)
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let mut data = encode(&[Value::String("publish".to_string())]);
+        // A string claiming more bytes than there are.
+        data.extend_from_slice(&[0x02, 0xff, 0xff, b'a']);
+        assert_eq!(vec![Value::String("publish".to_string())], decode(&data));
+
+        // An array claiming more values than there are.
+        assert!(decode(&[0x0a, 0xff, 0xff, 0xff, 0xff, 0x05]).is_empty());
+        // Types commands do not use end decoding.
+        assert!(decode(&[0x10, 0x05]).is_empty());
+    }
+
+    #[test]
+    fn test_depth() {
+        let nest = |depth: usize| {
+            let mut data = Vec::new();
+            for _ in 0..depth {
+                data.extend_from_slice(&[0x0a, 0, 0, 0, 1]);
+            }
+            data.push(0x05);
+            data
+        };
+
+        assert_eq!(1, decode(&nest(MAX_DEPTH)).len());
+        assert!(decode(&nest(MAX_DEPTH + 1)).is_empty());
+        // Deep enough to overflow the stack without the limit.
+        assert!(decode(&nest(1_000_000)).is_empty());
+    }
+}
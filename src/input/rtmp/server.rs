@@ -0,0 +1,936 @@
+//! A minimal RTMP server, accepting the streams encoders such as OBS publish and handing them to
+//! the inputs waiting for them as FLV.
+
+use super::amf::{self, Value};
+use crate::{mixer::Error as MixerError, Result};
+
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+const HANDSHAKE_SIZE: usize = 1536;
+/// Size of the chunks we send, announced to the client after it connected.
+const CHUNK_SIZE: usize = 4096;
+const WINDOW_ACK_SIZE: u32 = 2_500_000;
+/// Largest message accepted, to not allocate whatever a broken client asks for.
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+/// Largest chunk size a client may announce. Encoders use a few kilobytes.
+const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+/// Chunk streams a client may use, encoders use a handful.
+const MAX_CHUNK_STREAMS: usize = 64;
+/// Most bytes of unfinished messages a client may have over all of its chunk streams.
+const MAX_BUFFERED_SIZE: usize = 2 * MAX_MESSAGE_SIZE;
+/// Connections served at once by a listener, later connections are refused until one closes.
+const MAX_CONNECTIONS: usize = 64;
+/// Time without data after which a publisher is considered gone.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often a listener checks whether it was closed while no client connects.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+const FLV_HEADER: &[u8] = b"FLV\x01\x05\x00\x00\x00\x09\x00\x00\x00\x00";
+
+// Message types.
+const SET_CHUNK_SIZE: u8 = 1;
+const ACKNOWLEDGEMENT: u8 = 3;
+const WINDOW_ACKNOWLEDGEMENT_SIZE: u8 = 5;
+const SET_PEER_BANDWIDTH: u8 = 6;
+const AUDIO: u8 = 8;
+const VIDEO: u8 = 9;
+const DATA: u8 = 18;
+const COMMAND: u8 = 20;
+
+// Chunk streams messages are sent on.
+const CONTROL_CHUNK_STREAM: u8 = 2;
+const COMMAND_CHUNK_STREAM: u8 = 3;
+const STATUS_CHUNK_STREAM: u8 = 5;
+
+/// Connection state of an [`RTMP`](../struct.RTMP.html) input.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct Status {
+    /// An encoder is publishing to the input.
+    pub connected: bool,
+    /// Address of the publishing encoder.
+    pub client: Option<String>,
+}
+
+/// Listeners by address and port.
+type Listeners = Mutex<HashMap<(String, u16), Arc<Listener>>>;
+
+/// The running listeners. A listener runs while inputs wait for streams on it, publishes to
+/// stream keys no input waits for are rejected.
+fn listeners() -> &'static Listeners {
+    static LISTENERS: OnceLock<Listeners> = OnceLock::new();
+    LISTENERS.get_or_init(Default::default)
+}
+
+#[derive(Default)]
+struct Listener {
+    /// Endpoints by application and stream key.
+    endpoints: Mutex<HashMap<(String, String), Arc<Endpoint>>>,
+    /// Connections being served.
+    connections: AtomicUsize,
+    /// The last endpoint was removed, the listener stops accepting connections.
+    closed: AtomicBool,
+    /// The thread accepting connections, joined once closed.
+    accepting: Mutex<Option<std::thread::JoinHandle<()>>>,
+}
+
+impl Listener {
+    /// Accepts connections on a non-blocking `socket` until the listener is closed.
+    fn accept(self: Arc<Self>, socket: TcpListener) {
+        while !self.closed.load(Ordering::SeqCst) {
+            let stream = match socket.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(ACCEPT_INTERVAL);
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("rtmp: failed to accept a connection: {}", e);
+                    continue;
+                }
+            };
+            // Connections may take on the non-blocking mode of the socket they came from.
+            if let Err(e) = stream.set_nonblocking(false) {
+                tracing::warn!("rtmp: failed to set up a connection: {}", e);
+                continue;
+            }
+
+            if self.connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                self.connections.fetch_sub(1, Ordering::SeqCst);
+                tracing::warn!(
+                    client = ?stream.peer_addr().ok(),
+                    "rtmp: refusing a connection, {} are open already",
+                    MAX_CONNECTIONS
+                );
+                continue;
+            }
+
+            let listener = Arc::clone(&self);
+            std::thread::spawn(move || {
+                Arc::clone(&listener).serve(stream);
+                listener.connections.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    }
+
+    fn serve(self: Arc<Self>, stream: TcpStream) {
+        let mut session = match Session::new(self, stream) {
+            Ok(session) => session,
+            Err(e) => {
+                tracing::warn!("rtmp: failed to set up a connection: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = session.run() {
+            tracing::info!(client = session.client.as_str(), "rtmp: {}", e);
+        }
+        session.close();
+    }
+
+    fn endpoint(&self, app: &str, stream_key: &str) -> Option<Arc<Endpoint>> {
+        self.endpoints
+            .lock()
+            .unwrap()
+            .get(&(app.to_string(), stream_key.to_string()))
+            .cloned()
+    }
+}
+
+/// The FLV stream of an input, written to the `appsrc` of its `uridecodebin`.
+#[derive(Default)]
+struct Stream {
+    appsrc: Option<gst::Element>,
+    header_sent: bool,
+    /// Added to the timestamps of the current publisher, so they continue where the previous
+    /// publisher left off.
+    offset: i64,
+    /// Timestamp the first tag of the next publisher is moved to.
+    next_start: Option<u32>,
+    last_timestamp: u32,
+    last_tag: Option<Instant>,
+}
+
+/// Where a stream key of a listener leads to.
+#[derive(Default)]
+pub(crate) struct Endpoint {
+    stream: Mutex<Stream>,
+    status: Mutex<Status>,
+    closed: AtomicBool,
+}
+
+impl Endpoint {
+    pub(crate) fn status(&self) -> Status {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Takes on the `appsrc` created by `uridecodebin` for an `appsrc://` URI.
+    pub(crate) fn source_setup(&self, appsrc: gst::Element) {
+        appsrc.set_property("is-live", true);
+        appsrc.set_property("caps", gst::Caps::new_empty_simple("video/x-flv"));
+        appsrc.set_property_from_str("stream-type", "stream");
+        appsrc.set_property("max-bytes", 8_000_000u64);
+
+        *self.stream.lock().unwrap() = Stream {
+            appsrc: Some(appsrc),
+            next_start: Some(0),
+            ..Stream::default()
+        };
+    }
+
+    /// Claims the endpoint for a publisher, fails when another one is publishing already.
+    fn connect(&self, client: &str) -> bool {
+        let mut status = self.status.lock().unwrap();
+        if status.connected || self.closed.load(Ordering::SeqCst) {
+            return false;
+        }
+        *status = Status {
+            connected: true,
+            client: Some(client.to_string()),
+        };
+
+        // Continue the stream as if it had kept running while nobody was publishing.
+        let mut stream = self.stream.lock().unwrap();
+        let start = match stream.last_tag {
+            Some(at) => stream
+                .last_timestamp
+                .wrapping_add(at.elapsed().as_millis() as u32),
+            None => 0,
+        };
+        stream.next_start = Some(start);
+
+        true
+    }
+
+    fn disconnect(&self) {
+        *self.status.lock().unwrap() = Status::default();
+    }
+
+    /// Writes an FLV tag, `timestamp` being the timestamp the publisher sent it with.
+    fn push_tag(&self, tag_type: u8, timestamp: u32, data: &[u8]) {
+        let mut stream = self.stream.lock().unwrap();
+        let appsrc = match stream.appsrc.clone() {
+            Some(appsrc) => appsrc,
+            None => return,
+        };
+
+        if !stream.header_sent {
+            push(&appsrc, FLV_HEADER.to_vec());
+            stream.header_sent = true;
+        }
+        if let Some(start) = stream.next_start.take() {
+            stream.offset = start as i64 - timestamp as i64;
+        }
+        let timestamp = (timestamp as i64 + stream.offset).max(0) as u32;
+        stream.last_timestamp = timestamp;
+        stream.last_tag = Some(Instant::now());
+
+        let mut tag = Vec::with_capacity(data.len() + 15);
+        tag.push(tag_type);
+        tag.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+        tag.extend_from_slice(&timestamp.to_be_bytes()[1..]);
+        tag.push((timestamp >> 24) as u8);
+        tag.extend_from_slice(&[0, 0, 0]);
+        tag.extend_from_slice(data);
+        tag.extend_from_slice(&(data.len() as u32 + 11).to_be_bytes());
+        push(&appsrc, tag);
+    }
+}
+
+fn push(appsrc: &gst::Element, data: Vec<u8>) {
+    let buffer = gst::Buffer::from_mut_slice(data);
+    // Pushing only fails while the input is shutting down.
+    let _ = appsrc.emit_by_name::<gst::FlowReturn>("push-buffer", &[&buffer]);
+}
+
+/// Keeps an endpoint registered with the listener on its address and port, until dropped. The
+/// listener stops with its last endpoint.
+pub(crate) struct Registration {
+    address: (String, u16),
+    key: (String, String),
+    listener: Arc<Listener>,
+    pub(crate) endpoint: Arc<Endpoint>,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.endpoint.closed.store(true, Ordering::SeqCst);
+
+        let mut listeners = listeners().lock().unwrap();
+        let mut endpoints = self.listener.endpoints.lock().unwrap();
+        endpoints.remove(&self.key);
+        if !endpoints.is_empty() {
+            return;
+        }
+        drop(endpoints);
+
+        // The port is free again once this returns, an input may listen on it right away.
+        listeners.remove(&self.address);
+        self.listener.closed.store(true, Ordering::SeqCst);
+        if let Some(accepting) = self.listener.accepting.lock().unwrap().take() {
+            let _ = accepting.join();
+        }
+    }
+}
+
+/// Waits for streams published to `rtmp://{address}:{port}/{app}/{stream_key}`, starting a
+/// listener on `address` and `port` when there is none yet.
+pub(crate) fn register(
+    address: &str,
+    port: u16,
+    app: &str,
+    stream_key: &str,
+) -> Result<Registration> {
+    // Held until the endpoint is added, so the listener is not closed meanwhile.
+    let mut listeners = listeners().lock().unwrap();
+    let listen = (address.to_string(), port);
+    let listener = match listeners.get(&listen) {
+        Some(listener) => Arc::clone(listener),
+        None => {
+            let socket = TcpListener::bind((address, port))
+                .and_then(|socket| socket.set_nonblocking(true).map(|_| socket))
+                .map_err(|e| {
+                    MixerError::System(format!("failed to listen on {}:{}: {}", address, port, e))
+                })?;
+            let listener = Arc::new(Listener::default());
+            let accepting = Arc::clone(&listener);
+            *listener.accepting.lock().unwrap() =
+                Some(std::thread::spawn(move || accepting.accept(socket)));
+            listeners.insert(listen.clone(), Arc::clone(&listener));
+            listener
+        }
+    };
+
+    let key = (app.to_string(), stream_key.to_string());
+    let mut endpoints = listener.endpoints.lock().unwrap();
+    if endpoints.contains_key(&key) {
+        return Err(MixerError::Exists(
+            "RTMP stream".to_string(),
+            format!("{}:{}/{}/{}", address, port, app, stream_key),
+        ));
+    }
+    let endpoint = Arc::new(Endpoint::default());
+    endpoints.insert(key.clone(), Arc::clone(&endpoint));
+    drop(endpoints);
+
+    Ok(Registration {
+        address: listen,
+        key,
+        listener,
+        endpoint,
+    })
+}
+
+/// State of a chunk stream, headers of later chunks leave out what did not change.
+#[derive(Default)]
+struct ChunkStream {
+    timestamp: u32,
+    delta: u32,
+    length: usize,
+    type_id: u8,
+    stream_id: u32,
+    extended: bool,
+    payload: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Message {
+    type_id: u8,
+    stream_id: u32,
+    timestamp: u32,
+    payload: Vec<u8>,
+}
+
+/// A connection of a client.
+struct Session {
+    listener: Arc<Listener>,
+    socket: TcpStream,
+    client: String,
+    chunk_size: usize,
+    peer_chunk_size: usize,
+    chunk_streams: HashMap<u32, ChunkStream>,
+    received: u64,
+    acknowledged: u64,
+    window: u32,
+    app: String,
+    endpoint: Option<Arc<Endpoint>>,
+}
+
+impl Session {
+    fn new(listener: Arc<Listener>, socket: TcpStream) -> io::Result<Self> {
+        socket.set_read_timeout(Some(READ_TIMEOUT))?;
+        socket.set_nodelay(true)?;
+        let client = socket.peer_addr()?.to_string();
+
+        Ok(Self {
+            listener,
+            socket,
+            client,
+            chunk_size: 128,
+            peer_chunk_size: 128,
+            chunk_streams: HashMap::new(),
+            received: 0,
+            acknowledged: 0,
+            window: 0,
+            app: String::new(),
+            endpoint: None,
+        })
+    }
+
+    fn run(&mut self) -> io::Result<()> {
+        self.handshake()?;
+
+        loop {
+            if self
+                .endpoint
+                .as_ref()
+                .is_some_and(|endpoint| endpoint.closed.load(Ordering::SeqCst))
+            {
+                return Ok(());
+            }
+
+            let message = self.read_message()?;
+            match message.type_id {
+                SET_CHUNK_SIZE => self.set_peer_chunk_size(&message.payload)?,
+                WINDOW_ACKNOWLEDGEMENT_SIZE => self.window = read_u32(&message.payload)?,
+                COMMAND => {
+                    let going_on = self.command(&message)?;
+                    if !going_on {
+                        return Ok(());
+                    }
+                }
+                AUDIO | VIDEO => {
+                    if let Some(endpoint) = self.endpoint.as_ref() {
+                        endpoint.push_tag(message.type_id, message.timestamp, &message.payload);
+                    }
+                }
+                DATA => {
+                    if let Some(endpoint) = self.endpoint.as_ref() {
+                        // Encoders wrap the metadata in a call FLV files do not have.
+                        let mut values = amf::decode(&message.payload);
+                        if values.first().and_then(Value::as_str) == Some("@setDataFrame") {
+                            values.remove(0);
+                        }
+                        endpoint.push_tag(DATA, message.timestamp, &amf::encode(&values));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn set_peer_chunk_size(&mut self, payload: &[u8]) -> io::Result<()> {
+        let size = (read_u32(payload)? & 0x7fff_ffff) as usize;
+        if size == 0 || size > MAX_CHUNK_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("chunk size of {} bytes is out of range", size),
+            ));
+        }
+        self.peer_chunk_size = size;
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        if let Some(endpoint) = self.endpoint.take() {
+            endpoint.disconnect();
+        }
+        let _ = self.socket.shutdown(std::net::Shutdown::Both);
+    }
+
+    fn handshake(&mut self) -> io::Result<()> {
+        let mut c0c1 = vec![0; 1 + HANDSHAKE_SIZE];
+        self.read_exact(&mut c0c1)?;
+        if c0c1[0] != 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported RTMP version {}", c0c1[0]),
+            ));
+        }
+
+        let mut s0s1s2 = Vec::with_capacity(1 + 2 * HANDSHAKE_SIZE);
+        s0s1s2.push(3);
+        s0s1s2.extend_from_slice(&[0; 8]);
+        s0s1s2.extend((8..HANDSHAKE_SIZE).map(|_| fastrand::u8(..)));
+        s0s1s2.extend_from_slice(&c0c1[1..]);
+        self.socket.write_all(&s0s1s2)?;
+
+        let mut c2 = vec![0; HANDSHAKE_SIZE];
+        self.read_exact(&mut c2)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.socket.read_exact(buf)?;
+        self.received += buf.len() as u64;
+
+        if self.window > 0 && self.received - self.acknowledged >= self.window as u64 {
+            self.acknowledged = self.received;
+            let sequence = (self.received as u32).to_be_bytes();
+            self.send(CONTROL_CHUNK_STREAM, ACKNOWLEDGEMENT, 0, &sequence)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_bytes<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut buf = [0; N];
+        self.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn read_u24(&mut self) -> io::Result<u32> {
+        let [a, b, c] = self.read_bytes::<3>()?;
+        Ok(u32::from_be_bytes([0, a, b, c]))
+    }
+
+    /// Reads chunks until a message is complete.
+    fn read_message(&mut self) -> io::Result<Message> {
+        loop {
+            let [first] = self.read_bytes::<1>()?;
+            let format = first >> 6;
+            let id = match first & 0x3f {
+                0 => 64 + self.read_bytes::<1>()?[0] as u32,
+                1 => {
+                    let [low, high] = self.read_bytes::<2>()?;
+                    64 + low as u32 + high as u32 * 256
+                }
+                id => id as u32,
+            };
+
+            if !self.chunk_streams.contains_key(&id)
+                && self.chunk_streams.len() >= MAX_CHUNK_STREAMS
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("more than {} chunk streams", MAX_CHUNK_STREAMS),
+                ));
+            }
+            let mut chunk_stream = self.chunk_streams.remove(&id).unwrap_or_default();
+            let starts_message = chunk_stream.payload.is_empty();
+            match format {
+                0 => {
+                    let timestamp = self.read_u24()?;
+                    chunk_stream.length = self.read_u24()? as usize;
+                    chunk_stream.type_id = self.read_bytes::<1>()?[0];
+                    chunk_stream.stream_id = u32::from_le_bytes(self.read_bytes::<4>()?);
+                    chunk_stream.extended = timestamp == 0xff_ffff;
+                    chunk_stream.timestamp = match chunk_stream.extended {
+                        true => u32::from_be_bytes(self.read_bytes::<4>()?),
+                        false => timestamp,
+                    };
+                    chunk_stream.delta = chunk_stream.timestamp;
+                }
+                1 | 2 => {
+                    let delta = self.read_u24()?;
+                    if format == 1 {
+                        chunk_stream.length = self.read_u24()? as usize;
+                        chunk_stream.type_id = self.read_bytes::<1>()?[0];
+                    }
+                    chunk_stream.extended = delta == 0xff_ffff;
+                    chunk_stream.delta = match chunk_stream.extended {
+                        true => u32::from_be_bytes(self.read_bytes::<4>()?),
+                        false => delta,
+                    };
+                    chunk_stream.timestamp =
+                        chunk_stream.timestamp.wrapping_add(chunk_stream.delta);
+                }
+                _ => {
+                    if chunk_stream.extended {
+                        self.read_bytes::<4>()?;
+                    }
+                    if starts_message {
+                        chunk_stream.timestamp =
+                            chunk_stream.timestamp.wrapping_add(chunk_stream.delta);
+                    }
+                }
+            }
+
+            if chunk_stream.length > MAX_MESSAGE_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("message of {} bytes is too large", chunk_stream.length),
+                ));
+            }
+
+            // A header may change the length of a message halfway through.
+            let start = chunk_stream.payload.len();
+            if start > chunk_stream.length {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "message of {} bytes already has {} bytes",
+                        chunk_stream.length, start
+                    ),
+                ));
+            }
+            let size = (chunk_stream.length - start).min(self.peer_chunk_size);
+            let buffered: usize = self.chunk_streams.values().map(|c| c.payload.len()).sum();
+            if buffered + start + size > MAX_BUFFERED_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "unfinished messages take more than {} bytes",
+                        MAX_BUFFERED_SIZE
+                    ),
+                ));
+            }
+            chunk_stream.payload.resize(start + size, 0);
+            self.read_exact(&mut chunk_stream.payload[start..])?;
+
+            if chunk_stream.payload.len() < chunk_stream.length {
+                self.chunk_streams.insert(id, chunk_stream);
+                continue;
+            }
+
+            let message = Message {
+                type_id: chunk_stream.type_id,
+                stream_id: chunk_stream.stream_id,
+                timestamp: chunk_stream.timestamp,
+                payload: std::mem::take(&mut chunk_stream.payload),
+            };
+            self.chunk_streams.insert(id, chunk_stream);
+            return Ok(message);
+        }
+    }
+
+    fn send(
+        &mut self,
+        chunk_stream: u8,
+        type_id: u8,
+        stream_id: u32,
+        payload: &[u8],
+    ) -> io::Result<()> {
+        let mut out = Vec::with_capacity(payload.len() + 16);
+        out.push(chunk_stream);
+        out.extend_from_slice(&[0, 0, 0]);
+        out.extend_from_slice(&(payload.len() as u32).to_be_bytes()[1..]);
+        out.push(type_id);
+        out.extend_from_slice(&stream_id.to_le_bytes());
+        for (i, chunk) in payload.chunks(self.chunk_size).enumerate() {
+            if i > 0 {
+                out.push(0xc0 | chunk_stream);
+            }
+            out.extend_from_slice(chunk);
+        }
+        self.socket.write_all(&out)
+    }
+
+    fn send_command(
+        &mut self,
+        chunk_stream: u8,
+        stream_id: u32,
+        values: &[Value],
+    ) -> io::Result<()> {
+        self.send(chunk_stream, COMMAND, stream_id, &amf::encode(values))
+    }
+
+    /// Answers a command of the client. Returns whether the session goes on.
+    fn command(&mut self, message: &Message) -> io::Result<bool> {
+        let values = amf::decode(&message.payload);
+        let name = values.first().and_then(Value::as_str).unwrap_or_default();
+        let transaction = values.get(1).and_then(Value::as_number).unwrap_or(0.0);
+
+        match name {
+            "connect" => {
+                self.app = values
+                    .get(2)
+                    .and_then(|object| object.get("app"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .trim_end_matches('/')
+                    .to_string();
+
+                self.send(
+                    CONTROL_CHUNK_STREAM,
+                    WINDOW_ACKNOWLEDGEMENT_SIZE,
+                    0,
+                    &WINDOW_ACK_SIZE.to_be_bytes(),
+                )?;
+                let mut bandwidth = WINDOW_ACK_SIZE.to_be_bytes().to_vec();
+                bandwidth.push(2);
+                self.send(CONTROL_CHUNK_STREAM, SET_PEER_BANDWIDTH, 0, &bandwidth)?;
+                self.send(
+                    CONTROL_CHUNK_STREAM,
+                    SET_CHUNK_SIZE,
+                    0,
+                    &(CHUNK_SIZE as u32).to_be_bytes(),
+                )?;
+                self.chunk_size = CHUNK_SIZE;
+
+                self.send_command(
+                    COMMAND_CHUNK_STREAM,
+                    0,
+                    &[
+                        Value::String("_result".to_string()),
+                        Value::Number(transaction),
+                        Value::Object(vec![
+                            (
+                                "fmsVer".to_string(),
+                                Value::String("FMS/3,0,1,123".to_string()),
+                            ),
+                            ("capabilities".to_string(), Value::Number(31.0)),
+                        ]),
+                        status(
+                            "status",
+                            "NetConnection.Connect.Success",
+                            "Connection succeeded.",
+                        ),
+                    ],
+                )?;
+            }
+            "createStream" => {
+                self.send_command(
+                    COMMAND_CHUNK_STREAM,
+                    0,
+                    &[
+                        Value::String("_result".to_string()),
+                        Value::Number(transaction),
+                        Value::Null,
+                        Value::Number(1.0),
+                    ],
+                )?;
+            }
+            "releaseStream" | "FCPublish" => {
+                self.send_command(
+                    COMMAND_CHUNK_STREAM,
+                    0,
+                    &[
+                        Value::String("_result".to_string()),
+                        Value::Number(transaction),
+                        Value::Null,
+                    ],
+                )?;
+            }
+            "publish" => {
+                // Clients may append a query string, for example to authenticate elsewhere.
+                let stream_key = values
+                    .get(3)
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .split('?')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+
+                let endpoint = self
+                    .listener
+                    .endpoint(&self.app, &stream_key)
+                    .filter(|endpoint| endpoint.connect(&self.client));
+                let endpoint = match endpoint {
+                    Some(endpoint) => endpoint,
+                    None => {
+                        self.send_command(
+                            STATUS_CHUNK_STREAM,
+                            message.stream_id,
+                            &[
+                                Value::String("onStatus".to_string()),
+                                Value::Number(0.0),
+                                Value::Null,
+                                status(
+                                    "error",
+                                    "NetStream.Publish.BadName",
+                                    "No input is waiting for this stream, or it is in use.",
+                                ),
+                            ],
+                        )?;
+                        return Ok(false);
+                    }
+                };
+
+                tracing::info!(
+                    client = self.client.as_str(),
+                    "rtmp: publishing to {}/{}",
+                    self.app,
+                    stream_key
+                );
+                self.endpoint = Some(endpoint);
+                self.send_command(
+                    STATUS_CHUNK_STREAM,
+                    message.stream_id,
+                    &[
+                        Value::String("onStatus".to_string()),
+                        Value::Number(0.0),
+                        Value::Null,
+                        status("status", "NetStream.Publish.Start", "Publishing."),
+                    ],
+                )?;
+            }
+            "FCUnpublish" | "deleteStream" | "closeStream" => return Ok(false),
+            _ => (),
+        }
+
+        Ok(true)
+    }
+}
+
+/// The info object of a status command.
+fn status(level: &str, code: &str, description: &str) -> Value {
+    Value::Object(vec![
+        ("level".to_string(), Value::String(level.to_string())),
+        ("code".to_string(), Value::String(code.to_string())),
+        (
+            "description".to_string(),
+            Value::String(description.to_string()),
+        ),
+    ])
+}
+
+fn read_u32(payload: &[u8]) -> io::Result<u32> {
+    payload
+        .get(..4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "truncated control message"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A session reading what the returned client socket writes.
+    fn connect() -> (Session, TcpStream) {
+        let socket = TcpListener::bind("127.0.0.1:0").expect("failed to listen");
+        let client = TcpStream::connect(socket.local_addr().unwrap()).expect("failed to connect");
+        let (server, _) = socket.accept().expect("failed to accept");
+        let session =
+            Session::new(Arc::new(Listener::default()), server).expect("failed to create session");
+        (session, client)
+    }
+
+    /// A type 0 chunk header on chunk stream 3.
+    fn header(timestamp: u32, length: usize, type_id: u8, stream_id: u32) -> Vec<u8> {
+        let mut header = vec![0x03];
+        header.extend_from_slice(&timestamp.min(0xff_ffff).to_be_bytes()[1..]);
+        header.extend_from_slice(&(length as u32).to_be_bytes()[1..]);
+        header.push(type_id);
+        header.extend_from_slice(&stream_id.to_le_bytes());
+        if timestamp >= 0xff_ffff {
+            header.extend_from_slice(&timestamp.to_be_bytes());
+        }
+        header
+    }
+
+    #[test]
+    fn test_chunk_reassembly() {
+        let (mut session, mut client) = connect();
+        let payload: Vec<u8> = (0..300).map(|i| i as u8).collect();
+
+        let mut data = header(1000, payload.len(), VIDEO, 1);
+        for (i, chunk) in payload.chunks(128).enumerate() {
+            if i > 0 {
+                data.push(0xc3);
+            }
+            data.extend_from_slice(chunk);
+        }
+        // A type 1 header carries a timestamp delta and the length of the next message.
+        data.extend_from_slice(&[0x43, 0, 0, 40, 0, 0, 4, VIDEO]);
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        client.write_all(&data).unwrap();
+
+        let message = session.read_message().expect("failed to read message");
+        assert_eq!(VIDEO, message.type_id);
+        assert_eq!(1, message.stream_id);
+        assert_eq!(1000, message.timestamp);
+        assert_eq!(payload, message.payload);
+
+        let message = session.read_message().expect("failed to read message");
+        assert_eq!(1040, message.timestamp);
+        assert_eq!(vec![1, 2, 3, 4], message.payload);
+    }
+
+    #[test]
+    fn test_extended_timestamp() {
+        let (mut session, mut client) = connect();
+        let timestamp = 0x0100_0000;
+
+        let mut data = header(timestamp, 200, AUDIO, 1);
+        data.extend_from_slice(&[7; 128]);
+        // Continuation chunks repeat the extended timestamp.
+        data.push(0xc3);
+        data.extend_from_slice(&timestamp.to_be_bytes());
+        data.extend_from_slice(&[7; 72]);
+        client.write_all(&data).unwrap();
+
+        let message = session.read_message().expect("failed to read message");
+        assert_eq!(timestamp, message.timestamp);
+        assert_eq!(vec![7; 200], message.payload);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let (mut session, mut client) = connect();
+
+        // The length shrinks below what was read of the message already.
+        let mut data = header(0, 300, VIDEO, 1);
+        data.extend_from_slice(&[0; 128]);
+        data.extend_from_slice(&[0x43, 0, 0, 0, 0, 0, 10, VIDEO]);
+        client.write_all(&data).unwrap();
+        let error = session.read_message().expect_err("accepted shrunk message");
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+
+        for size in [0, MAX_CHUNK_SIZE as u32 + 1] {
+            let error = session
+                .set_peer_chunk_size(&size.to_be_bytes())
+                .expect_err("accepted chunk size");
+            assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        }
+        assert!(session.set_peer_chunk_size(&[0, 0]).is_err());
+        session
+            .set_peer_chunk_size(&4096u32.to_be_bytes())
+            .expect("refused chunk size");
+        assert_eq!(4096, session.peer_chunk_size);
+    }
+
+    #[test]
+    fn test_limits() {
+        // Every chunk stream starts a message and leaves it unfinished.
+        let (mut session, mut client) = connect();
+        let mut data = Vec::new();
+        for id in 0..=MAX_CHUNK_STREAMS as u8 {
+            // A two byte basic header, for chunk stream 64 + id.
+            data.extend_from_slice(&[0x00, id]);
+            data.extend_from_slice(&header(0, 300, VIDEO, 1)[1..]);
+            data.extend_from_slice(&[0; 128]);
+        }
+        client.write_all(&data).unwrap();
+        let error = session
+            .read_message()
+            .expect_err("accepted too many chunk streams");
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+
+        // Few chunk streams, each with most of a large message.
+        let (mut session, mut client) = connect();
+        session.peer_chunk_size = MAX_CHUNK_SIZE;
+        let writer = std::thread::spawn(move || {
+            let chunk = vec![0; MAX_CHUNK_SIZE];
+            for id in 0..3u8 {
+                let mut data = vec![0x00, id];
+                data.extend_from_slice(&header(0, MAX_MESSAGE_SIZE, VIDEO, 1)[1..]);
+                data.extend_from_slice(&chunk);
+                for _ in 1..MAX_MESSAGE_SIZE / MAX_CHUNK_SIZE - 1 {
+                    data.extend_from_slice(&[0xc0, id]);
+                    data.extend_from_slice(&chunk);
+                }
+                // Fails once the session gave up reading.
+                if client.write_all(&data).is_err() {
+                    return;
+                }
+            }
+        });
+        let error = session
+            .read_message()
+            .expect_err("accepted too many buffered bytes");
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+        drop(session);
+        writer.join().unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+use super::{Config, URI};
+use crate::{mixer::Error as MixerError, Result};
+
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+/// Connection statistics of an [`SRT`](struct.SRT.html) input, as reported by `srtsrc`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct Stats {
+    pub rtt_ms: f64,
+    pub packets_received: i64,
+    pub packets_lost: i64,
+    pub packets_retransmitted: i64,
+    /// Packets that arrived too late to be played.
+    pub packets_dropped: i64,
+    pub bytes_received: i64,
+    pub receive_rate_mbps: f64,
+    pub bandwidth_mbps: f64,
+    /// Latency agreed on with the sender.
+    pub latency_ms: i64,
+}
+
+impl Stats {
+    fn from_structure(structure: &gst::StructureRef) -> Self {
+        Self {
+            rtt_ms: number(structure, "rtt-ms"),
+            packets_received: number(structure, "packets-received") as i64,
+            packets_lost: number(structure, "packets-received-lost") as i64,
+            packets_retransmitted: number(structure, "packets-received-retransmitted") as i64,
+            packets_dropped: number(structure, "packets-received-dropped") as i64,
+            bytes_received: number(structure, "bytes-received") as i64,
+            receive_rate_mbps: number(structure, "receive-rate-mbps"),
+            bandwidth_mbps: number(structure, "bandwidth-mbps"),
+            latency_ms: number(structure, "negotiated-latency-ms") as i64,
+        }
+    }
+}
+
+/// Reads a numeric field of the stats, which are not all of the same type. Missing fields, for
+/// example before a sender connected, read as zero.
+fn number(structure: &gst::StructureRef, field: &str) -> f64 {
+    let value = match structure.value(field) {
+        Ok(value) => value,
+        Err(_) => return 0.0,
+    };
+
+    value
+        .get::<f64>()
+        .or_else(|_| value.get::<i64>().map(|v| v as f64))
+        .or_else(|_| value.get::<u64>().map(|v| v as f64))
+        .or_else(|_| value.get::<i32>().map(f64::from))
+        .or_else(|_| value.get::<u32>().map(f64::from))
+        .unwrap_or(0.0)
+}
+
+/// An input receiving MPEG-TS over SRT, from an `srt://` location. It decodes the stream the
+/// same way a [`URI`](../uri/struct.URI.html) input does, which it derefs to for everything
+/// else.
+///
+/// In listener mode the location is the address to listen on, for example `srt://:9000`. In
+/// caller mode it is the address of the sender.
+pub struct SRT {
+    uri: URI,
+    /// The `srtsrc` of the input, once `uridecodebin` created it.
+    source: Arc<Mutex<Option<gst::Element>>>,
+}
+
+impl SRT {
+    pub fn create(config: Config, location: &str) -> Result<Self> {
+        if !location.starts_with("srt://") {
+            return Err(MixerError::InvalidLocation(
+                "SRT".to_string(),
+                location.to_string(),
+            ));
+        }
+
+        let srt = config.srt.clone().unwrap_or_default();
+        let uri = URI::create(config, location)?;
+        let source = Arc::new(Mutex::new(None));
+
+        let setup = Arc::clone(&source);
+        uri.source().connect("source-setup", false, move |args| {
+            if let Ok(element) = args[1].get::<gst::Element>() {
+                element.set_property_from_str("mode", srt.mode.as_str());
+                element.set_property("latency", srt.latency_ms);
+                if let Some(passphrase) = srt.passphrase.as_deref() {
+                    element.set_property("passphrase", passphrase);
+                }
+                *setup.lock().unwrap() = Some(element);
+            }
+            None
+        });
+
+        Ok(Self { uri, source })
+    }
+
+    /// Connection statistics, `None` until the input started.
+    pub fn stats(&self) -> Option<Stats> {
+        let source = self.source.lock().unwrap().clone()?;
+        source
+            .property::<Option<gst::Structure>>("stats")
+            .map(|stats| Stats::from_structure(&stats))
+    }
+}
+
+impl Deref for SRT {
+    type Target = URI;
+
+    fn deref(&self) -> &URI {
+        &self.uri
+    }
+}
+
+impl DerefMut for SRT {
+    fn deref_mut(&mut self) -> &mut URI {
+        &mut self.uri
+    }
+}
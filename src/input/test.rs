@@ -12,12 +12,17 @@ pub struct Test {
     audio: gst::Element,
     audio_convert: gst::Element,
     audio_resample: gst::Element,
+    audio_capsfilter: gst::Element,
+    audio_level: gst::Element,
     audio_queue: gst::Element,
     video: gst::Element,
     video_convert: gst::Element,
     video_scale: gst::Element,
     video_rate: gst::Element,
     video_capsfilter: gst::Element,
+    video_tee: gst::Element,
+    video_queue: gst::Element,
+    preview_queue: gst::Element,
 }
 
 impl Test {
@@ -50,6 +55,13 @@ impl Test {
             .build();
         video_capsfilter.set_property("caps", &video_caps);
 
+        let video_tee = gst_create_element("tee", &format!("input_{}_video_tee", config.name))?;
+        video_tee.set_property("allow-not-linked", true);
+        let video_queue =
+            gst_create_element("queue", &format!("input_{}_video_queue", config.name))?;
+        let preview_queue =
+            gst_create_element("queue", &format!("input_{}_preview_queue", config.name))?;
+
         let audio = gst_create_element(
             "audiotestsrc",
             &format!("input_{}_audiotestsrc", config.name),
@@ -66,6 +78,16 @@ impl Test {
             "audioresample",
             &format!("input_{}_audio_resample", config.name),
         )?;
+        let audio_capsfilter = gst_create_element(
+            "capsfilter",
+            &format!("input_{}_audio_capsfilter", config.name),
+        )?;
+        let audio_level =
+            gst_create_element("level", &format!("input_{}_audio_level", config.name))?;
+
+        super::set_src_pad_offset(&audio_queue, config.audio_offset_ms)?;
+        super::set_src_pad_offset(&video_queue, config.video_offset_ms)?;
+        super::set_src_pad_offset(&preview_queue, config.video_offset_ms)?;
 
         Ok(Test {
             name: config.name.clone(),
@@ -74,12 +96,17 @@ impl Test {
             audio,
             audio_queue,
             audio_resample,
+            audio_capsfilter,
+            audio_level,
             audio_convert,
             video,
             video_convert,
             video_rate,
             video_scale,
             video_capsfilter,
+            video_tee,
+            video_queue,
+            preview_queue,
         })
     }
 
@@ -92,16 +119,23 @@ impl Test {
         pipeline: gst::Pipeline,
         audio: gst::Element,
         video: gst::Element,
+        audio_caps: &gst::Caps,
     ) -> Result<()> {
+        self.audio_capsfilter.set_property("caps", audio_caps);
+
         pipeline.add_many(&[
             &self.video,
             &self.video_convert,
             &self.video_scale,
             &self.video_rate,
             &self.video_capsfilter,
+            &self.video_tee,
+            &self.video_queue,
             &self.audio,
             &self.audio_convert,
             &self.audio_resample,
+            &self.audio_capsfilter,
+            &self.audio_level,
             &self.audio_queue,
         ])?;
 
@@ -114,6 +148,8 @@ impl Test {
             &self.video_scale,
             &self.video_rate,
             &self.video_capsfilter,
+            &self.video_tee,
+            &self.video_queue,
             &video,
         ])?;
 
@@ -122,6 +158,8 @@ impl Test {
             &self.audio,
             &self.audio_convert,
             &self.audio_resample,
+            &self.audio_capsfilter,
+            &self.audio_level,
             &self.audio_queue,
             &audio,
         ])?;
@@ -129,22 +167,42 @@ impl Test {
         Ok(())
     }
 
+    pub fn link_preview(&mut self, video: gst::Element) -> Result<()> {
+        let pipeline = self.pipeline.as_ref().ok_or_else(|| {
+            MixerError::Gstreamer("input must be linked before the preview".to_string())
+        })?;
+
+        pipeline.add(&self.preview_queue)?;
+        gst::Element::link_many([&self.video_tee, &self.preview_queue, &video])?;
+
+        Ok(())
+    }
+
     pub fn unlink(&self) -> Result<()> {
-        super::release_request_pad(&self.audio)?;
-        super::release_request_pad(&self.video)?;
+        super::release_request_pad(&self.audio_queue)?;
+        super::release_request_pad(&self.video_queue)?;
+        super::release_request_pad(&self.preview_queue)?;
 
         if let Some(pipeline) = self.pipeline.as_ref() {
-            pipeline.remove_many(&[
-                &self.video,
-                &self.video_convert,
-                &self.video_scale,
-                &self.video_rate,
-                &self.video_capsfilter,
-                &self.audio,
-                &self.audio_convert,
-                &self.audio_resample,
-                &self.audio_queue,
-            ])?;
+            super::remove_added(
+                pipeline,
+                &[
+                    &self.video,
+                    &self.video_convert,
+                    &self.video_scale,
+                    &self.video_rate,
+                    &self.video_capsfilter,
+                    &self.video_tee,
+                    &self.video_queue,
+                    &self.preview_queue,
+                    &self.audio,
+                    &self.audio_convert,
+                    &self.audio_resample,
+                    &self.audio_capsfilter,
+                    &self.audio_level,
+                    &self.audio_queue,
+                ],
+            )?;
         }
         Ok(())
     }
@@ -153,12 +211,20 @@ impl Test {
         self.audio.set_state(state)?;
         self.audio_convert.set_state(state)?;
         self.audio_resample.set_state(state)?;
+        self.audio_capsfilter.set_state(state)?;
+        self.audio_level.set_state(state)?;
         self.audio_queue.set_state(state)?;
         self.video.set_state(state)?;
         self.video_convert.set_state(state)?;
         self.video_scale.set_state(state)?;
         self.video_rate.set_state(state)?;
         self.video_capsfilter.set_state(state)?;
+        self.video_tee.set_state(state)?;
+        self.video_queue.set_state(state)?;
+        // The preview branch is only added once the input is linked to the preview bus.
+        if self.preview_queue.parent().is_some() {
+            self.preview_queue.set_state(state)?;
+        }
         Ok(())
     }
 
@@ -169,7 +235,7 @@ impl Test {
     pub fn set_zorder(&mut self, zorder: u32, _update_config: bool) -> Result<()> {
         super::set_peer_pad_property(
             &self
-                .video_capsfilter
+                .video_queue
                 .static_pad("src")
                 .ok_or_else(|| MixerError::Gstreamer("Failed to get static src pad".to_string()))?,
             "zorder",
@@ -199,7 +265,42 @@ impl Test {
         Ok(())
     }
 
+    pub fn set_audio_offset(&mut self, offset_ms: i64, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.audio_offset_ms = offset_ms;
+        }
+        super::set_src_pad_offset(&self.audio_queue, offset_ms)
+    }
+
+    pub fn set_video_offset(&mut self, offset_ms: i64, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video_offset_ms = offset_ms;
+        }
+        super::set_src_pad_offset(&self.video_queue, offset_ms)?;
+        super::set_src_pad_offset(&self.preview_queue, offset_ms)
+    }
+
     pub fn config(&self) -> Config {
         self.config.clone()
     }
+
+    pub fn compositor_pad(&self) -> Option<gst::Pad> {
+        self.video_queue.static_pad("src")?.peer()
+    }
+
+    pub fn video_src_pad(&self) -> Option<gst::Pad> {
+        self.video_queue.static_pad("src")
+    }
+
+    pub fn audio_src_pad(&self) -> Option<gst::Pad> {
+        self.audio_queue.static_pad("src")
+    }
+
+    pub fn preview_pad(&self) -> Option<gst::Pad> {
+        self.preview_queue.static_pad("src")?.peer()
+    }
+
+    pub fn video_tee(&self) -> gst::Element {
+        self.video_tee.clone()
+    }
 }
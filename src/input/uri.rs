@@ -1,7 +1,7 @@
 use super::Config;
 use crate::{
     gst_create_element, mixer,
-    output::{Config as OutputConfig, EncoderConfig, File as FileOutput},
+    output::{Bus, Config as OutputConfig, EncoderConfig, File as FileOutput},
     AudioEncoder, AudioEncoderConfig, Mux, Result, VideoEncoder, VideoEncoderConfig,
 };
 
@@ -18,7 +18,9 @@ pub struct URI {
     audio_tee_queue: gst::Element,
     audio_convert: gst::Element,
     audio_resample: gst::Element,
+    audio_capsfilter: gst::Element,
     audio_volume: gst::Element,
+    audio_level: gst::Element,
     audio_queue: gst::Element,
     video_tee: gst::Element,
     video_tee_queue: gst::Element,
@@ -27,6 +29,7 @@ pub struct URI {
     video_rate: gst::Element,
     video_capsfilter: gst::Element,
     video_queue: gst::Element,
+    preview_queue: gst::Element,
     record_output: Option<FileOutput>,
 }
 
@@ -66,6 +69,8 @@ impl URI {
 
         let video_queue =
             gst_create_element("queue2", &format!("input_{}_video_queue", config.name))?;
+        let preview_queue =
+            gst_create_element("queue", &format!("input_{}_preview_queue", config.name))?;
 
         let audio_tee_queue =
             gst_create_element("queue2", &format!("input_{}_audio_tee_queue", config.name))?;
@@ -82,10 +87,16 @@ impl URI {
             "audioresample",
             &format!("input_{}_audio_resample", config.name),
         )?;
+        let audio_capsfilter = gst_create_element(
+            "capsfilter",
+            &format!("input_{}_audio_capsfilter", config.name),
+        )?;
 
         let audio_volume =
             gst_create_element("volume", &format!("input_{}_audio_volume", config.name))?;
         audio_volume.set_property("volume", &config.audio.volume);
+        let audio_level =
+            gst_create_element("level", &format!("input_{}_audio_level", config.name))?;
 
         let audio = audio_convert.clone();
         let video = video_convert.clone();
@@ -204,6 +215,9 @@ impl URI {
                 },
             },
             mux: Some(Mux::MKV),
+            bus: Bus::Program,
+            fixed_caps: false,
+            sink: None,
         };
 
         let record_output = match config.record {
@@ -215,9 +229,13 @@ impl URI {
             false => None,
         };
 
+        super::set_src_pad_offset(&audio_queue, config.audio_offset_ms)?;
+        super::set_src_pad_offset(&video_queue, config.video_offset_ms)?;
+        super::set_src_pad_offset(&preview_queue, config.video_offset_ms)?;
+
         Ok(Self {
             name: config.name.to_string(),
-            location: config.name.to_string(),
+            location: uri.to_string(),
             config,
             pipeline: None,
             source,
@@ -225,7 +243,9 @@ impl URI {
             audio_tee_queue,
             audio_convert,
             audio_volume,
+            audio_level,
             audio_resample,
+            audio_capsfilter,
             audio_queue,
             video_tee,
             video_tee_queue,
@@ -234,6 +254,7 @@ impl URI {
             video_rate,
             video_capsfilter,
             video_queue,
+            preview_queue,
             record_output,
         })
     }
@@ -247,7 +268,10 @@ impl URI {
         pipeline: gst::Pipeline,
         audio: gst::Element,
         video: gst::Element,
+        audio_caps: &gst::Caps,
     ) -> Result<()> {
+        self.audio_capsfilter.set_property("caps", audio_caps);
+
         pipeline.add_many(&[
             &self.audio_tee_queue,
             &self.audio_tee,
@@ -267,7 +291,9 @@ impl URI {
             &self.source,
             &self.audio_convert,
             &self.audio_volume,
+            &self.audio_level,
             &self.audio_resample,
+            &self.audio_capsfilter,
             &self.audio_queue,
             &self.video_convert,
             &self.video_scale,
@@ -281,7 +307,9 @@ impl URI {
         gst::Element::link_many(&[
             &self.audio_convert,
             &self.audio_volume,
+            &self.audio_level,
             &self.audio_resample,
+            &self.audio_capsfilter,
             &self.audio_tee_queue,
             &self.audio_tee,
             &self.audio_queue,
@@ -320,27 +348,45 @@ impl URI {
         Ok(())
     }
 
+    pub fn link_preview(&mut self, video: gst::Element) -> Result<()> {
+        let pipeline = self.pipeline.as_ref().ok_or_else(|| {
+            mixer::Error::Gstreamer("input must be linked before the preview".to_string())
+        })?;
+
+        pipeline.add(&self.preview_queue)?;
+        gst::Element::link_many([&self.video_tee, &self.preview_queue, &video])?;
+
+        Ok(())
+    }
+
     pub fn unlink(&self) -> Result<()> {
         super::release_request_pad(&self.audio_queue)?;
         super::release_request_pad(&self.video_queue)?;
+        super::release_request_pad(&self.preview_queue)?;
 
         if let Some(pipeline) = self.pipeline.as_ref() {
-            pipeline.remove_many(&[
-                &self.source,
-                &self.audio_tee,
-                &self.audio_tee_queue,
-                &self.audio_convert,
-                &self.audio_volume,
-                &self.audio_resample,
-                &self.audio_queue,
-                &self.video_tee,
-                &self.video_tee_queue,
-                &self.video_convert,
-                &self.video_scale,
-                &self.video_rate,
-                &self.video_capsfilter,
-                &self.video_queue,
-            ])?;
+            super::remove_added(
+                pipeline,
+                &[
+                    &self.source,
+                    &self.audio_tee,
+                    &self.audio_tee_queue,
+                    &self.audio_convert,
+                    &self.audio_volume,
+                    &self.audio_level,
+                    &self.audio_resample,
+                    &self.audio_capsfilter,
+                    &self.audio_queue,
+                    &self.video_tee,
+                    &self.video_tee_queue,
+                    &self.video_convert,
+                    &self.video_scale,
+                    &self.video_rate,
+                    &self.video_capsfilter,
+                    &self.video_queue,
+                    &self.preview_queue,
+                ],
+            )?;
         }
 
         Ok(())
@@ -350,13 +396,19 @@ impl URI {
         self.source.set_state(state)?;
         self.audio_convert.set_state(state)?;
         self.audio_resample.set_state(state)?;
+        self.audio_capsfilter.set_state(state)?;
         self.audio_volume.set_state(state)?;
+        self.audio_level.set_state(state)?;
         self.audio_queue.set_state(state)?;
         self.video_convert.set_state(state)?;
         self.video_scale.set_state(state)?;
         self.video_rate.set_state(state)?;
         self.video_capsfilter.set_state(state)?;
         self.video_queue.set_state(state)?;
+        // The preview branch is only added once the input is linked to the preview bus.
+        if self.preview_queue.parent().is_some() {
+            self.preview_queue.set_state(state)?;
+        }
         Ok(())
     }
 
@@ -464,7 +516,51 @@ impl URI {
         Ok(())
     }
 
+    pub fn set_audio_offset(&mut self, offset_ms: i64, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.audio_offset_ms = offset_ms;
+        }
+        super::set_src_pad_offset(&self.audio_queue, offset_ms)
+    }
+
+    pub fn set_video_offset(&mut self, offset_ms: i64, update_config: bool) -> Result<()> {
+        if update_config {
+            self.config.video_offset_ms = offset_ms;
+        }
+        super::set_src_pad_offset(&self.video_queue, offset_ms)?;
+        super::set_src_pad_offset(&self.preview_queue, offset_ms)
+    }
+
     pub fn config(&self) -> Config {
         self.config.clone()
     }
+
+    pub fn compositor_pad(&self) -> Option<gst::Pad> {
+        self.video_queue.static_pad("src")?.peer()
+    }
+
+    pub fn video_src_pad(&self) -> Option<gst::Pad> {
+        self.video_queue.static_pad("src")
+    }
+
+    pub fn audio_src_pad(&self) -> Option<gst::Pad> {
+        self.audio_queue.static_pad("src")
+    }
+
+    pub fn preview_pad(&self) -> Option<gst::Pad> {
+        self.preview_queue.static_pad("src")?.peer()
+    }
+
+    pub fn volume_element(&self) -> gst::Element {
+        self.audio_volume.clone()
+    }
+
+    pub fn video_tee(&self) -> gst::Element {
+        self.video_tee.clone()
+    }
+
+    /// The `uridecodebin` the input decodes its URI with.
+    pub(crate) fn source(&self) -> &gst::Element {
+        &self.source
+    }
 }
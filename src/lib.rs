@@ -11,12 +11,18 @@ use serde::{Deserialize, Serialize};
 
 type Result<T> = std::result::Result<T, Error>;
 
+/// Creates an element of `element_type` named `name`. Elements are named after the mixer, input
+/// or output they belong to (`mixer_{name}_*`, `input_{name}_*` and `output_{name}_*`), which is
+/// how the messages they post are attributed to their source, see
+/// [`mixer::event::Source`](mixer/event/enum.Source.html).
 fn gst_create_element(element_type: &str, name: &str) -> Result<gst::Element> {
-    Ok(gst::ElementFactory::make(element_type).build()
-        .map_err(|_| Error::Gstreamer(format!("Failed to create element: {}", name)))?)
+    gst::ElementFactory::make(element_type)
+        .name(name)
+        .build()
+        .map_err(|_| Error::Gstreamer(format!("Failed to create element: {}", name)))
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct VideoConfig {
     #[serde(default = "VideoConfig::framerate_default")]
@@ -74,7 +80,7 @@ impl Default for VideoConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct VideoEncoderConfig {
     #[serde(default = "VideoEncoderConfig::encoder_default")]
     pub encoder: VideoEncoder,
@@ -100,7 +106,7 @@ impl Default for VideoEncoderConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct AudioEncoderConfig {
     #[serde(default = "AudioEncoderConfig::encoder_default")]
     pub encoder: AudioEncoder,
@@ -120,7 +126,7 @@ impl Default for AudioEncoderConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(default)]
 pub struct AudioConfig {
     #[serde(default = "AudioConfig::volume_default")]
@@ -141,6 +147,31 @@ impl AudioConfig {
     }
 }
 
+/// Format the audio of a mixer is mixed in. Inputs are resampled, mixed down or up and converted
+/// to the format of their mixer.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct AudioMixConfig {
+    pub rate: AudioRate,
+    pub channels: AudioChannels,
+    pub format: AudioFormat,
+}
+
+impl AudioMixConfig {
+    /// Raw audio caps with the rate, channel layout and format of this config.
+    pub fn caps(&self) -> gst::Caps {
+        let mut caps = gst::Caps::builder("audio/x-raw")
+            .field("rate", self.rate.hz())
+            .field("channels", self.channels.count())
+            .field("layout", "interleaved")
+            .field("format", self.format.to_string());
+        if let Some(mask) = self.channels.mask() {
+            caps = caps.field("channel-mask", gst::Bitmask::new(mask));
+        }
+        caps.build()
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone)]
 #[allow(non_camel_case_types)]
 pub enum VideoEncoder {
@@ -271,6 +302,96 @@ impl std::fmt::Display for AudioEncoder {
     }
 }
 
+/// Sample rate of a mix, given in Hz.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(try_from = "i32", into = "i32")]
+pub enum AudioRate {
+    Hz44100,
+    #[default]
+    Hz48000,
+}
+
+impl AudioRate {
+    pub fn hz(&self) -> i32 {
+        match self {
+            AudioRate::Hz44100 => 44100,
+            AudioRate::Hz48000 => 48000,
+        }
+    }
+}
+
+impl std::convert::TryFrom<i32> for AudioRate {
+    type Error = String;
+
+    fn try_from(hz: i32) -> std::result::Result<Self, Self::Error> {
+        match hz {
+            44100 => Ok(AudioRate::Hz44100),
+            48000 => Ok(AudioRate::Hz48000),
+            _ => Err(format!(
+                "unsupported sample rate {}, expected 44100 or 48000",
+                hz
+            )),
+        }
+    }
+}
+
+impl From<AudioRate> for i32 {
+    fn from(rate: AudioRate) -> Self {
+        rate.hz()
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+pub enum AudioChannels {
+    Mono,
+    #[default]
+    Stereo,
+    /// 5.1 surround: front left and right, center, LFE, rear left and right.
+    Surround51,
+}
+
+impl AudioChannels {
+    pub fn count(&self) -> i32 {
+        match self {
+            AudioChannels::Mono => 1,
+            AudioChannels::Stereo => 2,
+            AudioChannels::Surround51 => 6,
+        }
+    }
+
+    /// Channel positions of layouts with more than two channels, which have no implied positions.
+    fn mask(&self) -> Option<u64> {
+        match self {
+            AudioChannels::Surround51 => Some(0x3f),
+            _ => None,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+pub enum AudioFormat {
+    S16LE,
+    S24LE,
+    #[default]
+    S32LE,
+    F32LE,
+}
+
+impl std::fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use AudioFormat::*;
+
+        let s = match self {
+            S16LE => "S16LE",
+            S24LE => "S24LE",
+            S32LE => "S32LE",
+            F32LE => "F32LE",
+        };
+
+        f.write_str(s)
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone)]
 #[allow(non_camel_case_types)]
 pub enum Mux {
@@ -1,17 +1,52 @@
+pub mod background;
 mod error;
+pub mod event;
+mod failover;
+pub mod health;
+pub mod layout;
+pub mod level;
+pub mod routing;
+pub mod scene;
+pub mod snapshot;
+pub mod stats;
+pub mod tally;
+pub mod transition;
 
-use crate::{gst_create_element, input, output, AudioConfig, Result, VideoConfig};
+use crate::{gst_create_element, input, output, AudioConfig, AudioMixConfig, Result, VideoConfig};
+pub use background::{Background, Pattern};
 pub use error::Error;
+pub use event::Event;
 use gst::prelude::*;
+pub use health::{Health, HealthState};
+pub use layout::{Geometry, Layout, LayoutType};
+pub use level::{Level, Levels};
+pub use routing::{AudioMode, AudioRoute, AudioStatus};
+pub use scene::{Scene, SceneInput};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+pub use snapshot::ImageFormat;
+pub use stats::Stats;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+pub use tally::Tally;
+use tokio::{runtime, sync::broadcast};
+pub use transition::{Transition, TransitionType};
 // use gst::ffi;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// The zorder of the input on program, above the zorder of any input that is not.
+const PROGRAM_ZORDER: u32 = 1000;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
 pub struct Config {
     pub name: String,
     pub video: VideoConfig,
     pub audio: AudioConfig,
+    #[serde(default)]
+    pub audio_mix: AudioMixConfig,
+    #[serde(default)]
+    pub background: Background,
 }
 
 pub struct Mixer {
@@ -19,11 +54,34 @@ pub struct Mixer {
     pipeline: gst::Pipeline,
     audio_mixer: gst::Element,
     video_mixer: gst::Element,
+    video_capsfilter: gst::Element,
+    preview_capsfilter: gst::Element,
     pub inputs: HashMap<String, input::Input>,
     pub outputs: HashMap<String, output::Output>,
+    pub scenes: HashMap<String, Scene>,
     audio_out: gst::Element,
     video_out: gst::Element,
+    preview_mixer: gst::Element,
+    preview_out: gst::Element,
     join_handle: Option<std::thread::JoinHandle<()>>,
+    transition: Option<transition::Handle>,
+    active: Option<String>,
+    preview: Option<String>,
+    events: broadcast::Sender<Event>,
+    sources: Arc<Mutex<event::Sources>>,
+    tick_events: broadcast::Receiver<Event>,
+    failovers: HashMap<String, failover::Active>,
+    health: HashMap<String, health::Monitor>,
+    meter: level::Meter,
+    audio_routes: HashMap<String, AudioRoute>,
+    background: background::Chain,
+    /// Latest frame of program, for snapshots.
+    program_tap: snapshot::Tap,
+    /// Latest frame of every input with video, for snapshots.
+    input_taps: HashMap<String, snapshot::Tap>,
+    /// The tally last published, see [`tally`](#method.tally).
+    tally: Vec<Tally>,
+    stats: stats::Recorder,
 }
 
 impl Drop for Mixer {
@@ -34,7 +92,6 @@ impl Drop for Mixer {
 
 impl Mixer {
     pub fn new(config: Config) -> Result<Self> {
-        let background_enabled = true;
         let pipeline = gst::Pipeline::new();
         //TODO pipeline.set_name(&config.name.as_str());
 
@@ -47,12 +104,8 @@ impl Mixer {
             "compositor",
             format!("mixer_{}_video_compositor", config.name).as_str(),
         )?;
-        let video_caps = gst::Caps::builder("video/x-raw")
-            .field("framerate", &gst::Fraction::new(config.video.framerate, 1))
-            .field("format", &config.video.format.to_string())
-            .field("width", &config.video.width)
-            .field("height", &config.video.height)
-            .build();
+        video_mixer.set_property_from_str("background", "black");
+        let video_caps = video_caps(&config.video);
 
         video_capsfilter.set_property("caps", &video_caps);
 
@@ -67,6 +120,39 @@ impl Mixer {
         pipeline.add_many(&[&video_mixer, &video_capsfilter, &video_queue, &video_tee])?;
         gst::Element::link_many(&[&video_mixer, &video_capsfilter, &video_queue, &video_tee])?;
 
+        // Create Preview Channel, this is fed by the same inputs as the video channel and lets
+        // operators stage an input before taking it to program.
+        let preview_mixer = gst_create_element(
+            "compositor",
+            format!("mixer_{}_preview_compositor", config.name).as_str(),
+        )?;
+        preview_mixer.set_property_from_str("background", "black");
+        let preview_capsfilter = gst_create_element(
+            "capsfilter",
+            format!("mixer_{}_preview_capsfilter", config.name).as_str(),
+        )?;
+        preview_capsfilter.set_property("caps", &video_caps);
+        let preview_queue = gst_create_element(
+            "queue",
+            format!("mixer_{}_preview_queue", config.name).as_str(),
+        )?;
+        let preview_tee =
+            gst_create_element("tee", format!("mixer_{}_preview_tee", config.name).as_str())?;
+        preview_tee.set_property("allow-not-linked", true);
+
+        pipeline.add_many([
+            &preview_mixer,
+            &preview_capsfilter,
+            &preview_queue,
+            &preview_tee,
+        ])?;
+        gst::Element::link_many([
+            &preview_mixer,
+            &preview_capsfilter,
+            &preview_queue,
+            &preview_tee,
+        ])?;
+
         let audio_mixer = gst_create_element(
             "audiomixer",
             format!("mixer_{}_audio_mixer", config.name).as_str(),
@@ -80,47 +166,100 @@ impl Mixer {
             "capsfilter",
             format!("mixer_{}_audio_capsfilter", config.name).as_str(),
         )?;
-        let audio_caps = gst::Caps::builder("audio/x-raw")
-            .field("channels", &2)
-            .field("layout", &"interleaved")
-            .field("format", &"S32LE")
-            .build();
-        audio_capsfilter.set_property("caps", &audio_caps);
+        audio_capsfilter.set_property("caps", config.audio_mix.caps());
+        let audio_level = gst_create_element(
+            "level",
+            format!("mixer_{}_audio_level", config.name).as_str(),
+        )?;
 
         let audio_tee =
             gst_create_element("tee", format!("mixer_{}_audio_tee", config.name).as_str())?;
         audio_tee.set_property("allow-not-linked", &true);
 
-        pipeline.add_many(&[&audio_mixer, &volume, &audio_capsfilter, &audio_tee])?;
-        gst::Element::link_many(&[&audio_mixer, &volume, &audio_capsfilter, &audio_tee])?;
+        pipeline.add_many([
+            &audio_mixer,
+            &volume,
+            &audio_capsfilter,
+            &audio_level,
+            &audio_tee,
+        ])?;
+        gst::Element::link_many([
+            &audio_mixer,
+            &volume,
+            &audio_capsfilter,
+            &audio_level,
+            &audio_tee,
+        ])?;
 
-        let mixer = Mixer {
+        let background = background::Chain::create(
+            &config.name,
+            &config.background,
+            &config.video,
+            &config.audio_mix.caps(),
+        )?;
+
+        let program_tap =
+            snapshot::Tap::attach(&pipeline, &video_tee, &format!("mixer_{}", config.name))?;
+
+        let stats = stats::Recorder::default();
+        if let Some(pad) = video_capsfilter.static_pad("src") {
+            let stats = stats.clone();
+            pad.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+                stats.frame(Instant::now());
+                gst::PadProbeReturn::Ok
+            });
+        }
+        // Stream status messages are handled synchronously, in the streaming thread they are
+        // about, so the thread can be identified.
+        if let Some(bus) = pipeline.bus() {
+            let stats = stats.clone();
+            bus.set_sync_handler(move |_, msg| {
+                if let gst::MessageView::StreamStatus(status) = msg.view() {
+                    stats.stream_status(status);
+                }
+                gst::BusSyncReply::Pass
+            });
+        }
+
+        let (events, tick_events) = broadcast::channel(event::CHANNEL_CAPACITY);
+        let mut mixer = Mixer {
             config: config.clone(),
             pipeline,
             join_handle: None,
+            transition: None,
+            active: None,
             audio_mixer,
             video_mixer,
+            video_capsfilter,
+            preview_capsfilter,
             inputs: HashMap::new(),
             outputs: HashMap::new(),
+            scenes: HashMap::new(),
             audio_out: audio_tee,
             video_out: video_tee,
+            preview_mixer,
+            preview_out: preview_tee,
+            preview: None,
+            events,
+            sources: Arc::new(Mutex::new(event::Sources::default())),
+            tick_events,
+            failovers: HashMap::new(),
+            health: HashMap::new(),
+            meter: level::Meter::new(audio_level),
+            audio_routes: HashMap::new(),
+            background,
+            program_tap,
+            input_taps: HashMap::new(),
+            tally: Vec::new(),
+            stats,
         };
 
-        let config = input::Config {
-            name: "background".to_string(),
-            audio: AudioConfig { volume: 0.0 },
-            video: config.video,
-            record: false,
-        };
-
-        let mut background = input::Input::create_test(config)?;
-        if background_enabled {
-            background.link(
-                mixer.pipeline.clone(),
-                mixer.audio_mixer.clone(),
-                mixer.video_mixer.clone(),
-            )?;
-        }
+        mixer.background.link(
+            &mixer.pipeline,
+            &mixer.audio_mixer,
+            &mixer.video_mixer,
+            &mixer.preview_mixer,
+        )?;
 
         Ok(mixer)
     }
@@ -133,6 +272,11 @@ impl Mixer {
         if self.inputs.contains_key(&input.name()) {
             return Err(Error::Exists("input".to_string(), input.name()));
         }
+        if let Some(failover) = input.config().failover {
+            if !self.inputs.contains_key(&failover.input) {
+                return Err(Error::NotFound("input".to_string(), failover.input));
+            }
+        }
 
         // TODO: Handle pending states
         let state = self.pipeline.state(gst::ClockTime::from_seconds(15)).1;
@@ -141,13 +285,96 @@ impl Mixer {
             self.pipeline.clone(),
             self.audio_mixer.clone(),
             self.video_mixer.clone(),
+            &self.config.audio_mix.caps(),
         )?;
+        if let Err(e) = self.input_branches_add(&mut input) {
+            self.input_branches_remove(&input.name());
+            let stopped = input.set_state(gst::State::Null);
+            if let Err(e) = stopped.and_then(|_| input.unlink()) {
+                tracing::warn!("failed to unlink input {}: {}", input.name(), e);
+            }
+            return Err(e);
+        }
 
-        self.inputs.insert(input.name(), input);
+        if let Some(pad) = input.compositor_pad() {
+            let events = self.events.clone();
+            let name = input.name();
+            pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, info| {
+                if let Some(gst::PadProbeData::Event(ref event)) = info.data {
+                    if event.type_() == gst::EventType::Eos {
+                        let _ = events.send(Event::InputEos {
+                            input: name.clone(),
+                        });
+                    }
+                }
+                gst::PadProbeReturn::Ok
+            });
+        }
+
+        if let Some(video) = input.video_src_pad() {
+            let config = input.config();
+            let stall_timeout = config
+                .stall_timeout_ms
+                .or_else(|| config.failover.and_then(|f| f.timeout_ms))
+                .map(Duration::from_millis)
+                .unwrap_or(health::DEFAULT_STALL_TIMEOUT);
+            let audio = input.audio_src_pad();
+            self.health.insert(
+                input.name(),
+                health::Monitor::watch(&video, audio.as_ref(), stall_timeout),
+            );
+        }
+
+        let name = input.name();
+        self.sources.lock().unwrap().inputs.insert(name.clone());
+        self.inputs.insert(name.clone(), input);
+        // Mutes the new input unless its audio route puts it on air.
+        self.audio_apply()?;
+        self.preview_layout()?;
+        self.multiview_tally();
+        self.emit(Event::InputAdded { input: name });
+        self.tally_update();
 
         Ok(())
     }
 
+    /// Branches the linked `input` off to the preview bus, its snapshot tap and the
+    /// multiviews.
+    fn input_branches_add(&mut self, input: &mut input::Input) -> Result<()> {
+        input.link_preview(self.preview_mixer.clone())?;
+        if let Some(tee) = input.video_tee() {
+            let prefix = format!("input_{}", input.name());
+            self.input_taps.insert(
+                input.name(),
+                snapshot::Tap::attach(&self.pipeline, &tee, &prefix)?,
+            );
+            for output in self.outputs.values_mut() {
+                if let output::Output::Multiview(multiview) = output {
+                    multiview.input_add(&input.name(), &tee)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes what [`input_branches_add`](#method.input_branches_add) added so far, after it
+    /// failed.
+    fn input_branches_remove(&mut self, name: &str) {
+        if let Some(tap) = self.input_taps.remove(name) {
+            if let Err(e) = tap.detach(&self.pipeline) {
+                tracing::warn!("failed to detach snapshot tap of {}: {}", name, e);
+            }
+        }
+        for output in self.outputs.values_mut() {
+            if let output::Output::Multiview(multiview) = output {
+                if let Err(e) = multiview.input_remove(name) {
+                    tracing::warn!("failed to remove multiview tile of {}: {}", name, e);
+                }
+            }
+        }
+    }
+
     pub fn input_remove(&mut self, name: &str) -> Result<()> {
         if !self.inputs.contains_key(name) {
             return Err(Error::NotFound("input".to_string(), name.to_string()));
@@ -157,9 +384,34 @@ impl Mixer {
             .inputs
             .get_mut(name)
             .ok_or_else(|| Error::NotFound("mixer".to_string(), name.to_string()))?;
+        if let Some(tap) = self.input_taps.remove(name) {
+            tap.detach(&self.pipeline)?;
+        }
+        for output in self.outputs.values_mut() {
+            if let output::Output::Multiview(multiview) = output {
+                multiview.input_remove(name)?;
+            }
+        }
         input.set_state(gst::State::Null)?;
         input.unlink()?;
         self.inputs.remove(name);
+        self.sources.lock().unwrap().inputs.remove(name);
+        self.health.remove(name);
+        self.meter.remove(name);
+        self.stats.remove(&event::Source::Input(name.to_string()));
+        self.audio_routes.remove(name);
+        self.failovers.remove(name);
+        self.emit(Event::InputRemoved {
+            input: name.to_string(),
+        });
+
+        if self.active.as_deref() == Some(name) {
+            self.program_set(None);
+        }
+        if self.preview.as_deref() == Some(name) {
+            self.preview_set(None);
+        }
+        self.tally_update();
 
         Ok(())
     }
@@ -173,15 +425,27 @@ impl Mixer {
             return Err(Error::Exists("output".to_string(), output.name()));
         }
 
+        // The preview bus has no audio mix of its own, outputs tapping it receive program audio.
+        let video_out = match output.config().bus {
+            output::Bus::Program => self.video_out.clone(),
+            output::Bus::Preview => self.preview_out.clone(),
+        };
+
         // TODO: Handle pending states
         let state = self.pipeline.state(gst::ClockTime::from_seconds(15)).1;
         output.set_state(state)?;
-        output.link(
-            self.pipeline.clone(),
-            self.audio_out.clone(),
-            self.video_out.clone(),
-        )?;
+        output.link(self.pipeline.clone(), self.audio_out.clone(), video_out)?;
+        if let output::Output::Multiview(multiview) = &mut output {
+            multiview.tiles_link(&self.video_out, &self.preview_out)?;
+            for (name, input) in &self.inputs {
+                if let Some(tee) = input.video_tee() {
+                    multiview.input_add(name, &tee)?;
+                }
+            }
+            multiview.set_tally(self.active.as_deref(), self.preview.as_deref());
+        }
 
+        self.sources.lock().unwrap().outputs.insert(output.name());
         self.outputs.insert(output.name(), output);
 
         Ok(())
@@ -199,28 +463,76 @@ impl Mixer {
         output.set_state(gst::State::Null)?;
         output.unlink()?;
         self.outputs.remove(name);
+        self.sources.lock().unwrap().outputs.remove(name);
+        self.stats.remove(&event::Source::Output(name.to_string()));
 
         Ok(())
     }
 
     pub fn play(&mut self) -> Result<()> {
         let p = self.pipeline.clone();
-        self.join_handle = Some(std::thread::spawn(move || watch_bus(p)));
+        let events = self.events.clone();
+        let sources = self.sources.clone();
+        let meter = self.meter.clone();
+        let stats = self.stats.clone();
+        self.join_handle = Some(std::thread::spawn(move || {
+            watch_bus(p, events, sources, meter, stats)
+        }));
 
         self.pipeline.set_state(gst::State::Playing)?;
         Ok(())
     }
 
+    /// Stops the pipeline, waits for the bus thread to exit and unlinks every input and output.
+    /// A step that fails does not stop the teardown, the errors of all steps are reported once
+    /// everything has been torn down.
     pub fn stop(&mut self) -> Result<()> {
-        self.pipeline.set_state(gst::State::Null)?;
+        self.transition_cancel();
+        self.meter.close();
+        let mut errors = Vec::new();
 
-        /* TODO: Fix me
-        if self.join_handle.is_some() {
-            self.join_handle.take().unwrap().join().unwrap();
+        // The bus is flushed once the pipeline reaches Null, so the bus thread has to be told to
+        // exit before the state change.
+        if let Some(join_handle) = self.join_handle.take() {
+            let posted = match self.pipeline.bus() {
+                Some(bus) => bus
+                    .post(gst::message::Application::new(gst::Structure::new_empty(
+                        "shutdown",
+                    )))
+                    .map_err(|e| e.to_string()),
+                None => Err("failed to retrieve pipeline bus".to_string()),
+            };
+            match posted {
+                Ok(_) => {
+                    let _ = join_handle.join();
+                }
+                Err(e) => errors.push(e),
+            }
         }
-        */
 
-        Ok(())
+        if let Err(e) = self.pipeline.set_state(gst::State::Null) {
+            errors.push(e.to_string());
+        }
+
+        let inputs: Vec<String> = self.inputs.keys().cloned().collect();
+        for name in inputs {
+            if let Err(e) = self.input_remove(&name) {
+                errors.push(format!("input '{}': {}", name, e));
+            }
+        }
+
+        let outputs: Vec<String> = self.outputs.keys().cloned().collect();
+        for name in outputs {
+            if let Err(e) = self.output_remove(&name) {
+                errors.push(format!("output '{}': {}", name, e));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Teardown(errors))
+        }
     }
 
     pub fn generate_dot(&self) -> String {
@@ -237,57 +549,1006 @@ impl Mixer {
         self.config.clone()
     }
 
+    /// Returns the name of the input currently on program, if any.
+    pub fn program(&self) -> Option<String> {
+        self.active.clone()
+    }
+
+    /// Returns the name of the input currently on preview, if any.
+    pub fn preview(&self) -> Option<String> {
+        self.preview.clone()
+    }
+
+    /// Changes the resolution, framerate and format of the mixer while it is running. Inputs and
+    /// scenes keep their position and size relative to the frame, and outputs follow the change
+    /// unless they have fixed caps. The config of the mixer only changes once the pipeline has,
+    /// if any step fails the mixer goes back to its previous frame.
+    pub fn video_set(&mut self, video: &VideoConfig) -> Result<()> {
+        for (field, value) in [
+            ("width", video.width),
+            ("height", video.height),
+            ("framerate", video.framerate),
+        ] {
+            if value <= 0 {
+                return Err(Error::InvalidVideo(format!(
+                    "{} must be positive, got {}",
+                    field, value
+                )));
+            }
+        }
+
+        let old = self.config.video.clone();
+        let new = VideoConfig {
+            width: video.width,
+            height: video.height,
+            framerate: video.framerate,
+            format: video.format.clone(),
+            ..old.clone()
+        };
+
+        let (width, height) = (new.width, new.height);
+        let x = |value: i32| rescale(value, width, old.width);
+        let y = |value: i32| rescale(value, height, old.height);
+
+        // Geometries are kept as they are before anything changes, rescaling them back after a
+        // failure would round them.
+        let saved: HashMap<String, Geometry> = self
+            .inputs
+            .iter()
+            .map(|(name, input)| {
+                let video = input.config().video;
+                let geometry = Geometry {
+                    xpos: video.xpos,
+                    ypos: video.ypos,
+                    width: video.width,
+                    height: video.height,
+                };
+                (name.clone(), geometry)
+            })
+            .collect();
+        let scaled: HashMap<String, Geometry> = saved
+            .iter()
+            .map(|(name, geometry)| {
+                let geometry = Geometry {
+                    xpos: x(geometry.xpos),
+                    ypos: y(geometry.ypos),
+                    width: x(geometry.width),
+                    height: y(geometry.height),
+                };
+                (name.clone(), geometry)
+            })
+            .collect();
+
+        self.transition_cancel();
+        if let Err(e) = self.video_switch(&new, &scaled) {
+            // The background is replaced last and only once it linked, so it still has the old
+            // frame.
+            if let Err(e) = self.frame_set(&old, &saved) {
+                tracing::warn!("{}: failed to restore the frame: {}", self.config.name, e);
+            }
+            for (name, output) in self.outputs.iter_mut() {
+                if let Err(e) = output.set_video(&old) {
+                    tracing::warn!(
+                        "{}: failed to restore output {}: {}",
+                        self.config.name,
+                        name,
+                        e
+                    );
+                }
+            }
+            return Err(e);
+        }
+
+        self.config.video = new;
+        for scene in self.scenes.values_mut() {
+            for input in scene.inputs.values_mut() {
+                input.xpos = x(input.xpos);
+                input.ypos = y(input.ypos);
+                input.width = x(input.width);
+                input.height = y(input.height);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves the pipeline, outputs and background to `video`, placing the inputs at `geometry`.
+    fn video_switch(
+        &mut self,
+        video: &VideoConfig,
+        geometry: &HashMap<String, Geometry>,
+    ) -> Result<()> {
+        self.frame_set(video, geometry)?;
+        for output in self.outputs.values_mut() {
+            output.set_video(video)?;
+        }
+
+        let background = self.config.background.clone();
+        self.background_replace(&background, video)
+    }
+
+    /// Sets the frame of the compositors to `video` and places the inputs at `geometry`.
+    fn frame_set(
+        &mut self,
+        video: &VideoConfig,
+        geometry: &HashMap<String, Geometry>,
+    ) -> Result<()> {
+        let (width, height) = (video.width, video.height);
+        let caps = video_caps(video);
+
+        self.with_compositor_blocked(|mixer| {
+            mixer.video_capsfilter.set_property("caps", &caps);
+            mixer.preview_capsfilter.set_property("caps", &caps);
+
+            for (name, input) in mixer.inputs.iter_mut() {
+                if let Some(geometry) = geometry.get(name) {
+                    input.set_xpos(geometry.xpos, true)?;
+                    input.set_ypos(geometry.ypos, true)?;
+                    input.set_width(geometry.width, true)?;
+                    input.set_height(geometry.height, true)?;
+                }
+
+                // The input on program covers the frame regardless of its own geometry.
+                if mixer.active.as_deref() == Some(name.as_str()) {
+                    input.set_xpos(0, false)?;
+                    input.set_ypos(0, false)?;
+                    input.set_width(width, false)?;
+                    input.set_height(height, false)?;
+                }
+            }
+
+            mixer.preview_layout_sized(width, height)
+        })
+    }
+
+    /// Replaces the background shown beneath the inputs.
+    pub fn background_set(&mut self, background: Background) -> Result<()> {
+        let video = self.config.video.clone();
+        self.background_replace(&background, &video)?;
+        self.config.background = background;
+
+        Ok(())
+    }
+
+    /// Links a new chain for `background` in `video` format in place of the current one.
+    fn background_replace(&mut self, background: &Background, video: &VideoConfig) -> Result<()> {
+        let mut chain = background::Chain::create(
+            &self.config.name,
+            background,
+            video,
+            &self.config.audio_mix.caps(),
+        )?;
+
+        // The old background stays until the new one is linked, so a background that fails to
+        // link leaves the mixer as it was.
+        if let Err(e) = chain.link(
+            &self.pipeline,
+            &self.audio_mixer,
+            &self.video_mixer,
+            &self.preview_mixer,
+        ) {
+            if let Err(e) = chain.unlink() {
+                tracing::warn!("{}: failed to remove background: {}", self.config.name, e);
+            }
+            return Err(e);
+        }
+
+        let mut previous = std::mem::replace(&mut self.background, chain);
+        previous.unlink()
+    }
+
     pub fn input_set_active(&mut self, name: &str) -> Result<()> {
         if !self.inputs.contains_key(name) {
             return Err(Error::NotFound("input".to_string(), name.to_string()));
         }
 
+        self.transition_cancel();
+
         let input = self
             .inputs
             .get_mut(name)
             .ok_or_else(|| Error::NotFound("mixer".to_string(), name.to_string()))?;
 
-        input.set_zorder(1000, false)?;
+        input.set_alpha(input.config().video.alpha, false)?;
+        input.set_zorder(PROGRAM_ZORDER, false)?;
         input.set_xpos(0, false)?;
         input.set_ypos(0, false)?;
         input.set_width(self.config.video.width, false)?;
         input.set_height(self.config.video.height, false)?;
 
         let input_config = input.config();
-        input.set_volume(input_config.audio.volume, false)?;
 
-        // Decrease volume and restore zorder of all other inputs
+        // Restore zorder of all other inputs
         for (n, input) in self.inputs.iter_mut() {
             if n != name {
-                input.set_volume(0.0, false)?;
                 // zorder should never be empty, we default it in the config.
                 input.set_zorder(input_config.video.zorder.unwrap_or(0), false)?;
             }
         }
 
+        self.program_set(Some(name.to_string()));
+        self.audio_apply()
+    }
+
+    /// Makes `name` the active input like [`input_set_active`](#method.input_set_active), but
+    /// animates the change over the duration of the transition instead of cutting. Crossfades
+    /// fade the compositor pad `alpha`, all other transitions animate the compositor pad
+    /// geometry, which wipes crop to match. Input volumes are faded in every case. Once the transition completes the mixer is
+    /// left in the same state as `input_set_active` would leave it.
+    pub fn input_transition(&mut self, name: &str, transition: &Transition) -> Result<()> {
+        if transition.transition_type == TransitionType::Cut || transition.duration_ms == 0 {
+            return self.input_set_active(name);
+        }
+
+        let input_config = match self.inputs.get(name) {
+            Some(input) => input.config(),
+            None => return Err(Error::NotFound("input".to_string(), name.to_string())),
+        };
+
+        self.transition_cancel();
+
+        let (width, height) = (self.config.video.width, self.config.video.height);
+        let mut tracks = Vec::new();
+
+        // Fade every input to the volume it has once the incoming input is on program.
+        for (n, target) in self.audio_targets(Some(name)) {
+            if let Some(volume) = self.inputs.get(&n).and_then(|i| i.volume_element()) {
+                let from = volume.property::<f64>("volume");
+                tracks.push(transition::Track::new(&volume, "volume", from, target));
+            }
+        }
+
+        // Restore the zorder of all other inputs, so that the incoming input is layered above
+        // them for the duration of the transition.
+        for (n, input) in self.inputs.iter_mut() {
+            if n != name {
+                input.set_zorder(input_config.video.zorder.unwrap_or(0), false)?;
+            }
+        }
+
+        if let Some(outgoing) = self.active.as_ref().filter(|active| *active != name) {
+            if let Some(pad) = self.inputs.get(outgoing).and_then(|i| i.compositor_pad()) {
+                tracks.extend(transition.outgoing_tracks(&pad, width, height));
+            }
+        }
+
+        let input = self
+            .inputs
+            .get_mut(name)
+            .ok_or_else(|| Error::NotFound("input".to_string(), name.to_string()))?;
+
+        input.set_alpha(input_config.video.alpha, false)?;
+        input.set_zorder(PROGRAM_ZORDER, false)?;
+        input.set_xpos(0, false)?;
+        input.set_ypos(0, false)?;
+        input.set_width(width, false)?;
+        input.set_height(height, false)?;
+
+        if let Some(pad) = input.compositor_pad() {
+            tracks.extend(transition.incoming_tracks(
+                &pad,
+                input_config.video.alpha,
+                width,
+                height,
+            ));
+        }
+
+        let clock = self
+            .pipeline
+            .clock()
+            .unwrap_or_else(|| gst::SystemClock::obtain().upcast());
+        self.transition = Some(transition::start(
+            clock,
+            self.pipeline.base_time(),
+            gst::ClockTime::from_mseconds(transition.duration_ms),
+            self.config.video.framerate,
+            tracks,
+        ));
+        self.program_set(Some(name.to_string()));
+
         Ok(())
     }
+
+    /// Sets the audio routing of input `name` and applies it to the program audio. The audio
+    /// source of `route` has to be an input of this mixer.
+    pub fn input_set_audio_route(&mut self, name: &str, route: AudioRoute) -> Result<()> {
+        if !self.inputs.contains_key(name) {
+            return Err(Error::NotFound("input".to_string(), name.to_string()));
+        }
+        if let Some(source) = route.audio_source.as_deref() {
+            if !self.inputs.contains_key(source) {
+                return Err(Error::NotFound("input".to_string(), source.to_string()));
+            }
+        }
+
+        self.transition_cancel();
+        self.audio_routes.insert(name.to_string(), route);
+        self.audio_apply()
+    }
+
+    /// Returns the latest frame of input `name`, or of program when there is no input.
+    pub fn snapshot(&self, input: Option<&str>) -> Result<gst::Sample> {
+        let tap = match input {
+            None => Some(&self.program_tap),
+            Some(name) if self.inputs.contains_key(name) => self.input_taps.get(name),
+            Some(name) => return Err(Error::NotFound("input".to_string(), name.to_string())),
+        };
+
+        tap.and_then(|tap| tap.sample())
+            .ok_or_else(|| Error::NoFrame(input.unwrap_or("program").to_string()))
+    }
+
+    /// Returns the tally of every input: whether it is on program or preview, visible on program
+    /// and audible. Inputs without a volume element are audible at their configured volume.
+    pub fn tally(&self) -> Vec<Tally> {
+        let mut tally: Vec<Tally> = self
+            .inputs
+            .iter()
+            .map(|(name, input)| Tally {
+                input: name.clone(),
+                program: self.active.as_deref() == Some(name.as_str()),
+                preview: self.preview.as_deref() == Some(name.as_str()),
+                visible: input.compositor_pad().is_some_and(|pad| {
+                    tally::visible(&pad, self.config.video.width, self.config.video.height)
+                }),
+                audible: input
+                    .volume_element()
+                    .map(|volume| tally::audible(&volume))
+                    .unwrap_or(input.config().audio.volume > 0.0),
+            })
+            .collect();
+        tally.sort_by(|a, b| a.input.cmp(&b.input));
+        tally
+    }
+
+    /// Publishes the tally when it changed since it was last published.
+    fn tally_update(&mut self) {
+        let tally = self.tally();
+        if tally != self.tally {
+            self.tally = tally.clone();
+            self.emit(Event::TallyChanged { tally });
+        }
+    }
+
+    /// Returns the playlist input `name`, to control its playback and edit its items.
+    pub fn playlist(&mut self, name: &str) -> Result<&mut input::Playlist> {
+        match self.inputs.get_mut(name) {
+            Some(input::Input::Playlist(playlist)) => Ok(playlist),
+            Some(_) => Err(Error::NotPlaylist(name.to_string())),
+            None => Err(Error::NotFound("input".to_string(), name.to_string())),
+        }
+    }
+
+    /// Returns the audio routing of every input, and whether its audio is on air.
+    pub fn audio_routing(&self) -> Vec<AudioStatus> {
+        let mut routing: Vec<AudioStatus> = self
+            .inputs
+            .keys()
+            .map(|name| AudioStatus {
+                input: name.clone(),
+                route: self.audio_routes.get(name).cloned().unwrap_or_default(),
+                on_air: self.audio_on_air(name, self.active.as_deref()),
+            })
+            .collect();
+        routing.sort_by(|a, b| a.input.cmp(&b.input));
+        routing
+    }
+
+    /// Returns whether the audio of input `name` is on air while `program` is on program.
+    fn audio_on_air(&self, name: &str, program: Option<&str>) -> bool {
+        let mode = self
+            .audio_routes
+            .get(name)
+            .map(|route| route.mode)
+            .unwrap_or_default();
+
+        match mode {
+            AudioMode::On => true,
+            AudioMode::Off => false,
+            AudioMode::FollowVideo => {
+                // The input whose audio follows the program video.
+                let follow = program.map(|program| {
+                    self.audio_routes
+                        .get(program)
+                        .and_then(|route| route.audio_source.as_deref())
+                        .filter(|source| self.inputs.contains_key(*source))
+                        .unwrap_or(program)
+                });
+                follow == Some(name)
+            }
+        }
+    }
+
+    /// Computes the volume of every input while `program` is on program. Inputs that are on air
+    /// play at their configured volume, all others are muted.
+    fn audio_targets(&self, program: Option<&str>) -> HashMap<String, f64> {
+        self.inputs
+            .iter()
+            .map(|(name, input)| {
+                let volume = if self.audio_on_air(name, program) {
+                    input.config().audio.volume
+                } else {
+                    0.0
+                };
+                (name.clone(), volume)
+            })
+            .collect()
+    }
+
+    fn audio_apply(&mut self) -> Result<()> {
+        for (name, volume) in self.audio_targets(self.active.as_deref()) {
+            if let Some(input) = self.inputs.get_mut(&name) {
+                input.set_volume(volume, false)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Puts `name` on the preview bus. The preview input is layered above all other inputs and
+    /// scaled to the full frame, the same way [`input_set_active`](#method.input_set_active)
+    /// arranges the program bus. Program output is not affected.
+    pub fn input_set_preview(&mut self, name: &str) -> Result<()> {
+        if !self.inputs.contains_key(name) {
+            return Err(Error::NotFound("input".to_string(), name.to_string()));
+        }
+
+        self.preview_set(Some(name.to_string()));
+        self.preview_layout()
+    }
+
+    /// Swaps the preview and program inputs instantly.
+    pub fn cut(&mut self) -> Result<()> {
+        self.take(&Transition::cut())
+    }
+
+    /// Swaps the preview and program inputs, taking the preview input to program with
+    /// `transition`. The input that was on program is put on preview.
+    pub fn take(&mut self, transition: &Transition) -> Result<()> {
+        let preview = self
+            .preview
+            .clone()
+            .ok_or_else(|| Error::BusEmpty("preview".to_string()))?;
+        let program = self.active.clone();
+
+        self.input_transition(&preview, transition)?;
+
+        self.preview_set(program);
+        self.preview_layout()
+    }
+
+    /// Applies the preview selection to the compositor pads of the preview bus. Inputs that are
+    /// not on preview keep their configured geometry, so this has to be called again once the
+    /// configuration of an input changed.
+    pub fn preview_layout(&self) -> Result<()> {
+        self.preview_layout_sized(self.config.video.width, self.config.video.height)
+    }
+
+    /// Lays out the preview bus on a frame of `width` by `height`.
+    fn preview_layout_sized(&self, width: i32, height: i32) -> Result<()> {
+        for (name, input) in self.inputs.iter() {
+            let pad = match input.preview_pad() {
+                Some(pad) => pad,
+                None => continue,
+            };
+
+            let config = input.config().video;
+            pad.set_property("alpha", config.alpha);
+            if self.preview.as_deref() == Some(name.as_str()) {
+                pad.set_property("zorder", PROGRAM_ZORDER);
+                pad.set_property("xpos", 0);
+                pad.set_property("ypos", 0);
+                pad.set_property("width", width);
+                pad.set_property("height", height);
+            } else {
+                if let Some(zorder) = config.zorder {
+                    pad.set_property("zorder", zorder);
+                }
+                pad.set_property("xpos", config.xpos);
+                pad.set_property("ypos", config.ypos);
+                pad.set_property("width", config.width);
+                pad.set_property("height", config.height);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Captures the current compositor and volume properties of every input as a scene named
+    /// `name`. The scene is not stored on the mixer.
+    pub fn scene_capture(&self, name: &str) -> Scene {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|(n, input)| {
+                let config = input.config();
+                let volume = input
+                    .volume_element()
+                    .map(|v| v.property::<f64>("volume"))
+                    .unwrap_or(config.audio.volume);
+
+                // Prefer the properties of the compositor pad, they reflect changes made by
+                // setting an input active which are not stored to the input config.
+                let properties = match input.compositor_pad() {
+                    Some(pad) => SceneInput {
+                        zorder: pad.property("zorder"),
+                        xpos: pad.property("xpos"),
+                        ypos: pad.property("ypos"),
+                        width: pad.property("width"),
+                        height: pad.property("height"),
+                        alpha: pad.property("alpha"),
+                        volume,
+                    },
+                    None => SceneInput {
+                        zorder: config.video.zorder.unwrap_or(0),
+                        xpos: config.video.xpos,
+                        ypos: config.video.ypos,
+                        width: config.video.width,
+                        height: config.video.height,
+                        alpha: config.video.alpha,
+                        volume,
+                    },
+                };
+
+                (n.clone(), properties)
+            })
+            .collect();
+
+        Scene {
+            name: name.to_string(),
+            inputs,
+        }
+    }
+
+    pub fn scene_add(&mut self, scene: Scene) -> Result<()> {
+        if self.scenes.contains_key(&scene.name) {
+            return Err(Error::Exists("scene".to_string(), scene.name));
+        }
+
+        self.scenes.insert(scene.name.clone(), scene);
+        Ok(())
+    }
+
+    pub fn scene_update(&mut self, scene: Scene) -> Result<()> {
+        if !self.scenes.contains_key(&scene.name) {
+            return Err(Error::NotFound("scene".to_string(), scene.name));
+        }
+
+        self.scenes.insert(scene.name.clone(), scene);
+        Ok(())
+    }
+
+    pub fn scene_remove(&mut self, name: &str) -> Result<()> {
+        self.scenes
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| Error::NotFound("scene".to_string(), name.to_string()))
+    }
+
+    /// Applies the scene `name` to the inputs of the mixer. All compositor properties take
+    /// effect on the same output frame. Inputs in the scene that no longer exist on the mixer are
+    /// skipped. The configuration of the inputs is left as it is, the scene only changes what is
+    /// on air.
+    pub fn scene_apply(&mut self, name: &str) -> Result<()> {
+        let scene = self
+            .scenes
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::NotFound("scene".to_string(), name.to_string()))?;
+
+        self.transition_cancel();
+        self.with_compositor_blocked(|mixer| {
+            // The scene sets the volume of inputs that are on air, the audio routes keep the rest
+            // muted.
+            let program = mixer.active.clone();
+            let on_air: Vec<String> = scene
+                .inputs
+                .keys()
+                .filter(|name| mixer.audio_on_air(name, program.as_deref()))
+                .cloned()
+                .collect();
+
+            for (input_name, properties) in scene.inputs.iter() {
+                let input = match mixer.inputs.get_mut(input_name) {
+                    Some(input) => input,
+                    None => continue,
+                };
+
+                input.set_zorder(properties.zorder, false)?;
+                input.set_xpos(properties.xpos, false)?;
+                input.set_ypos(properties.ypos, false)?;
+                input.set_width(properties.width, false)?;
+                input.set_height(properties.height, false)?;
+                input.set_alpha(properties.alpha, false)?;
+                if on_air.contains(input_name) {
+                    input.set_volume(properties.volume, false)?;
+                } else {
+                    input.set_volume(0.0, false)?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Arranges `inputs` according to `layout`, in order of importance. Every input is moved on
+    /// the same frame, and stacked above the inputs before it and above the input on program.
+    /// Inputs that are not listed are left where they are, an input on program that is not listed
+    /// stays beneath the layout.
+    pub fn layout_apply(&mut self, layout: &Layout, inputs: &[String]) -> Result<()> {
+        layout.validate()?;
+        if inputs.len() > layout.capacity() {
+            return Err(Error::LayoutCapacity(
+                format!("{:?}", layout.layout_type),
+                layout.capacity(),
+            ));
+        }
+
+        if let Some(name) = inputs.iter().find(|name| !self.inputs.contains_key(*name)) {
+            return Err(Error::NotFound("input".to_string(), name.to_string()));
+        }
+
+        let geometry = layout.geometry(
+            inputs.len(),
+            self.config.video.width,
+            self.config.video.height,
+        );
+
+        self.transition_cancel();
+        self.with_compositor_blocked(|mixer| {
+            for (i, (name, geometry)) in inputs.iter().zip(geometry).enumerate() {
+                let input = mixer.inputs.get_mut(name).unwrap();
+                // The stacking only holds while the layout is on air, the next input put on
+                // program covers it again.
+                input.set_zorder(PROGRAM_ZORDER + 1 + i as u32, false)?;
+                input.set_xpos(geometry.xpos, true)?;
+                input.set_ypos(geometry.ypos, true)?;
+                input.set_width(geometry.width, true)?;
+                input.set_height(geometry.height, true)?;
+            }
+
+            mixer.preview_layout()
+        })
+    }
+
+    /// Runs `f` while the streaming thread of the compositor is blocked between two output
+    /// frames, so every pad property changed by `f` takes effect on the same frame.
+    fn with_compositor_blocked<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Self) -> Result<()>,
+    {
+        let pad = self
+            .video_mixer
+            .static_pad("src")
+            .ok_or_else(|| Error::Gstreamer("failed to retrieve compositor src pad".to_string()))?;
+
+        if self.pipeline.current_state() != gst::State::Playing {
+            return f(self);
+        }
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let probe = pad.add_probe(
+            gst::PadProbeType::BLOCK | gst::PadProbeType::BUFFER,
+            move |_, _| {
+                let _ = sender.send(());
+                gst::PadProbeReturn::Ok
+            },
+        );
+
+        // If no frame arrives the compositor is not producing output, and there is nothing to
+        // tear.
+        block_in_place(|| {
+            let _ = receiver.recv_timeout(std::time::Duration::from_secs(1));
+        });
+        let result = f(self);
+
+        if let Some(probe) = probe {
+            pad.remove_probe(probe);
+        }
+
+        result
+    }
+
+    /// Periodic housekeeping, must be called regularly. Records the errors and end of stream
+    /// reported for inputs in their [`health`](#method.input_health), and fails inputs on program
+    /// over to their fallback input while they are not live. See
+    /// [`input::FailoverConfig`](../input/struct.FailoverConfig.html).
+    pub fn tick(&mut self) -> Result<()> {
+        loop {
+            let (input, state) = match self.tick_events.try_recv() {
+                Ok(Event::Error {
+                    source: event::Source::Input(input),
+                    ..
+                }) => (input, HealthState::Error),
+                Ok(Event::InputEos { input }) => (input, HealthState::Eos),
+                Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            };
+
+            if let Some(monitor) = self.health.get(&input) {
+                monitor.fail(state);
+            }
+        }
+
+        self.failover(Instant::now());
+        // Catches changes the mixer does not make itself, such as transitions and input updates.
+        self.tally_update();
+        Ok(())
+    }
+
+    /// Fails inputs over and back. An input that cannot be switched is logged and left for the
+    /// next tick, without holding up the others.
+    fn failover(&mut self, now: Instant) {
+        let configs: Vec<(String, input::FailoverConfig)> = self
+            .inputs
+            .iter()
+            .filter_map(|(name, input)| input.config().failover.map(|f| (name.clone(), f)))
+            .collect();
+
+        for (name, config) in configs {
+            let monitor = match self.health.get(&name) {
+                Some(monitor) => monitor,
+                None => continue,
+            };
+            let state = monitor.health(now).state;
+
+            let active = match self.failovers.get_mut(&name) {
+                Some(active) => active,
+                None => {
+                    if self.active.as_deref() != Some(name.as_str()) {
+                        continue;
+                    }
+
+                    let reason = match state {
+                        HealthState::Live => continue,
+                        HealthState::Stalled => event::FailoverReason::Stalled,
+                        HealthState::Eos => event::FailoverReason::Eos,
+                        HealthState::Error => event::FailoverReason::Error,
+                    };
+
+                    if let Err(e) = self.input_set_active(&config.input) {
+                        tracing::warn!(
+                            "{}: failed to fail {} over to {}: {}",
+                            self.config.name,
+                            name,
+                            config.input,
+                            e
+                        );
+                        continue;
+                    }
+                    self.failovers.insert(
+                        name.clone(),
+                        failover::Active {
+                            fallback: config.input.clone(),
+                            since: now,
+                            recovered: false,
+                        },
+                    );
+                    self.emit(Event::FailoverActivated {
+                        input: name,
+                        fallback: config.input,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+
+            // Failing again restarts the recovery.
+            if state != HealthState::Live {
+                active.since = now;
+                active.recovered = false;
+                continue;
+            }
+
+            if active.recovered || !monitor.recovered(now, active.since) {
+                continue;
+            }
+
+            let fallback = active.fallback.clone();
+            match config.recovery {
+                input::Recovery::Automatic => {
+                    // Stay where we are if an operator has moved on from the fallback.
+                    if self.active.as_deref() != Some(fallback.as_str()) {
+                        self.failovers.remove(&name);
+                        continue;
+                    }
+                    // Keeps the failover to try again on the next tick.
+                    if let Err(e) = self.input_set_active(&name) {
+                        tracing::warn!(
+                            "{}: failed to return {} from {}: {}",
+                            self.config.name,
+                            name,
+                            fallback,
+                            e
+                        );
+                        continue;
+                    }
+                    self.failovers.remove(&name);
+                    self.emit(Event::FailoverReturned {
+                        input: name,
+                        fallback,
+                    });
+                }
+                input::Recovery::Manual => {
+                    active.recovered = true;
+                    self.emit(Event::FailoverRecovered {
+                        input: name,
+                        fallback,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Returns the health of input `name`, if it is linked to the compositor.
+    pub fn input_health(&self, name: &str) -> Option<Health> {
+        self.health
+            .get(name)
+            .map(|monitor| monitor.health(Instant::now()))
+    }
+
+    /// Puts an input that failed over back on program, whether or not it has recovered.
+    pub fn failover_return(&mut self, name: &str) -> Result<()> {
+        let active = self
+            .failovers
+            .remove(name)
+            .ok_or_else(|| Error::NotFound("failover".to_string(), name.to_string()))?;
+
+        self.input_set_active(name)?;
+        self.emit(Event::FailoverReturned {
+            input: name.to_string(),
+            fallback: active.fallback,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the performance statistics of the pipeline of this mixer.
+    pub fn stats(&self) -> Stats {
+        self.stats
+            .snapshot(&self.pipeline, &self.sources.lock().unwrap())
+    }
+
+    /// Returns the audio level meter of this mixer and its inputs.
+    pub fn meter(&self) -> level::Meter {
+        self.meter.clone()
+    }
+
+    /// Subscribes to the events published by this mixer. Events are only delivered to
+    /// subscribers that exist at the time they are published.
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    pub(crate) fn emit(&self, event: Event) {
+        // Sending only fails when nobody is subscribed.
+        let _ = self.events.send(event);
+    }
+
+    fn program_set(&mut self, input: Option<String>) {
+        if self.active != input {
+            self.active = input.clone();
+            self.multiview_tally();
+            self.emit(Event::ProgramChanged { input });
+            self.tally_update();
+        }
+    }
+
+    fn preview_set(&mut self, input: Option<String>) {
+        if self.preview != input {
+            self.preview = input.clone();
+            self.multiview_tally();
+            self.emit(Event::PreviewChanged { input });
+            self.tally_update();
+        }
+    }
+
+    /// Frames the inputs on program and preview in the tally colours of every multiview output.
+    fn multiview_tally(&self) {
+        for output in self.outputs.values() {
+            if let output::Output::Multiview(multiview) = output {
+                multiview.set_tally(self.active.as_deref(), self.preview.as_deref());
+            }
+        }
+    }
+
+    fn transition_cancel(&mut self) {
+        if let Some(transition) = self.transition.take() {
+            transition.cancel();
+        }
+    }
 }
 
-fn watch_bus(pipeline: gst::Pipeline) {
-    // Wait until error or EOS
+/// Runs `f`, which blocks the calling thread, without stalling the other tasks of the async
+/// runtime it is called from. Only multi-threaded runtimes can hand their other tasks to another
+/// worker, on any other thread `f` just runs.
+fn block_in_place<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    match runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+/// Raw video caps the compositors of a mixer produce.
+fn video_caps(video: &VideoConfig) -> gst::Caps {
+    gst::Caps::builder("video/x-raw")
+        .field("framerate", gst::Fraction::new(video.framerate, 1))
+        .field("format", video.format.to_string())
+        .field("width", video.width)
+        .field("height", video.height)
+        .build()
+}
+
+/// Scales `value` by `new / old`, for positions and sizes on a frame that changed size.
+fn rescale(value: i32, new: i32, old: i32) -> i32 {
+    if old == 0 {
+        return value;
+    }
+    (value as i64 * new as i64 / old as i64) as i32
+}
+
+fn watch_bus(
+    pipeline: gst::Pipeline,
+    events: broadcast::Sender<Event>,
+    sources: Arc<Mutex<event::Sources>>,
+    meter: level::Meter,
+    stats: stats::Recorder,
+) {
+    // Runs until the mixer is stopped, errors are reported and do not end the loop.
     let bus = pipeline.bus().unwrap();
     for msg in bus.iter_timed(None) { //ffi::GST_CLOCK_TIME_NONE) {
         use gst::MessageView;
         match msg.view() {
             MessageView::Error(err) => {
-                eprintln!(
-                    "{}: Error received from element {:?} {}",
+                tracing::error!(
+                    element = ?err.src().map(|s| s.path_string()),
+                    debug = ?err.debug(),
+                    "{}: error received from element: {}",
                     pipeline.name(),
-                    err.src().map(|s| s.path_string()),
                     err.error()
                 );
-                eprintln!(
-                    "{}: Debugging information: {:?}",
-                    pipeline.name(),
-                    err.debug()
-                );
-                break;
+
+                let _ = events.send(Event::Error {
+                    source: err
+                        .src()
+                        .map(|s| sources.lock().unwrap().find(s))
+                        .unwrap_or(event::Source::Mixer),
+                    element: err.src().map(|s| s.name().to_string()),
+                    message: err.error().to_string(),
+                    debug: err.debug().map(|d| d.to_string()),
+                });
+            }
+            MessageView::Element(element) => {
+                if let (Some(src), Some(structure)) = (element.src(), element.structure()) {
+                    if structure.has_name("level") {
+                        let source = sources.lock().unwrap().find(src);
+                        meter.record(src, source, structure);
+                    }
+                }
+            }
+            MessageView::Qos(qos) => {
+                let (jitter, proportion, _) = qos.values();
+                let (_, dropped) = qos.stats();
+                let source = qos
+                    .src()
+                    .map(|s| sources.lock().unwrap().find(s))
+                    .unwrap_or(event::Source::Mixer);
+                if let Some(src) = qos.src() {
+                    stats.qos(source.clone(), src.name().as_str(), qos);
+                }
+
+                let _ = events.send(Event::Qos {
+                    source,
+                    element: qos.src().map(|s| s.name().to_string()),
+                    jitter,
+                    proportion,
+                    dropped: dropped.value(),
+                });
             }
             MessageView::StateChanged(state_changed) => {
                 if state_changed
@@ -295,20 +1556,32 @@ fn watch_bus(pipeline: gst::Pipeline) {
                     .map(|s| *s == pipeline)
                     .unwrap_or(false)
                 {
-                    println!(
-                        "{}: Pipeline state changed from {:?} to {:?}",
+                    tracing::info!(
+                        "{}: pipeline state changed from {:?} to {:?}",
                         pipeline.name(),
                         state_changed.old(),
                         state_changed.current()
                     );
 
+                    let _ = events.send(Event::StateChanged {
+                        old: format!("{:?}", state_changed.old()),
+                        current: format!("{:?}", state_changed.current()),
+                    });
+
                     match state_changed.current() {
                         gst::State::Null => break,
                         _ => continue,
                     }
                 }
             }
-            MessageView::Eos(..) => break,
+            MessageView::Application(application)
+                if application
+                    .structure()
+                    .map(|s| s.has_name("shutdown"))
+                    .unwrap_or(false) =>
+            {
+                break
+            }
             _ => (),
         }
     }
@@ -0,0 +1,316 @@
+use super::Error;
+use crate::{gst_create_element, Result, VideoConfig};
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Numbers the chains, so that a new background can be linked while the one it replaces is still
+/// in the pipeline.
+static CHAIN_ID: AtomicU32 = AtomicU32::new(0);
+
+/// What the compositor shows where no input covers the frame.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "background_type")]
+pub enum Background {
+    /// A solid color.
+    Color {
+        red: u8,
+        green: u8,
+        blue: u8,
+        #[serde(default = "Background::alpha_default")]
+        alpha: u8,
+    },
+    /// A `videotestsrc` pattern.
+    Pattern { pattern: Pattern },
+    /// A still image file, such as a branded slate.
+    Image { location: String },
+    /// Nothing, the compositor fills the frame with black.
+    None,
+}
+
+impl Background {
+    fn alpha_default() -> u8 {
+        255
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Pattern {
+            pattern: Pattern::Black,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum Pattern {
+    Smpte,
+    Snow,
+    Black,
+    White,
+    Red,
+    Green,
+    Blue,
+    Checkers1,
+    Checkers2,
+    Checkers4,
+    Checkers8,
+    Circular,
+    Blink,
+    Smpte75,
+    ZonePlate,
+    Gamut,
+    ChromaZonePlate,
+    Ball,
+    Smpte100,
+    Bar,
+    Pinwheel,
+    Spokes,
+    Gradient,
+    Colors,
+    SmpteRp219,
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Pattern::*;
+
+        let s = match self {
+            Smpte => "smpte",
+            Snow => "snow",
+            Black => "black",
+            White => "white",
+            Red => "red",
+            Green => "green",
+            Blue => "blue",
+            Checkers1 => "checkers-1",
+            Checkers2 => "checkers-2",
+            Checkers4 => "checkers-4",
+            Checkers8 => "checkers-8",
+            Circular => "circular",
+            Blink => "blink",
+            Smpte75 => "smpte75",
+            ZonePlate => "zone-plate",
+            Gamut => "gamut",
+            ChromaZonePlate => "chroma-zone-plate",
+            Ball => "ball",
+            Smpte100 => "smpte100",
+            Bar => "bar",
+            Pinwheel => "pinwheel",
+            Spokes => "spokes",
+            Gradient => "gradient",
+            Colors => "colors",
+            SmpteRp219 => "smpte-rp-219",
+        };
+
+        f.write_str(s)
+    }
+}
+
+/// The elements producing the background of a mixer. Besides the video, the background feeds
+/// silence to the audio mixer so the mix keeps running while no input is connected.
+pub(crate) struct Chain {
+    pipeline: Option<gst::Pipeline>,
+    /// Elements linked in order, ending with the queue feeding the audio mixer.
+    audio: Vec<gst::Element>,
+    /// Runs of elements linked in order, ending with the tee feeding the compositors. Elements
+    /// with dynamic pads end a run and are linked to the next one once their pad appears.
+    video: Vec<Vec<gst::Element>>,
+    /// The queues linked to the program and preview compositors, absent without a video.
+    video_queues: Option<(gst::Element, gst::Element)>,
+}
+
+impl Chain {
+    pub(crate) fn create(
+        mixer: &str,
+        background: &Background,
+        video: &VideoConfig,
+        audio_caps: &gst::Caps,
+    ) -> Result<Self> {
+        let id = CHAIN_ID.fetch_add(1, Ordering::Relaxed);
+        let name = |element: &str| format!("mixer_{}_background_{}_{}", mixer, id, element);
+
+        let audio = gst_create_element("audiotestsrc", &name("audiotestsrc"))?;
+        audio.set_property_from_str("wave", "silence");
+        audio.set_property("is-live", true);
+        let audio_convert = gst_create_element("audioconvert", &name("audio_convert"))?;
+        let audio_resample = gst_create_element("audioresample", &name("audio_resample"))?;
+        let audio_capsfilter = gst_create_element("capsfilter", &name("audio_capsfilter"))?;
+        audio_capsfilter.set_property("caps", audio_caps);
+        let audio_queue = gst_create_element("queue", &name("audio_queue"))?;
+
+        let mut chain = Self {
+            pipeline: None,
+            audio: vec![
+                audio,
+                audio_convert,
+                audio_resample,
+                audio_capsfilter,
+                audio_queue,
+            ],
+            video: vec![],
+            video_queues: None,
+        };
+
+        let source = match background {
+            Background::Color {
+                red,
+                green,
+                blue,
+                alpha,
+            } => {
+                let source = gst_create_element("videotestsrc", &name("videotestsrc"))?;
+                source.set_property_from_str("pattern", "solid-color");
+                source.set_property(
+                    "foreground-color",
+                    u32::from_be_bytes([*alpha, *red, *green, *blue]),
+                );
+                source.set_property("is-live", true);
+                source
+            }
+            Background::Pattern { pattern } => {
+                let source = gst_create_element("videotestsrc", &name("videotestsrc"))?;
+                source.set_property_from_str("pattern", &pattern.to_string());
+                source.set_property("is-live", true);
+                source
+            }
+            Background::Image { location } => {
+                if !std::path::Path::new(location).is_file() {
+                    return Err(Error::NotFound("image".to_string(), location.clone()));
+                }
+
+                let file = gst_create_element("filesrc", &name("filesrc"))?;
+                file.set_property("location", location);
+                let decode = gst_create_element("decodebin", &name("decodebin"))?;
+                let freeze = gst_create_element("imagefreeze", &name("imagefreeze"))?;
+                freeze.set_property("is-live", true);
+
+                let sink = freeze.clone();
+                decode.connect_pad_added(move |_, src_pad| {
+                    let sink_pad = match sink.static_pad("sink") {
+                        Some(pad) if !pad.is_linked() => pad,
+                        _ => return,
+                    };
+                    if let Err(e) = src_pad.link(&sink_pad) {
+                        tracing::warn!("Failed to link background image: {}", e);
+                    }
+                });
+
+                chain.video.push(vec![file, decode]);
+                freeze
+            }
+            Background::None => return Ok(chain),
+        };
+
+        let video_convert = gst_create_element("videoconvert", &name("video_convert"))?;
+        let video_scale = gst_create_element("videoscale", &name("video_scale"))?;
+        let video_rate = gst_create_element("videorate", &name("video_rate"))?;
+        let video_capsfilter = gst_create_element("capsfilter", &name("video_capsfilter"))?;
+        let video_caps = gst::Caps::builder("video/x-raw")
+            .field("framerate", gst::Fraction::new(video.framerate, 1))
+            .field("format", video.format.to_string())
+            .field("width", video.width)
+            .field("height", video.height)
+            .build();
+        video_capsfilter.set_property("caps", &video_caps);
+        let video_tee = gst_create_element("tee", &name("video_tee"))?;
+        video_tee.set_property("allow-not-linked", true);
+
+        chain.video.push(vec![
+            source,
+            video_convert,
+            video_scale,
+            video_rate,
+            video_capsfilter,
+            video_tee,
+        ]);
+        chain.video_queues = Some((
+            gst_create_element("queue", &name("video_queue"))?,
+            gst_create_element("queue", &name("preview_queue"))?,
+        ));
+
+        Ok(chain)
+    }
+
+    fn elements(&self) -> Vec<&gst::Element> {
+        let mut elements: Vec<&gst::Element> = self
+            .audio
+            .iter()
+            .chain(self.video.iter().flatten())
+            .collect();
+        if let Some((video_queue, preview_queue)) = self.video_queues.as_ref() {
+            elements.push(video_queue);
+            elements.push(preview_queue);
+        }
+        elements
+    }
+
+    /// Adds the background to `pipeline` and links it to the mixers, beneath every input. On
+    /// failure, [`unlink`](#method.unlink) removes what was added.
+    pub(crate) fn link(
+        &mut self,
+        pipeline: &gst::Pipeline,
+        audio: &gst::Element,
+        video: &gst::Element,
+        preview: &gst::Element,
+    ) -> Result<()> {
+        self.pipeline = Some(pipeline.clone());
+        pipeline.add_many(self.elements())?;
+
+        gst::Element::link_many(self.audio.iter().chain([audio]))?;
+        for run in self.video.iter() {
+            gst::Element::link_many(run)?;
+        }
+
+        if let (Some((video_queue, preview_queue)), Some(tee)) = (
+            self.video_queues.as_ref(),
+            self.video.last().and_then(|run| run.last()),
+        ) {
+            gst::Element::link_many([tee, video_queue, video])?;
+            gst::Element::link_many([tee, preview_queue, preview])?;
+
+            for queue in [video_queue, preview_queue] {
+                if let Some(pad) = queue.static_pad("src").and_then(|pad| pad.peer()) {
+                    pad.set_property("zorder", 0u32);
+                }
+            }
+        }
+
+        for element in self.elements() {
+            element.sync_state_with_parent()?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the background from the pipeline it was linked into.
+    pub(crate) fn unlink(&mut self) -> Result<()> {
+        let pipeline = match self.pipeline.take() {
+            Some(pipeline) => pipeline,
+            None => return Ok(()),
+        };
+
+        let mut queues = self.audio.last().into_iter().collect::<Vec<_>>();
+        if let Some((video_queue, preview_queue)) = self.video_queues.as_ref() {
+            queues.push(video_queue);
+            queues.push(preview_queue);
+        }
+        for queue in queues {
+            if let Some(peer) = queue.static_pad("src").and_then(|pad| pad.peer()) {
+                if let Some(parent) = peer.parent_element() {
+                    parent.release_request_pad(&peer);
+                }
+            }
+        }
+
+        for element in self.elements() {
+            element.set_state(gst::State::Null)?;
+            if element.parent().as_ref() == Some(pipeline.upcast_ref()) {
+                pipeline.remove(element)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -12,6 +12,33 @@ pub enum Error {
     #[error("a {0} with the name '{1}' was not found")]
     NotFound(String, String),
 
+    #[error("the {0} layout has room for at most {1} inputs")]
+    LayoutCapacity(String, usize),
+
+    #[error("invalid layout: {0}")]
+    InvalidLayout(String),
+
+    #[error("invalid video format: {0}")]
+    InvalidVideo(String),
+
+    #[error("no input is on {0}")]
+    BusEmpty(String),
+
+    #[error("input '{0}' is not a playlist")]
+    NotPlaylist(String),
+
+    #[error("no frame of {0} has arrived yet")]
+    NoFrame(String),
+
+    #[error("an offset of {0} ms is out of range, offsets go from 0 to {1} ms")]
+    InvalidOffset(i64, i64),
+
+    #[error("'{1}' is not a valid location for an {0} input")]
+    InvalidLocation(String, String),
+
+    #[error("failed to tear down: {}", .0.join("; "))]
+    Teardown(Vec<String>),
+
     #[error("An error was returned from gstreamer: '{0}'")]
     GstBool(#[from] gst::glib::BoolError),
 
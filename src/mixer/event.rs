@@ -0,0 +1,113 @@
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Number of events buffered for each subscriber before the oldest are dropped.
+pub(crate) const CHANNEL_CAPACITY: usize = 256;
+
+/// The part of a mixer an [`Event`](enum.Event.html) originated from.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub enum Source {
+    Mixer,
+    Input(String),
+    Output(String),
+}
+
+/// Why an input was failed over to its fallback.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum FailoverReason {
+    Error,
+    Eos,
+    Stalled,
+}
+
+/// Events published by a mixer to its subscribers.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "event_type")]
+pub enum Event {
+    /// The pipeline of the mixer changed state.
+    StateChanged { old: String, current: String },
+    /// An element of the pipeline reported an error. The mixer keeps running.
+    Error {
+        source: Source,
+        element: Option<String>,
+        message: String,
+        debug: Option<String>,
+    },
+    /// An input reached the end of its stream.
+    InputEos { input: String },
+    /// An element dropped or delayed buffers to keep up.
+    Qos {
+        source: Source,
+        element: Option<String>,
+        jitter: i64,
+        proportion: f64,
+        dropped: i64,
+    },
+    InputAdded { input: String },
+    InputRemoved { input: String },
+    /// The input on program changed.
+    ProgramChanged { input: Option<String> },
+    /// The input on preview changed.
+    PreviewChanged { input: Option<String> },
+    /// An input on program failed and was replaced by its fallback input.
+    FailoverActivated {
+        input: String,
+        fallback: String,
+        reason: FailoverReason,
+    },
+    /// An input that failed over has recovered and is waiting for an operator to switch back.
+    FailoverRecovered { input: String, fallback: String },
+    /// An input that failed over was put back on program.
+    FailoverReturned { input: String, fallback: String },
+    /// The tally of one or more inputs changed, carries the tally of every input.
+    TallyChanged {
+        tally: Vec<super::Tally>,
+    },
+}
+
+/// Names of the inputs and outputs linked to a mixer, used to attribute bus messages to them.
+/// Elements are named `input_{name}_*` and `output_{name}_*` by convention.
+#[derive(Debug, Default)]
+pub(crate) struct Sources {
+    pub(crate) inputs: HashSet<String>,
+    pub(crate) outputs: HashSet<String>,
+}
+
+impl Sources {
+    /// Finds the input or output `object` belongs to, by checking the names of the object and its
+    /// parents. When names overlap the longest match wins, so an input named `cam_1` is never
+    /// mistaken for an input named `cam`.
+    pub(crate) fn find(&self, object: &gst::Object) -> Source {
+        let mut current = Some(object.clone());
+        while let Some(object) = current {
+            if let Some(source) = self.find_by_name(object.name().as_str()) {
+                return source;
+            }
+            current = object.parent();
+        }
+
+        Source::Mixer
+    }
+
+    fn find_by_name(&self, element: &str) -> Option<Source> {
+        let input = element.strip_prefix("input_").and_then(|rest| {
+            self.inputs
+                .iter()
+                .filter(|name| rest.starts_with(&format!("{}_", name)))
+                .max_by_key(|name| name.len())
+        });
+        if let Some(input) = input {
+            return Some(Source::Input(input.clone()));
+        }
+
+        let output = element.strip_prefix("output_").and_then(|rest| {
+            self.outputs
+                .iter()
+                .filter(|name| rest.starts_with(&format!("{}_", name)))
+                .max_by_key(|name| name.len())
+        });
+        output.map(|output| Source::Output(output.clone()))
+    }
+}
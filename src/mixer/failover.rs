@@ -0,0 +1,10 @@
+use std::time::Instant;
+
+/// An input that has been replaced on program by its fallback.
+#[derive(Debug, Clone)]
+pub(crate) struct Active {
+    pub(crate) fallback: String,
+    pub(crate) since: Instant,
+    /// Set once the input has recovered, while waiting for an operator to switch back.
+    pub(crate) recovered: bool,
+}
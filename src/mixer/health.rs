@@ -0,0 +1,176 @@
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Stall timeout of inputs that do not configure one.
+pub(crate) const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Frames are counted over windows of this length to measure the frame rate.
+const FPS_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum HealthState {
+    Live,
+    Stalled,
+    Eos,
+    Error,
+}
+
+/// Health of an input as measured where its video and audio leave it for the mixers.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Health {
+    pub state: HealthState,
+    /// When the last frame arrived, in milliseconds since the unix epoch.
+    pub last_frame_time: Option<u64>,
+    /// Frames per second received over the last second, 0 while the input is not live.
+    pub fps: f64,
+}
+
+/// Tracks the frames and audio buffers an input delivers to the mixers.
+pub(crate) struct Monitor {
+    frames: Arc<Mutex<Frames>>,
+}
+
+struct Frames {
+    /// When tracking started, stands in for the last frame until one arrives.
+    started: Instant,
+    last: Option<(Instant, SystemTime)>,
+    /// When frames started arriving without stalling in between.
+    streak: Option<Instant>,
+    stall_timeout: Duration,
+    window_start: Instant,
+    window_frames: u32,
+    fps: f64,
+    /// Set by an EOS or error reported for the input, cleared by the next frame.
+    failed: Option<HealthState>,
+    /// When the last audio buffer arrived, `None` until the first one does.
+    audio_last: Option<Instant>,
+}
+
+impl Frames {
+    fn last_instant(&self) -> Instant {
+        self.last.map(|(instant, _)| instant).unwrap_or(self.started)
+    }
+
+    /// Whether the video, or the audio once it started, has stopped for the stall timeout.
+    fn stalled(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_instant()) >= self.stall_timeout
+            || self
+                .audio_last
+                .map(|last| now.saturating_duration_since(last) >= self.stall_timeout)
+                .unwrap_or(false)
+    }
+}
+
+impl Monitor {
+    /// Starts tracking the buffers passing through the `video` and `audio` pads. The input is
+    /// stalled once no frame has arrived for `stall_timeout`, or no audio for as long after audio
+    /// started. Audio is only watched once it started, so media without sound do not stall.
+    pub(crate) fn watch(
+        video: &gst::Pad,
+        audio: Option<&gst::Pad>,
+        stall_timeout: Duration,
+    ) -> Self {
+        let now = Instant::now();
+        let monitor = Self {
+            frames: Arc::new(Mutex::new(Frames {
+                started: now,
+                last: None,
+                streak: None,
+                stall_timeout,
+                window_start: now,
+                window_frames: 0,
+                fps: 0.0,
+                failed: None,
+                audio_last: None,
+            })),
+        };
+
+        let frames = monitor.frames.clone();
+        video.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+            let now = Instant::now();
+            let mut frames = frames.lock().unwrap();
+
+            if frames.streak.is_none() || frames.stalled(now) {
+                frames.streak = Some(now);
+            }
+            frames.last = Some((now, SystemTime::now()));
+            frames.failed = None;
+
+            frames.window_frames += 1;
+            let elapsed = now.duration_since(frames.window_start);
+            if elapsed >= FPS_WINDOW {
+                frames.fps = frames.window_frames as f64 / elapsed.as_secs_f64();
+                frames.window_start = now;
+                frames.window_frames = 0;
+            }
+
+            gst::PadProbeReturn::Ok
+        });
+
+        if let Some(audio) = audio {
+            let frames = monitor.frames.clone();
+            audio.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+                let now = Instant::now();
+                let mut frames = frames.lock().unwrap();
+
+                // Audio coming back restarts the recovery, the same way video does.
+                if frames.streak.is_some() && frames.stalled(now) {
+                    frames.streak = Some(now);
+                }
+                frames.audio_last = Some(now);
+
+                gst::PadProbeReturn::Ok
+            });
+        }
+
+        monitor
+    }
+
+    /// Records that the input reached the end of its stream or reported an error.
+    pub(crate) fn fail(&self, state: HealthState) {
+        self.frames.lock().unwrap().failed = Some(state);
+    }
+
+    pub(crate) fn health(&self, now: Instant) -> Health {
+        let frames = self.frames.lock().unwrap();
+
+        let state = match frames.failed {
+            Some(state) => state,
+            None if frames.stalled(now) => HealthState::Stalled,
+            None => HealthState::Live,
+        };
+
+        Health {
+            state,
+            last_frame_time: frames.last.map(|(_, time)| {
+                time.duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0)
+            }),
+            fps: if state == HealthState::Live {
+                frames.fps
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Returns whether the input has been live without interruption since `after` for at least
+    /// its stall timeout.
+    pub(crate) fn recovered(&self, now: Instant, after: Instant) -> bool {
+        if self.health(now).state != HealthState::Live {
+            return false;
+        }
+
+        let frames = self.frames.lock().unwrap();
+        match frames.streak {
+            Some(streak) => now.saturating_duration_since(streak.max(after)) >= frames.stall_timeout,
+            None => false,
+        }
+    }
+}
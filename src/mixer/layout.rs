@@ -0,0 +1,180 @@
+use super::Error;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum LayoutType {
+    PictureInPicture,
+    SideBySide,
+    Grid2x2,
+    Grid3x3,
+    SpeakerFilmstrip,
+}
+
+/// The corner of the frame the inset of a `PictureInPicture` layout is placed in.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, Default)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Layout {
+    pub layout_type: LayoutType,
+    #[serde(default)]
+    pub corner: Corner,
+    /// Distance in pixels between the inset and the edges of the frame, at least 0.
+    #[serde(default = "Layout::margin_default")]
+    pub margin: i32,
+    /// Size of the inset relative to the frame, greater than 0 and at most 1.
+    #[serde(default = "Layout::scale_default")]
+    pub scale: f64,
+}
+
+/// Position and size of a single input on the compositor.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub struct Geometry {
+    pub xpos: i32,
+    pub ypos: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Number of inputs shown in the filmstrip of a `SpeakerFilmstrip` layout.
+const FILMSTRIP_SIZE: i32 = 5;
+
+impl Layout {
+    fn margin_default() -> i32 {
+        20
+    }
+
+    fn scale_default() -> f64 {
+        0.25
+    }
+
+    /// Checks that the inset of a `PictureInPicture` layout fits the frame.
+    pub fn validate(&self) -> Result<()> {
+        if self.layout_type != LayoutType::PictureInPicture {
+            return Ok(());
+        }
+
+        if !(self.scale > 0.0 && self.scale <= 1.0) {
+            return Err(Error::InvalidLayout(format!(
+                "scale {} is not greater than 0 and at most 1",
+                self.scale
+            )));
+        }
+        if self.margin < 0 {
+            return Err(Error::InvalidLayout(format!(
+                "margin {} is negative",
+                self.margin
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The maximum number of inputs this layout has room for.
+    pub fn capacity(&self) -> usize {
+        match self.layout_type {
+            LayoutType::PictureInPicture => 2,
+            LayoutType::SideBySide => 2,
+            LayoutType::Grid2x2 => 4,
+            LayoutType::Grid3x3 => 9,
+            LayoutType::SpeakerFilmstrip => 1 + FILMSTRIP_SIZE as usize,
+        }
+    }
+
+    /// Computes the geometry of `count` inputs on a frame of `width` by `height` pixels. Inputs
+    /// are ordered by importance: the first input is the main input of a `PictureInPicture` or
+    /// `SpeakerFilmstrip` layout, and grids are filled row by row.
+    pub fn geometry(&self, count: usize, width: i32, height: i32) -> Vec<Geometry> {
+        let count = count.min(self.capacity());
+
+        match self.layout_type {
+            LayoutType::PictureInPicture => {
+                let inset_width = (width as f64 * self.scale).round() as i32;
+                let inset_height = (height as f64 * self.scale).round() as i32;
+                let xpos = match self.corner {
+                    Corner::TopLeft | Corner::BottomLeft => self.margin,
+                    Corner::TopRight | Corner::BottomRight => width - inset_width - self.margin,
+                };
+                let ypos = match self.corner {
+                    Corner::TopLeft | Corner::TopRight => self.margin,
+                    Corner::BottomLeft | Corner::BottomRight => height - inset_height - self.margin,
+                };
+
+                let main = Geometry {
+                    xpos: 0,
+                    ypos: 0,
+                    width,
+                    height,
+                };
+                let inset = Geometry {
+                    xpos,
+                    ypos,
+                    width: inset_width,
+                    height: inset_height,
+                };
+                [main, inset].iter().take(count).copied().collect()
+            }
+            LayoutType::SideBySide => (0..count as i32)
+                .map(|i| Geometry {
+                    xpos: i * width / 2,
+                    ypos: height / 4,
+                    width: width / 2,
+                    height: height / 2,
+                })
+                .collect(),
+            LayoutType::Grid2x2 => grid(2, count, width, height),
+            LayoutType::Grid3x3 => grid(3, count, width, height),
+            LayoutType::SpeakerFilmstrip => {
+                let tile_width = width / FILMSTRIP_SIZE;
+                let tile_height = height / FILMSTRIP_SIZE;
+                let speaker_width = width - tile_width;
+                let speaker_height = height - tile_height;
+
+                let mut geometry = Vec::with_capacity(count);
+                if count > 0 {
+                    geometry.push(Geometry {
+                        xpos: (width - speaker_width) / 2,
+                        ypos: 0,
+                        width: speaker_width,
+                        height: speaker_height,
+                    });
+                }
+
+                // The filmstrip is centred below the speaker.
+                let tiles = count.saturating_sub(1) as i32;
+                let offset = (width - tiles * tile_width) / 2;
+                for i in 0..tiles {
+                    geometry.push(Geometry {
+                        xpos: offset + i * tile_width,
+                        ypos: speaker_height,
+                        width: tile_width,
+                        height: tile_height,
+                    });
+                }
+
+                geometry
+            }
+        }
+    }
+}
+
+fn grid(size: i32, count: usize, width: i32, height: i32) -> Vec<Geometry> {
+    let cell_width = width / size;
+    let cell_height = height / size;
+
+    (0..count as i32)
+        .map(|i| Geometry {
+            xpos: (i % size) * cell_width,
+            ypos: (i / size) * cell_height,
+            width: cell_width,
+            height: cell_height,
+        })
+        .collect()
+}
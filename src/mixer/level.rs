@@ -0,0 +1,100 @@
+use super::event::Source;
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Audio level reported by a `level` element, in dB with one value per channel.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct Level {
+    pub rms: Vec<f64>,
+    pub peak: Vec<f64>,
+    pub decay: Vec<f64>,
+}
+
+impl Level {
+    fn from_structure(structure: &gst::StructureRef) -> Option<Self> {
+        let channels = |field: &str| -> Option<Vec<f64>> {
+            let values = structure.get::<&gst::glib::ValueArray>(field).ok()?;
+            values.iter().map(|value| value.get::<f64>().ok()).collect()
+        };
+
+        Some(Self {
+            rms: channels("rms")?,
+            peak: channels("peak")?,
+            decay: channels("decay")?,
+        })
+    }
+}
+
+/// The latest audio levels of a mixer.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct Levels {
+    /// Level of the mix, after the master volume.
+    pub master: Option<Level>,
+    pub inputs: HashMap<String, Level>,
+}
+
+/// Collects the messages posted by the `level` elements of a mixer. Cheap to clone, every clone
+/// shares the same levels.
+#[derive(Clone)]
+pub struct Meter {
+    levels: Arc<Mutex<Levels>>,
+    master: gst::Element,
+    /// Set once the mixer stopped, after which no more levels arrive.
+    closed: Arc<AtomicBool>,
+}
+
+impl Meter {
+    pub(crate) fn new(master: gst::Element) -> Self {
+        Self {
+            levels: Arc::new(Mutex::new(Levels::default())),
+            master,
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns the latest levels.
+    pub fn snapshot(&self) -> Levels {
+        self.levels.lock().unwrap().clone()
+    }
+
+    /// Records the level message `structure` posted by `element`, which belongs to `source`.
+    /// Levels of elements that are neither the master meter nor part of an input are ignored.
+    pub(crate) fn record(&self, element: &gst::Object, source: Source, structure: &gst::StructureRef) {
+        let level = match Level::from_structure(structure) {
+            Some(level) => level,
+            None => return,
+        };
+
+        let mut levels = self.levels.lock().unwrap();
+        match source {
+            Source::Input(input) => {
+                levels.inputs.insert(input, level);
+            }
+            Source::Mixer if element == self.master.upcast_ref::<gst::Object>() => {
+                levels.master = Some(level);
+            }
+            _ => (),
+        }
+    }
+
+    pub(crate) fn remove(&self, input: &str) {
+        self.levels.lock().unwrap().inputs.remove(input);
+    }
+
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether the mixer of this meter has stopped.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
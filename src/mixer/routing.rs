@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// Decides when the audio of an input is on air.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, Default)]
+pub enum AudioMode {
+    /// On air while the input, or an input paired with it, is on program.
+    #[default]
+    FollowVideo,
+    /// Always on air, for example a music bed or a presenter microphone.
+    On,
+    /// Never on air.
+    Off,
+}
+
+/// Audio routing of a single input.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct AudioRoute {
+    #[serde(default)]
+    pub mode: AudioMode,
+    /// Input whose audio follows the video of this input instead of its own audio.
+    #[serde(default)]
+    pub audio_source: Option<String>,
+}
+
+/// The resulting audio routing of an input on a mixer.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct AudioStatus {
+    pub input: String,
+    #[serde(flatten)]
+    pub route: AudioRoute,
+    pub on_air: bool,
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Compositor and volume properties of a single input within a [`Scene`](struct.Scene.html).
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SceneInput {
+    pub zorder: u32,
+    pub xpos: i32,
+    pub ypos: i32,
+    pub width: i32,
+    pub height: i32,
+    pub alpha: f64,
+    pub volume: f64,
+}
+
+/// A named set of per-input properties that can be recalled on a mixer in a single step.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Scene {
+    pub name: String,
+    pub inputs: HashMap<String, SceneInput>,
+}
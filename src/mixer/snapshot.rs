@@ -0,0 +1,139 @@
+use super::Error;
+use crate::{gst_create_element, Result};
+
+use gst::prelude::*;
+use gstreamer as gst;
+
+/// Widest snapshot that is encoded, in pixels.
+pub const MAX_WIDTH: i32 = 3840;
+
+/// Image formats snapshots are encoded to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+        }
+    }
+}
+
+/// Holds on to the latest frame of a video branch, so snapshots can be encoded from it on
+/// demand.
+pub(crate) struct Tap {
+    queue: gst::Element,
+    sink: gst::Element,
+}
+
+impl Tap {
+    /// Branches off `tee` into an appsink that only keeps the latest frame. Elements are named
+    /// after `prefix`.
+    pub(crate) fn attach(
+        pipeline: &gst::Pipeline,
+        tee: &gst::Element,
+        prefix: &str,
+    ) -> Result<Self> {
+        let queue = gst_create_element("queue", &format!("{}_snapshot_queue", prefix))?;
+        queue.set_property("max-size-buffers", 1u32);
+        queue.set_property_from_str("leaky", "downstream");
+
+        let sink = gst_create_element("appsink", &format!("{}_snapshot_sink", prefix))?;
+        sink.set_property("max-buffers", 1u32);
+        sink.set_property("drop", true);
+        sink.set_property("sync", false);
+        // Do not hold up state changes of the pipeline waiting for a frame.
+        sink.set_property("async", false);
+        sink.set_property("enable-last-sample", true);
+
+        pipeline.add_many([&queue, &sink])?;
+        let tap = Self { queue, sink };
+        if let Err(e) = tap.start(tee) {
+            // Whatever part of the tap got linked goes again, the error that matters is `e`.
+            let _ = tap.detach(pipeline);
+            return Err(e);
+        }
+
+        Ok(tap)
+    }
+
+    fn start(&self, tee: &gst::Element) -> Result<()> {
+        gst::Element::link_many([tee, &self.queue, &self.sink])?;
+        self.queue.sync_state_with_parent()?;
+        self.sink.sync_state_with_parent()?;
+        Ok(())
+    }
+
+    pub(crate) fn detach(&self, pipeline: &gst::Pipeline) -> Result<()> {
+        if let Some(tee_pad) = self.queue.static_pad("sink").and_then(|pad| pad.peer()) {
+            if let Some(tee) = tee_pad.parent_element() {
+                tee.release_request_pad(&tee_pad);
+            }
+        }
+
+        self.queue.set_state(gst::State::Null)?;
+        self.sink.set_state(gst::State::Null)?;
+        pipeline.remove_many([&self.queue, &self.sink])?;
+
+        Ok(())
+    }
+
+    /// The latest frame, `None` until one arrived.
+    pub(crate) fn sample(&self) -> Option<gst::Sample> {
+        self.sink.property::<Option<gst::Sample>>("last-sample")
+    }
+}
+
+/// Checks that a requested snapshot `width` is above 0.
+pub fn validate_width(width: Option<i32>) -> Result<()> {
+    match width {
+        Some(width) if width <= 0 => Err(Error::InvalidVideo(format!(
+            "snapshot width {} is not above 0",
+            width
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Encodes `sample` as `format`. When `width` is set the image is scaled to it, keeping the
+/// aspect ratio of the frame. Images are never scaled up, nor wider than [`MAX_WIDTH`]. Blocks
+/// until the image is encoded.
+pub fn encode(sample: &gst::Sample, format: ImageFormat, width: Option<i32>) -> Result<Vec<u8>> {
+    validate_width(width)?;
+
+    let mut caps = gst::Caps::builder(format.content_type());
+    if let Some(width) = width {
+        let structure = sample
+            .caps()
+            .and_then(|caps| caps.structure(0))
+            .ok_or_else(|| Error::Gstreamer("snapshot frame has no caps".to_string()))?;
+        let frame_width = structure
+            .get::<i32>("width")
+            .map_err(|e| Error::Gstreamer(e.to_string()))?;
+        let frame_height = structure
+            .get::<i32>("height")
+            .map_err(|e| Error::Gstreamer(e.to_string()))?;
+
+        let width = width.min(frame_width).min(MAX_WIDTH);
+        let height = (width as i64 * frame_height as i64 / frame_width.max(1) as i64).max(1);
+        caps = caps
+            .field("width", width)
+            .field("height", height as i32)
+            .field("pixel-aspect-ratio", gst::Fraction::new(1, 1));
+    }
+
+    let image = gst_video::convert_sample(sample, &caps.build(), gst::ClockTime::from_seconds(5))
+        .map_err(|e| Error::Gstreamer(e.to_string()))?;
+    let buffer = image
+        .buffer()
+        .ok_or_else(|| Error::Gstreamer("encoded snapshot is empty".to_string()))?;
+    let map = buffer
+        .map_readable()
+        .map_err(|e| Error::Gstreamer(e.to_string()))?;
+
+    Ok(map.as_slice().to_vec())
+}
@@ -0,0 +1,289 @@
+use super::event::{Source, Sources};
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Clock ticks per second of the times in `/proc/*/stat`. Fixed at 100 on every architecture
+/// Linux runs on.
+const USER_HZ: u64 = 100;
+
+/// How long frames are counted for before the frame rate is updated.
+const FPS_WINDOW: Duration = Duration::from_secs(1);
+
+/// Performance statistics of a mixer.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct Stats {
+    /// Minimum latency of the pipeline, `None` until it has been configured.
+    pub latency_ms: Option<f64>,
+    /// Frames per second leaving the program compositor, over the last second.
+    pub fps: f64,
+    /// Quality of service counters of every element that reported falling behind.
+    pub qos: Vec<Qos>,
+    pub queues: Vec<Queue>,
+    /// CPU time spent by the streaming threads of the mixer, including threads that exited.
+    /// Only time spent while a thread streams for the mixer is counted, and threads elements
+    /// start on their own, such as the worker threads of `x264enc`, are not counted at all.
+    pub cpu_time_ms: u64,
+    /// CPU time of every running streaming thread since it started streaming, by the element
+    /// that started it.
+    pub threads: Vec<Thread>,
+}
+
+/// Quality of service counters of a single element, from the QoS messages it posted.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Qos {
+    pub source: Source,
+    pub element: String,
+    /// Buffers that arrived too late, one for every QoS message.
+    pub late: u64,
+    /// Buffers dropped by the element, as last reported by it.
+    pub dropped: u64,
+    /// Buffers processed by the element, as last reported by it.
+    pub processed: u64,
+    /// How late the last late buffer was.
+    pub jitter_ms: f64,
+    pub proportion: f64,
+}
+
+/// Fill level of a `queue` or `queue2` element.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Queue {
+    pub source: Source,
+    pub element: String,
+    pub buffers: u64,
+    pub bytes: u64,
+    pub time_ms: u64,
+    /// The fullest of the three levels, as a fraction of its limit. Unlimited levels are ignored.
+    pub fill: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Thread {
+    pub element: String,
+    pub cpu_time_ms: u64,
+}
+
+/// A streaming thread of the mixer.
+struct StreamThread {
+    /// The element that started the thread.
+    element: String,
+    /// CPU time the thread had spent before it started streaming. Threads come from a pool and
+    /// may have run something else before.
+    entered_cpu_time_ms: u64,
+}
+
+impl StreamThread {
+    /// CPU time thread `tid` spent since it started streaming.
+    fn cpu_time_ms(&self, tid: u64) -> Option<u64> {
+        thread_cpu_time_ms(tid).map(|now| now.saturating_sub(self.entered_cpu_time_ms))
+    }
+}
+
+#[derive(Default)]
+struct Recorded {
+    qos: HashMap<String, Qos>,
+    frames: u64,
+    window_start: Option<Instant>,
+    fps: f64,
+    /// Running streaming threads by thread id.
+    threads: HashMap<u64, StreamThread>,
+    /// CPU time of streaming threads that already exited.
+    exited_cpu_time_ms: u64,
+}
+
+/// Collects the statistics of a mixer that can only be observed as they happen.
+#[derive(Clone, Default)]
+pub(crate) struct Recorder {
+    recorded: Arc<Mutex<Recorded>>,
+}
+
+impl Recorder {
+    /// Counts a frame leaving the program compositor.
+    pub(crate) fn frame(&self, now: Instant) {
+        let mut recorded = self.recorded.lock().unwrap();
+        recorded.frames += 1;
+        let start = *recorded.window_start.get_or_insert(now);
+        let elapsed = now.duration_since(start);
+        if elapsed >= FPS_WINDOW {
+            recorded.fps = recorded.frames as f64 / elapsed.as_secs_f64();
+            recorded.frames = 0;
+            recorded.window_start = Some(now);
+        }
+    }
+
+    pub(crate) fn qos(&self, source: Source, element: &str, qos: &gst::message::Qos) {
+        let (jitter, proportion, _) = qos.values();
+        let (processed, dropped) = qos.stats();
+
+        let mut recorded = self.recorded.lock().unwrap();
+        let entry = recorded
+            .qos
+            .entry(element.to_string())
+            .or_insert_with(|| Qos {
+                source,
+                element: element.to_string(),
+                late: 0,
+                dropped: 0,
+                processed: 0,
+                jitter_ms: 0.0,
+                proportion: 1.0,
+            });
+        entry.late += 1;
+        entry.dropped = dropped.value().max(0) as u64;
+        entry.processed = processed.value().max(0) as u64;
+        entry.jitter_ms = jitter as f64 / 1_000_000.0;
+        entry.proportion = proportion;
+    }
+
+    /// Records a stream status message. Has to be called from the thread that posted it, as
+    /// the thread is identified by the caller.
+    pub(crate) fn stream_status(&self, status: &gst::message::StreamStatus) {
+        let tid = match current_thread_id() {
+            Some(tid) => tid,
+            None => return,
+        };
+
+        let (status_type, owner) = status.get();
+        let mut recorded = self.recorded.lock().unwrap();
+        match status_type {
+            gst::StreamStatusType::Enter => {
+                let thread = StreamThread {
+                    element: owner.name().to_string(),
+                    entered_cpu_time_ms: thread_cpu_time_ms(tid).unwrap_or(0),
+                };
+                recorded.threads.insert(tid, thread);
+            }
+            gst::StreamStatusType::Leave => {
+                let exited = recorded
+                    .threads
+                    .remove(&tid)
+                    .and_then(|thread| thread.cpu_time_ms(tid));
+                recorded.exited_cpu_time_ms += exited.unwrap_or(0);
+            }
+            _ => (),
+        }
+    }
+
+    /// Forgets the QoS counters of the elements of `source`, once it was removed.
+    pub(crate) fn remove(&self, source: &Source) {
+        self.recorded
+            .lock()
+            .unwrap()
+            .qos
+            .retain(|_, qos| qos.source != *source);
+    }
+
+    /// Gathers the statistics of `pipeline`.
+    pub(crate) fn snapshot(&self, pipeline: &gst::Pipeline, sources: &Sources) -> Stats {
+        let mut query = gst::query::Latency::new();
+        let latency_ms = if pipeline.query(&mut query) {
+            let (_, min, _) = query.result();
+            Some(min.nseconds() as f64 / 1_000_000.0)
+        } else {
+            None
+        };
+
+        let mut queues: Vec<Queue> = pipeline
+            .iterate_recurse()
+            .into_iter()
+            .filter_map(|element| element.ok())
+            .filter(|element| {
+                element
+                    .factory()
+                    .is_some_and(|factory| ["queue", "queue2"].contains(&factory.name().as_str()))
+            })
+            .map(|element| queue(&element, sources))
+            .collect();
+        queues.sort_by(|a, b| a.element.cmp(&b.element));
+
+        let recorded = self.recorded.lock().unwrap();
+        let mut qos: Vec<Qos> = recorded.qos.values().cloned().collect();
+        qos.sort_by(|a, b| a.element.cmp(&b.element));
+
+        let mut threads: Vec<Thread> = recorded
+            .threads
+            .iter()
+            .filter_map(|(tid, thread)| {
+                thread.cpu_time_ms(*tid).map(|cpu_time_ms| Thread {
+                    element: thread.element.clone(),
+                    cpu_time_ms,
+                })
+            })
+            .collect();
+        threads.sort_by_key(|thread| std::cmp::Reverse(thread.cpu_time_ms));
+
+        Stats {
+            latency_ms,
+            fps: recorded.fps,
+            qos,
+            queues,
+            cpu_time_ms: recorded.exited_cpu_time_ms
+                + threads.iter().map(|t| t.cpu_time_ms).sum::<u64>(),
+            threads,
+        }
+    }
+}
+
+fn queue(element: &gst::Element, sources: &Sources) -> Queue {
+    let buffers = level(element, "current-level-buffers");
+    let bytes = level(element, "current-level-bytes");
+    let time = level(element, "current-level-time");
+    let fill = [
+        (buffers, level(element, "max-size-buffers")),
+        (bytes, level(element, "max-size-bytes")),
+        (time, level(element, "max-size-time")),
+    ]
+    .iter()
+    .filter(|(_, max)| *max > 0)
+    .map(|(current, max)| *current as f64 / *max as f64)
+    .fold(0.0, f64::max);
+
+    Queue {
+        source: sources.find(element.upcast_ref()),
+        element: element.name().to_string(),
+        buffers,
+        bytes,
+        time_ms: time / 1_000_000,
+        fill,
+    }
+}
+
+/// Reads an unsigned property of a queue, which `queue` and `queue2` do not agree on the
+/// width of.
+fn level(element: &gst::Element, property: &str) -> u64 {
+    let value = element.property_value(property);
+    value
+        .get::<u32>()
+        .map(u64::from)
+        .or_else(|_| value.get::<u64>())
+        .unwrap_or(0)
+}
+
+/// The kernel id of the calling thread.
+fn current_thread_id() -> Option<u64> {
+    std::fs::read_link("/proc/thread-self")
+        .ok()?
+        .file_name()?
+        .to_str()?
+        .parse()
+        .ok()
+}
+
+/// CPU time, user and system, spent by thread `tid` of this process.
+fn thread_cpu_time_ms(tid: u64) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/self/task/{}/stat", tid)).ok()?;
+    // The thread name may contain spaces and parentheses, the fields start after the last `)`.
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+
+    Some((utime + stime) * 1000 / USER_HZ)
+}
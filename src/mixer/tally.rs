@@ -0,0 +1,53 @@
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+
+/// Whether an input is on air, for "on air" lights and similar.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Tally {
+    pub input: String,
+    /// The input is on program.
+    pub program: bool,
+    /// The input is on preview.
+    pub preview: bool,
+    /// The input is not fully transparent and at least partly inside the program frame.
+    pub visible: bool,
+    /// The volume of the input is above zero and it is not muted.
+    pub audible: bool,
+}
+
+/// Whether the input linked to compositor sink `pad` shows up in a `width` by `height` frame.
+pub(crate) fn visible(pad: &gst::Pad, width: i32, height: i32) -> bool {
+    if pad.property::<f64>("alpha") <= 0.0 {
+        return false;
+    }
+
+    let xpos = pad.property::<i32>("xpos");
+    let ypos = pad.property::<i32>("ypos");
+    let (mut pad_width, mut pad_height) =
+        (pad.property::<i32>("width"), pad.property::<i32>("height"));
+    // A size of zero means the input is drawn at the size of its own frames.
+    if pad_width <= 0 || pad_height <= 0 {
+        let size = pad.current_caps().and_then(|caps| {
+            let structure = caps.structure(0)?;
+            Some((
+                structure.get::<i32>("width").ok()?,
+                structure.get::<i32>("height").ok()?,
+            ))
+        });
+        let (caps_width, caps_height) = size.unwrap_or((width, height));
+        if pad_width <= 0 {
+            pad_width = caps_width;
+        }
+        if pad_height <= 0 {
+            pad_height = caps_height;
+        }
+    }
+
+    xpos < width && ypos < height && xpos + pad_width > 0 && ypos + pad_height > 0
+}
+
+/// Whether the `volume` element of an input lets any audio through.
+pub(crate) fn audible(volume: &gst::Element) -> bool {
+    volume.property::<f64>("volume") > 0.0 && !volume.property::<bool>("mute")
+}
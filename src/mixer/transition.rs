@@ -0,0 +1,336 @@
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum TransitionType {
+    Cut,
+    Crossfade,
+    Wipe,
+    Slide,
+    Push,
+    Zoom,
+}
+
+/// The direction the incoming input travels in. `Left` enters from the right edge of the frame and
+/// moves left. Ignored by transitions that are not directional.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, Default)]
+pub enum Direction {
+    #[default]
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Transition {
+    pub transition_type: TransitionType,
+    #[serde(default = "Transition::duration_ms_default")]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+impl Transition {
+    pub(crate) fn duration_ms_default() -> u64 {
+        1000
+    }
+
+    pub fn cut() -> Self {
+        Self {
+            transition_type: TransitionType::Cut,
+            duration_ms: 0,
+            direction: Direction::default(),
+        }
+    }
+
+    /// Geometry (xpos, ypos, width, height) the incoming input starts the transition at. Every
+    /// transition ends with the incoming input covering the full frame.
+    fn incoming_start(&self, width: i32, height: i32) -> (i32, i32, i32, i32) {
+        use Direction::*;
+        use TransitionType::*;
+
+        // The compositor treats a width or height of 0 as "use the input size", so a collapsed
+        // pad is 1 pixel wide instead.
+        match (self.transition_type, self.direction) {
+            (Wipe, Left) => (width, 0, 1, height),
+            (Wipe, Right) => (0, 0, 1, height),
+            (Wipe, Up) => (0, height, width, 1),
+            (Wipe, Down) => (0, 0, width, 1),
+            (Slide, Left) | (Push, Left) => (width, 0, width, height),
+            (Slide, Right) | (Push, Right) => (-width, 0, width, height),
+            (Slide, Up) | (Push, Up) => (0, height, width, height),
+            (Slide, Down) | (Push, Down) => (0, -height, width, height),
+            (Zoom, _) => (width / 2, height / 2, 1, 1),
+            (Cut, _) | (Crossfade, _) => (0, 0, width, height),
+        }
+    }
+
+    /// Builds the tracks animating the compositor pad of the input being transitioned to.
+    ///
+    /// A wipe grows the pad from one edge like the other transitions, but crops the frame of the
+    /// input to the same part of the picture. The picture is revealed in place instead of being
+    /// squeezed into the pad.
+    pub(crate) fn incoming_tracks(
+        &self,
+        pad: &gst::Pad,
+        alpha: f64,
+        width: i32,
+        height: i32,
+    ) -> Vec<Track> {
+        if self.transition_type == TransitionType::Crossfade {
+            return vec![Track::new(pad, "alpha", 0.0, alpha)];
+        }
+
+        let (xpos, ypos, w, h) = self.incoming_start(width, height);
+        let mut tracks = vec![
+            Track::new(pad, "xpos", xpos as f64, 0.0),
+            Track::new(pad, "ypos", ypos as f64, 0.0),
+            Track::new(pad, "width", w as f64, width as f64),
+            Track::new(pad, "height", h as f64, height as f64),
+        ];
+
+        if self.transition_type == TransitionType::Wipe {
+            let (frame_width, frame_height) = frame_size(pad).unwrap_or((width, height));
+            let start = match self.direction {
+                Direction::Left | Direction::Right => w as f64 / width.max(1) as f64,
+                Direction::Up | Direction::Down => h as f64 / height.max(1) as f64,
+            };
+            tracks.push(Track::crop(
+                pad,
+                self.direction,
+                frame_width,
+                frame_height,
+                start,
+            ));
+        }
+
+        tracks
+    }
+
+    /// Builds the tracks animating the compositor pad of the input being transitioned away from.
+    /// Only `Push` moves the outgoing input, which is put back where it was once it is covered.
+    pub(crate) fn outgoing_tracks(&self, pad: &gst::Pad, width: i32, height: i32) -> Vec<Track> {
+        if self.transition_type != TransitionType::Push {
+            return Vec::new();
+        }
+
+        let (xpos, ypos, _, _) = self.incoming_start(width, height);
+        let from_x = pad.property::<i32>("xpos") as f64;
+        let from_y = pad.property::<i32>("ypos") as f64;
+        vec![
+            Track::new(pad, "xpos", from_x, from_x - xpos as f64).settle(from_x),
+            Track::new(pad, "ypos", from_y, from_y - ypos as f64).settle(from_y),
+        ]
+    }
+}
+
+/// Size of the frames arriving on compositor pad `pad`, once it has negotiated.
+fn frame_size(pad: &gst::Pad) -> Option<(i32, i32)> {
+    let caps = pad.current_caps()?;
+    let structure = caps.structure(0)?;
+    Some((
+        structure.get::<i32>("width").ok()?,
+        structure.get::<i32>("height").ok()?,
+    ))
+}
+
+/// A single object property that is animated from one value to another over the course of a
+/// transition. Integer properties (such as the compositor pad geometry) are rounded.
+pub(crate) struct Track {
+    object: gst::Object,
+    property: &'static str,
+    from: f64,
+    to: f64,
+    settle: Option<f64>,
+    kind: Kind,
+}
+
+enum Kind {
+    /// The value is set on the property as is.
+    Number,
+    /// The value is the fraction of a `width` by `height` frame that is shown, cropped from the
+    /// edge the frame enters from. It is set as the source rectangle of the `converter-config`
+    /// of a compositor pad.
+    Crop {
+        direction: Direction,
+        width: i32,
+        height: i32,
+    },
+}
+
+impl Track {
+    pub(crate) fn new(
+        object: &impl IsA<gst::Object>,
+        property: &'static str,
+        from: f64,
+        to: f64,
+    ) -> Self {
+        Self {
+            object: object.clone().upcast(),
+            property,
+            from,
+            to,
+            settle: None,
+            kind: Kind::Number,
+        }
+    }
+
+    /// Crops the frames of compositor pad `pad` from showing a `from` fraction of the frame to
+    /// showing all of it. `width` and `height` are the size of the frames arriving on the pad.
+    pub(crate) fn crop(
+        pad: &gst::Pad,
+        direction: Direction,
+        width: i32,
+        height: i32,
+        from: f64,
+    ) -> Self {
+        Self {
+            kind: Kind::Crop {
+                direction,
+                width,
+                height,
+            },
+            ..Self::new(pad, "converter-config", from, 1.0)
+        }
+    }
+
+    /// Sets the value the property is left at once the transition has completed, if it differs
+    /// from the value the animation ends on.
+    pub(crate) fn settle(mut self, value: f64) -> Self {
+        self.settle = Some(value);
+        self
+    }
+
+    fn apply(&self, progress: f64) {
+        self.set(self.from + (self.to - self.from) * progress);
+    }
+
+    /// Leaves the property where it is once the transition has completed.
+    fn finish(&self) {
+        self.set(self.settle.unwrap_or(self.to));
+    }
+
+    fn set(&self, value: f64) {
+        match self.kind {
+            Kind::Number => {
+                if self.object.property_type(self.property) == Some(i32::static_type()) {
+                    self.object.set_property(self.property, value.round() as i32);
+                } else {
+                    self.object.set_property(self.property, value);
+                }
+            }
+            Kind::Crop {
+                direction,
+                width,
+                height,
+            } => {
+                // Shown completely, so later frames of another size are not cropped.
+                if value >= 1.0 {
+                    self.object
+                        .set_property(self.property, None::<gst::Structure>);
+                    return;
+                }
+
+                let w = ((width as f64 * value).round() as i32).clamp(1, width.max(1));
+                let h = ((height as f64 * value).round() as i32).clamp(1, height.max(1));
+                let (x, y, w, h) = match direction {
+                    Direction::Left => (width - w, 0, w, height),
+                    Direction::Right => (0, 0, w, height),
+                    Direction::Up => (0, height - h, width, h),
+                    Direction::Down => (0, 0, width, h),
+                };
+                let config = gst::Structure::builder("GstVideoConverter")
+                    .field("src-x", x)
+                    .field("src-y", y)
+                    .field("src-width", w)
+                    .field("src-height", h)
+                    .build();
+                self.object.set_property(self.property, config);
+            }
+        }
+    }
+}
+
+/// Handle to a transition running in the background.
+pub(crate) struct Handle {
+    cancelled: Arc<AtomicBool>,
+    join_handle: std::thread::JoinHandle<()>,
+}
+
+impl Handle {
+    /// Stops the transition and waits for its thread to exit. Every property is left where it
+    /// would be once the transition completed, so an input is not left part way or displaced.
+    /// This blocks for at most a single frame.
+    pub(crate) fn cancel(self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        let _ = self.join_handle.join();
+    }
+}
+
+/// Animates `tracks` over `duration` on the pipeline `clock`. Steps are aligned to the frame
+/// boundaries of the pipeline (starting from `base_time`), so every frame the compositor outputs
+/// advances the transition by exactly one step. The final step always applies the end value of
+/// every track.
+pub(crate) fn start(
+    clock: gst::Clock,
+    base_time: Option<gst::ClockTime>,
+    duration: gst::ClockTime,
+    framerate: i32,
+    tracks: Vec<Track>,
+) -> Handle {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let cancel = cancelled.clone();
+    let frame_duration = (gst::ClockTime::SECOND / framerate.max(1) as u64).nseconds();
+
+    let join_handle = std::thread::spawn(move || {
+        let start = clock.time().unwrap_or(gst::ClockTime::ZERO).nseconds();
+        let base = base_time.map(|t| t.nseconds()).unwrap_or(start);
+        let mut next = base + (start.saturating_sub(base) / frame_duration + 1) * frame_duration;
+
+        for track in &tracks {
+            track.apply(0.0);
+        }
+
+        loop {
+            let _ = clock
+                .new_single_shot_id(gst::ClockTime::from_nseconds(next))
+                .wait();
+            if cancel.load(Ordering::SeqCst) {
+                for track in &tracks {
+                    track.finish();
+                }
+                return;
+            }
+
+            let progress = if duration.is_zero() {
+                1.0
+            } else {
+                ((next - start) as f64 / duration.nseconds() as f64).min(1.0)
+            };
+
+            for track in &tracks {
+                track.apply(progress);
+            }
+
+            if progress >= 1.0 {
+                for track in &tracks {
+                    track.finish();
+                }
+                return;
+            }
+
+            next += frame_duration;
+        }
+    });
+
+    Handle {
+        cancelled,
+        join_handle,
+    }
+}
@@ -1,21 +1,26 @@
 pub mod auto;
 pub mod fake;
 pub mod file;
+pub mod multiview;
 pub mod rtmp;
 
 use crate::mixer::Error as MixerError;
 use crate::Result;
-use crate::{AudioConfig, AudioEncoderConfig, Mux, VideoConfig, VideoEncoderConfig};
+use crate::{
+    AudioConfig, AudioEncoderConfig, Mux, VideoConfig, VideoEncoderConfig, VideoEncoderProfile,
+    VideoEncoderSpeed,
+};
 
 pub use auto::Auto;
 pub use fake::Fake;
 pub use file::File;
 use gst::prelude::*;
 use gstreamer as gst;
+pub use multiview::Multiview;
 pub use rtmp::RTMP;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Config {
     pub name: String,
     pub video: VideoConfig,
@@ -24,9 +29,26 @@ pub struct Config {
     pub encoder: EncoderConfig,
     #[serde(default)]
     pub mux: Option<Mux>,
+    #[serde(default)]
+    pub bus: Bus,
+    /// Keep the resolution and framerate of `video` when the mixer changes its own, instead of
+    /// following the mixer.
+    #[serde(default)]
+    pub fixed_caps: bool,
+    /// Where a `Multiview` output sends its grid, unused by the other outputs.
+    #[serde(default)]
+    pub sink: Option<multiview::Sink>,
+}
+
+/// The mixer bus an output receives video from.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+pub enum Bus {
+    #[default]
+    Program,
+    Preview,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct EncoderConfig {
     #[serde(default)]
     pub audio: AudioEncoderConfig,
@@ -48,6 +70,7 @@ pub enum Output {
     Auto(Auto),
     Fake(Fake),
     File(File),
+    Multiview(Multiview),
 }
 
 impl Output {
@@ -67,12 +90,17 @@ impl Output {
         File::create(config, location).map(Self::File)
     }
 
+    pub fn create_multiview(config: Config) -> Result<Self> {
+        Multiview::create(config).map(Self::Multiview)
+    }
+
     pub fn name(&self) -> String {
         match self {
             Output::RTMP(output) => output.name(),
             Output::Auto(output) => output.name(),
             Output::Fake(output) => output.name(),
             Output::File(output) => output.name(),
+            Output::Multiview(output) => output.name(),
         }
     }
 
@@ -82,6 +110,17 @@ impl Output {
             Output::Auto(_) => "Auto".to_string(),
             Output::Fake(_) => "Fake".to_string(),
             Output::File(_) => "File".to_string(),
+            Output::Multiview(_) => "Multiview".to_string(),
+        }
+    }
+
+    pub fn config(&self) -> Config {
+        match self {
+            Output::RTMP(output) => output.config(),
+            Output::Auto(output) => output.config(),
+            Output::Fake(output) => output.config(),
+            Output::File(output) => output.config(),
+            Output::Multiview(output) => output.config(),
         }
     }
 
@@ -90,7 +129,8 @@ impl Output {
             Output::RTMP(output) => output.location.clone(),
             Output::Auto(_) => "".to_string(),
             Output::Fake(_) => "".to_string(),
-            Output::File(_) => "".to_string(),
+            Output::File(output) => output.location.clone(),
+            Output::Multiview(output) => output.location(),
         }
     }
 
@@ -105,6 +145,18 @@ impl Output {
             Output::Auto(output) => output.link(pipeline, audio, video),
             Output::Fake(output) => output.link(pipeline, audio, video),
             Output::File(output) => output.link(pipeline, audio, video),
+            Output::Multiview(output) => output.link(pipeline, audio, video),
+        }
+    }
+
+    /// Follows a change of the video format of the mixer, unless the output has fixed caps.
+    pub fn set_video(&mut self, video: &VideoConfig) -> Result<()> {
+        match self {
+            Output::RTMP(output) => output.set_video(video),
+            Output::Auto(output) => output.set_video(video),
+            Output::Fake(output) => output.set_video(video),
+            Output::File(output) => output.set_video(video),
+            Output::Multiview(output) => output.set_video(video),
         }
     }
 
@@ -114,6 +166,7 @@ impl Output {
             Output::Auto(output) => output.unlink(),
             Output::Fake(output) => output.unlink(),
             Output::File(output) => output.unlink(),
+            Output::Multiview(output) => output.unlink(),
         }
     }
 
@@ -123,10 +176,45 @@ impl Output {
             Output::Auto(output) => output.set_state(state),
             Output::Fake(output) => output.set_state(state),
             Output::File(output) => output.set_state(state),
+            Output::Multiview(output) => output.set_state(state),
         }
     }
 }
 
+/// Raw video caps an encoding output converts the mixer video to. Only outputs with fixed caps
+/// pin the resolution, the others take whatever the mixer produces.
+fn video_caps(config: &Config) -> gst::Caps {
+    let mut caps = gst::Caps::builder("video/x-raw")
+        .field("framerate", gst::Fraction::new(config.video.framerate, 1))
+        .field("format", config.video.format.to_string())
+        .field(
+            "profile",
+            config
+                .encoder
+                .video
+                .profile
+                .clone()
+                .unwrap_or(VideoEncoderProfile::High)
+                .to_string(),
+        )
+        .field(
+            "speed",
+            config
+                .encoder
+                .video
+                .speed
+                .clone()
+                .unwrap_or(VideoEncoderSpeed::None)
+                .to_string(),
+        );
+    if config.fixed_caps {
+        caps = caps
+            .field("width", config.video.width)
+            .field("height", config.video.height);
+    }
+    caps.build()
+}
+
 fn release_request_pad(elem: &gst::Element) -> Result<()> {
     let pad = elem.static_pad("sink").ok_or_else(|| {
         MixerError::Gstreamer("Failed to get static sink pad for element".to_string())
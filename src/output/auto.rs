@@ -1,11 +1,12 @@
 use super::Config;
 use crate::gst_create_element;
-use crate::Result;
+use crate::{Result, VideoConfig};
 use gst::prelude::*;
 use gstreamer as gst;
 
 pub struct Auto {
     pub name: String,
+    config: Config,
     pipeline: Option<gst::Pipeline>,
     audioqueue: gst::Element,
     videoqueue: gst::Element,
@@ -61,6 +62,7 @@ impl Auto {
 
         Ok(Self {
             name: name.to_string(),
+            config: config.clone(),
             pipeline: None,
             audioqueue,
             audiosink,
@@ -78,6 +80,10 @@ impl Auto {
         self.name.clone()
     }
 
+    pub fn config(&self) -> Config {
+        self.config.clone()
+    }
+
     pub fn link(
         &mut self,
         pipeline: gst::Pipeline,
@@ -146,4 +152,8 @@ impl Auto {
         self.audiosink.set_state(state)?;
         Ok(())
     }
+
+    pub fn set_video(&mut self, _video: &VideoConfig) -> Result<()> {
+        Ok(())
+    }
 }
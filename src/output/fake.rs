@@ -1,11 +1,12 @@
 use super::Config;
 use crate::gst_create_element;
-use crate::Result;
+use crate::{Result, VideoConfig};
 use gst::prelude::*;
 use gstreamer as gst;
 
 pub struct Fake {
     pub name: String,
+    config: Config,
     pipeline: Option<gst::Pipeline>,
     audio: gst::Element,
     video: gst::Element,
@@ -19,6 +20,7 @@ impl Fake {
 
         Ok(Fake {
             name: name.to_string(),
+            config: config.clone(),
             pipeline: None,
             audio,
             video,
@@ -29,6 +31,10 @@ impl Fake {
         self.name.clone()
     }
 
+    pub fn config(&self) -> Config {
+        self.config.clone()
+    }
+
     pub fn link(
         &mut self,
         pipeline: gst::Pipeline,
@@ -60,4 +66,8 @@ impl Fake {
         self.video.set_state(state)?;
         Ok(())
     }
+
+    pub fn set_video(&mut self, _video: &VideoConfig) -> Result<()> {
+        Ok(())
+    }
 }
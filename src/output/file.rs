@@ -1,11 +1,12 @@
 use super::Config;
-use crate::{gst_create_element, Result, VideoEncoder, VideoEncoderProfile, VideoEncoderSpeed};
+use crate::{gst_create_element, Result, VideoConfig, VideoEncoder};
 use gst::prelude::*;
 use gstreamer as gst;
 
 pub struct File {
     pub name: String,
     pub location: String,
+    config: Config,
     pipeline: Option<gst::Pipeline>,
     video_queue: gst::Element,
     video_convert: gst::Element,
@@ -27,7 +28,7 @@ pub struct File {
 
 impl File {
     pub fn create(config: Config, location: &str) -> Result<Self> {
-        let Config { name, .. } = config;
+        let name = config.name.clone();
         // Video stream
         let video_queue = gst_create_element("queue", &format!("output_{}_video_queue", name))?;
 
@@ -39,29 +40,7 @@ impl File {
         let video_capsfilter =
             gst_create_element("capsfilter", &format!("output_{}_video_capsfilter", name))?;
 
-        let video_caps = gst::Caps::builder("video/x-raw")
-            .field("framerate", &gst::Fraction::new(config.video.framerate, 1))
-            .field("format", &config.video.format.to_string())
-            .field(
-                "profile",
-                &config
-                    .encoder
-                    .video
-                    .profile
-                    .unwrap_or(VideoEncoderProfile::High)
-                    .to_string(),
-            )
-            .field(
-                "speed",
-                &config
-                    .encoder
-                    .video
-                    .speed
-                    .unwrap_or(VideoEncoderSpeed::None)
-                    .to_string(),
-            )
-            .build();
-        video_capsfilter.set_property("caps", &video_caps);
+        video_capsfilter.set_property("caps", super::video_caps(&config));
 
         let video_encoder = gst_create_element(
             &config.encoder.video.encoder.to_string(),
@@ -98,6 +77,7 @@ impl File {
         Ok(Self {
             name,
             location: location.to_string(),
+            config: config.clone(),
             pipeline: None,
             video_queue,
             video_convert,
@@ -121,6 +101,10 @@ impl File {
         self.name.clone()
     }
 
+    pub fn config(&self) -> Config {
+        self.config.clone()
+    }
+
     pub fn link(
         &mut self,
         pipeline: gst::Pipeline,
@@ -245,4 +229,17 @@ impl File {
         self.audioenc.set_state(state)?;
         Ok(())
     }
+
+    pub fn set_video(&mut self, video: &VideoConfig) -> Result<()> {
+        if self.config.fixed_caps {
+            return Ok(());
+        }
+
+        self.config.video.framerate = video.framerate;
+        self.config.video.width = video.width;
+        self.config.video.height = video.height;
+        self.video_capsfilter
+            .set_property("caps", super::video_caps(&self.config));
+        Ok(())
+    }
 }
@@ -0,0 +1,415 @@
+use super::{Config, Output};
+use crate::mixer::Error as MixerError;
+use crate::{gst_create_element, Result, VideoConfig};
+use gst::prelude::*;
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Thickness in pixels of the tally border drawn around every tile.
+const BORDER: i32 = 4;
+
+/// The output a `Multiview` sends its grid to.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(tag = "output_type")]
+pub enum Sink {
+    RTMP {
+        location: String,
+    },
+    File {
+        location: String,
+    },
+    #[default]
+    Auto,
+    Fake,
+}
+
+/// Tally state of a tile, shown as the colour of its border.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tally {
+    Program,
+    Preview,
+    Off,
+}
+
+impl Tally {
+    fn fill(&self) -> &'static str {
+        match self {
+            Tally::Program => "red",
+            Tally::Preview => "green",
+            Tally::Off => "black",
+        }
+    }
+}
+
+/// A labeled, bordered cell of the grid, fed from a video tee of the mixer.
+struct Tile {
+    queue: gst::Element,
+    convert: gst::Element,
+    scale: gst::Element,
+    capsfilter: gst::Element,
+    label: gst::Element,
+    border: gst::Element,
+}
+
+impl Tile {
+    fn create(prefix: &str, text: &str) -> Result<Self> {
+        let queue = gst_create_element("queue", &format!("{}_queue", prefix))?;
+        // A slow grid must never hold up the feed it monitors.
+        queue.set_property("max-size-buffers", 2u32);
+        queue.set_property_from_str("leaky", "downstream");
+        let convert = gst_create_element("videoconvert", &format!("{}_convert", prefix))?;
+        let scale = gst_create_element("videoscale", &format!("{}_scale", prefix))?;
+        // Letterbox the frame in its cell instead of stretching it to the cell.
+        scale.set_property("add-borders", true);
+        let capsfilter = gst_create_element("capsfilter", &format!("{}_capsfilter", prefix))?;
+
+        let label = gst_create_element("textoverlay", &format!("{}_label", prefix))?;
+        label.set_property("text", text);
+        label.set_property("font-desc", "Sans Bold 12");
+        label.set_property("shaded-background", true);
+        label.set_property_from_str("valignment", "bottom");
+        label.set_property_from_str("halignment", "left");
+
+        // Negative borders grow the frame, leaving room for the tally colour around it.
+        let border = gst_create_element("videobox", &format!("{}_border", prefix))?;
+        for side in ["left", "right", "top", "bottom"] {
+            border.set_property(side, -BORDER);
+        }
+        border.set_property_from_str("fill", Tally::Off.fill());
+
+        Ok(Self {
+            queue,
+            convert,
+            scale,
+            capsfilter,
+            label,
+            border,
+        })
+    }
+
+    fn elements(&self) -> [&gst::Element; 6] {
+        [
+            &self.queue,
+            &self.convert,
+            &self.scale,
+            &self.capsfilter,
+            &self.label,
+            &self.border,
+        ]
+    }
+
+    /// Adds the tile to `pipeline`, between `tee` and `compositor`.
+    fn link(
+        &self,
+        pipeline: &gst::Pipeline,
+        tee: &gst::Element,
+        compositor: &gst::Element,
+    ) -> Result<()> {
+        pipeline.add_many(self.elements())?;
+        gst::Element::link_many(self.elements())?;
+        self.border.link(compositor)?;
+        for element in self.elements() {
+            element.sync_state_with_parent()?;
+        }
+        // Link the tee last, so no frame reaches the tile before it is running.
+        tee.link(&self.queue)?;
+
+        Ok(())
+    }
+
+    fn unlink(&self, pipeline: &gst::Pipeline) -> Result<()> {
+        super::release_request_pad(&self.queue)?;
+        if let Some(pad) = self.border.static_pad("src").and_then(|pad| pad.peer()) {
+            if let Some(compositor) = pad.parent_element() {
+                compositor.release_request_pad(&pad);
+            }
+        }
+
+        for element in self.elements() {
+            element.set_state(gst::State::Null)?;
+        }
+        pipeline.remove_many(self.elements())?;
+
+        Ok(())
+    }
+
+    /// Moves the tile to the cell at `x`, `y` of `width` by `height`, border included. Frames
+    /// keep their aspect ratio, the rest of the cell is filled black.
+    fn place(&self, x: i32, y: i32, width: i32, height: i32) -> Result<()> {
+        let caps = gst::Caps::builder("video/x-raw")
+            .field("width", (width - 2 * BORDER).max(2))
+            .field("height", (height - 2 * BORDER).max(2))
+            .field("pixel-aspect-ratio", gst::Fraction::new(1, 1))
+            .build();
+        self.capsfilter.set_property("caps", caps);
+
+        let pad = self
+            .border
+            .static_pad("src")
+            .and_then(|pad| pad.peer())
+            .ok_or_else(|| MixerError::Gstreamer("multiview tile is not linked".to_string()))?;
+        pad.set_property("xpos", x);
+        pad.set_property("ypos", y);
+
+        Ok(())
+    }
+
+    fn set_tally(&self, tally: Tally) {
+        self.border.set_property_from_str("fill", tally.fill());
+    }
+}
+
+/// Monitors a mixer: program and preview side by side on top, every input in a grid below,
+/// each labeled with its name and framed in its tally colour. The grid is sent on to a regular
+/// output, see [`Sink`](enum.Sink.html).
+pub struct Multiview {
+    pub name: String,
+    config: Config,
+    pipeline: Option<gst::Pipeline>,
+    compositor: gst::Element,
+    capsfilter: gst::Element,
+    queue: gst::Element,
+    tee: gst::Element,
+    sink: Box<Output>,
+    program: Option<Tile>,
+    preview: Option<Tile>,
+    inputs: BTreeMap<String, Tile>,
+}
+
+impl Multiview {
+    pub fn create(config: Config) -> Result<Self> {
+        let name = &config.name;
+        let compositor = gst_create_element(
+            "compositor",
+            &format!("output_{}_multiview_compositor", name),
+        )?;
+        compositor.set_property_from_str("background", "black");
+        let capsfilter = gst_create_element(
+            "capsfilter",
+            &format!("output_{}_multiview_capsfilter", name),
+        )?;
+        capsfilter.set_property("caps", grid_caps(&config.video));
+        let queue = gst_create_element("queue", &format!("output_{}_multiview_queue", name))?;
+        let tee = gst_create_element("tee", &format!("output_{}_multiview_tee", name))?;
+        tee.set_property("allow-not-linked", true);
+
+        let sink_config = Config {
+            sink: None,
+            ..config.clone()
+        };
+        let sink = match config.sink.clone().unwrap_or_default() {
+            Sink::RTMP { location } => Output::create_rtmp(sink_config, &location)?,
+            Sink::File { location } => Output::create_file(sink_config, &location)?,
+            Sink::Auto => Output::create_auto(sink_config)?,
+            Sink::Fake => Output::create_fake(sink_config)?,
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            config,
+            pipeline: None,
+            compositor,
+            capsfilter,
+            queue,
+            tee,
+            sink: Box::new(sink),
+            program: None,
+            preview: None,
+            inputs: BTreeMap::new(),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    pub fn location(&self) -> String {
+        self.sink.location()
+    }
+
+    /// Links the grid to its sink. `video` is unused, the tiles are fed through
+    /// [`tiles_link`](#method.tiles_link) and [`input_add`](#method.input_add) instead.
+    pub fn link(
+        &mut self,
+        pipeline: gst::Pipeline,
+        audio: gst::Element,
+        _video: gst::Element,
+    ) -> Result<()> {
+        pipeline.add_many([&self.compositor, &self.capsfilter, &self.queue, &self.tee])?;
+        gst::Element::link_many([&self.compositor, &self.capsfilter, &self.queue, &self.tee])?;
+        self.sink.link(pipeline.clone(), audio, self.tee.clone())?;
+
+        self.pipeline = Some(pipeline);
+
+        Ok(())
+    }
+
+    /// Adds the program and preview tiles, fed from the `program` and `preview` tees of the
+    /// mixer.
+    pub fn tiles_link(&mut self, program: &gst::Element, preview: &gst::Element) -> Result<()> {
+        let pipeline = self.linked_pipeline()?;
+
+        let tile = Tile::create(&format!("output_{}_tile_program", self.name), "Program")?;
+        tile.link(&pipeline, program, &self.compositor)?;
+        tile.set_tally(Tally::Program);
+        self.program = Some(tile);
+
+        let tile = Tile::create(&format!("output_{}_tile_preview", self.name), "Preview")?;
+        tile.link(&pipeline, preview, &self.compositor)?;
+        tile.set_tally(Tally::Preview);
+        self.preview = Some(tile);
+
+        self.layout()
+    }
+
+    /// Adds a tile for `input`, fed from its video `tee`.
+    pub fn input_add(&mut self, input: &str, tee: &gst::Element) -> Result<()> {
+        if self.inputs.contains_key(input) {
+            return Ok(());
+        }
+
+        let pipeline = self.linked_pipeline()?;
+        let tile = Tile::create(&format!("output_{}_tile_input_{}", self.name, input), input)?;
+        if let Err(e) = tile.link(&pipeline, tee, &self.compositor) {
+            // Whatever part of the tile got linked goes again, the error that matters is `e`.
+            let _ = tile.unlink(&pipeline);
+            return Err(e);
+        }
+        self.inputs.insert(input.to_string(), tile);
+
+        self.layout()
+    }
+
+    pub fn input_remove(&mut self, input: &str) -> Result<()> {
+        if let Some(tile) = self.inputs.remove(input) {
+            tile.unlink(&self.linked_pipeline()?)?;
+            self.layout()?;
+        }
+
+        Ok(())
+    }
+
+    /// Frames the input on program in red and the one on preview in green.
+    pub fn set_tally(&self, program: Option<&str>, preview: Option<&str>) {
+        for (name, tile) in &self.inputs {
+            let tally = if program == Some(name.as_str()) {
+                Tally::Program
+            } else if preview == Some(name.as_str()) {
+                Tally::Preview
+            } else {
+                Tally::Off
+            };
+            tile.set_tally(tally);
+        }
+    }
+
+    pub fn unlink(&self) -> Result<()> {
+        if let Some(pipeline) = self.pipeline.as_ref() {
+            for tile in self
+                .program
+                .iter()
+                .chain(self.preview.iter())
+                .chain(self.inputs.values())
+            {
+                tile.unlink(pipeline)?;
+            }
+        }
+
+        self.sink.unlink()?;
+
+        if let Some(pipeline) = self.pipeline.as_ref() {
+            pipeline.remove_many([&self.compositor, &self.capsfilter, &self.queue, &self.tee])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_state(&mut self, state: gst::State) -> Result<()> {
+        for tile in self
+            .program
+            .iter()
+            .chain(self.preview.iter())
+            .chain(self.inputs.values())
+        {
+            for element in tile.elements() {
+                element.set_state(state)?;
+            }
+        }
+        self.compositor.set_state(state)?;
+        self.capsfilter.set_state(state)?;
+        self.queue.set_state(state)?;
+        self.tee.set_state(state)?;
+        self.sink.set_state(state)?;
+        Ok(())
+    }
+
+    pub fn set_video(&mut self, video: &VideoConfig) -> Result<()> {
+        self.sink.set_video(video)?;
+        if self.config.fixed_caps {
+            return Ok(());
+        }
+
+        self.config.video.framerate = video.framerate;
+        self.config.video.width = video.width;
+        self.config.video.height = video.height;
+        self.capsfilter
+            .set_property("caps", grid_caps(&self.config.video));
+        self.layout()
+    }
+
+    fn linked_pipeline(&self) -> Result<gst::Pipeline> {
+        self.pipeline
+            .clone()
+            .ok_or_else(|| MixerError::Gstreamer("multiview is not linked".to_string()))
+    }
+
+    /// Places program and preview across the top half and the inputs, sorted by name, in a
+    /// grid across the bottom half.
+    fn layout(&self) -> Result<()> {
+        let width = self.config.video.width;
+        let height = self.config.video.height;
+        let half = height / 2;
+
+        if let Some(tile) = self.program.as_ref() {
+            tile.place(0, 0, width / 2, half)?;
+        }
+        if let Some(tile) = self.preview.as_ref() {
+            tile.place(width / 2, 0, width - width / 2, half)?;
+        }
+
+        let count = self.inputs.len();
+        if count == 0 {
+            return Ok(());
+        }
+        let columns = (count as f64).sqrt().ceil() as usize;
+        let rows = count.div_ceil(columns);
+        let cell_width = width / columns as i32;
+        let cell_height = (height - half) / rows as i32;
+        for (i, tile) in self.inputs.values().enumerate() {
+            let column = (i % columns) as i32;
+            let row = (i / columns) as i32;
+            tile.place(
+                column * cell_width,
+                half + row * cell_height,
+                cell_width,
+                cell_height,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Raw video caps of the grid.
+fn grid_caps(video: &VideoConfig) -> gst::Caps {
+    gst::Caps::builder("video/x-raw")
+        .field("framerate", gst::Fraction::new(video.framerate, 1))
+        .field("width", video.width)
+        .field("height", video.height)
+        .build()
+}
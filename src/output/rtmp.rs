@@ -1,11 +1,12 @@
 use super::Config;
-use crate::{gst_create_element, Result, VideoEncoderProfile, VideoEncoderSpeed};
+use crate::{gst_create_element, Result, VideoConfig};
 use gst::prelude::*;
 use gstreamer as gst;
 
 pub struct RTMP {
     pub name: String,
     pub location: String,
+    config: Config,
     pipeline: Option<gst::Pipeline>,
     video_queue: gst::Element,
     video_convert: gst::Element,
@@ -40,29 +41,7 @@ impl RTMP {
         let video_capsfilter =
             gst_create_element("capsfilter", &format!("output_{}_video_capsfilter", name))?;
 
-        let video_caps = gst::Caps::builder("video/x-raw")
-            .field("framerate", &gst::Fraction::new(config.video.framerate, 1))
-            .field("format", &config.video.format.to_string())
-            .field(
-                "profile",
-                &config
-                    .encoder
-                    .video
-                    .profile
-                    .unwrap_or(VideoEncoderProfile::High)
-                    .to_string(),
-            )
-            .field(
-                "speed",
-                &config
-                    .encoder
-                    .video
-                    .speed
-                    .unwrap_or(VideoEncoderSpeed::None)
-                    .to_string(),
-            )
-            .build();
-        video_capsfilter.set_property("caps", &video_caps);
+        video_capsfilter.set_property("caps", super::video_caps(&config));
 
         let x264enc = gst_create_element(
             &config.encoder.video.encoder.to_string(),
@@ -92,6 +71,7 @@ impl RTMP {
         Ok(Self {
             name: name.to_string(),
             location: uri.to_string(),
+            config: config.clone(),
             pipeline: None,
             video_queue,
             video_convert,
@@ -115,6 +95,10 @@ impl RTMP {
         self.name.clone()
     }
 
+    pub fn config(&self) -> Config {
+        self.config.clone()
+    }
+
     pub fn link(
         &mut self,
         pipeline: gst::Pipeline,
@@ -222,4 +206,17 @@ impl RTMP {
         self.audioenc.set_state(state)?;
         Ok(())
     }
+
+    pub fn set_video(&mut self, video: &VideoConfig) -> Result<()> {
+        if self.config.fixed_caps {
+            return Ok(());
+        }
+
+        self.config.video.framerate = video.framerate;
+        self.config.video.width = video.width;
+        self.config.video.height = video.height;
+        self.video_capsfilter
+            .set_property("caps", super::video_caps(&self.config));
+        Ok(())
+    }
 }